@@ -1,80 +1,124 @@
-use std::time::Duration;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use winit::{
-    event::{ElementState, Event, KeyboardInput, WindowEvent},
+    event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
     event_loop::ControlFlow,
     window::Window,
 };
 
-use specs::{shrev::EventChannel, Dispatcher, DispatcherBuilder, World, WorldExt};
+use specs::{Dispatcher, DispatcherBuilder, WorldExt};
 
+#[cfg(feature = "audio")]
+use crate::audio::DeviceOutput;
+#[cfg(feature = "gamepad")]
+use crate::game::input::GamepadInputSource;
 use crate::{
+    assets::AssetSource,
     game::{
-        resource::{DeltaTime, GameWindowSize, KeyboardEvent, WindowEvent as GameWindowEvent},
-        system::{
-            AsteroidSpawnerSystem, BackgroundAnimatorSystem, EntityLifetimeSystem,
-            EntitySpinnerSystem, GameManagerSystem, ParticleSpawnerSystem,
-            PlayerBoundsEnforcerSystem, PlayerCollisionSystem, PlayerMovementSystem, RenderSystem,
-            RepairPackManagerSystem, VelocityApplicatorSystem,
-        },
+        input::{InputBindings, InputSource, WinitInputSource},
+        resource::{HighScores, KeyboardEvent, MouseEvent, QuitRequested, ScreenshotRequest},
+        system::{BackgroundAnimatorSystem, RenderSystem},
     },
-    renderer::Renderer,
+    renderer::{FrameMetrics, Renderer},
+    replay::Replay,
+    settings::{Settings, SettingsOverrides},
+    simulation::Simulation,
 };
 
+// Quicksaves go to the working directory, there's just the one slot
+const QUICKSAVE_PATH: &str = "quicksave.ron";
+const QUICKSAVE_KEY: VirtualKeyCode = VirtualKeyCode::F5;
+// High scores are kept next to the quicksave
+const HIGH_SCORES_PATH: &str = "highscores.ron";
+const QUICKLOAD_KEY: VirtualKeyCode = VirtualKeyCode::F9;
+const SCREENSHOT_KEY: VirtualKeyCode = VirtualKeyCode::F12;
+const FRAME_STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct AppOptions {
+    // As loaded from the settings file
+    pub settings: Settings,
+    pub overrides: SettingsOverrides,
+    // Where changes from the settings screen are saved, they only last the session without it
+    pub settings_path: Option<PathBuf>,
+    pub assets: AssetSource,
+    // User key bindings
+    pub input_config: PathBuf,
+    pub seed: Option<u64>,
+    pub tick_rate: u32,
+    // Where to write the replay of this session on exit
+    pub record: Option<PathBuf>,
+    // Replay to play back instead of taking input
+    pub replay: Option<Replay>,
+    pub frame_stats: bool,
+}
+
+impl Default for AppOptions {
+    fn default() -> Self {
+        Self {
+            settings: Settings::default(),
+            overrides: SettingsOverrides::default(),
+            settings_path: None,
+            assets: AssetSource::embedded(),
+            input_config: PathBuf::from("input.ron"),
+            seed: None,
+            tick_rate: 60,
+            record: None,
+            replay: None,
+            frame_stats: false,
+        }
+    }
+}
+
 pub struct App<'a> {
-    world: World,
-    update_dispatcher: Dispatcher<'a, 'a>,
+    simulation: Simulation<'a>,
     render_dispatcher: Dispatcher<'a, 'a>,
     close_requested: bool,
+    record_path: Option<PathBuf>,
+    playing_replay: bool,
+    // When frame stats were last printed, if they are printed at all
+    frame_stats_printed: Option<Instant>,
+    settings_path: Option<PathBuf>,
+    // What goes in the settings file, without the session's overrides
+    saved_settings: Settings,
+    // What the window was last updated to, overrides included
+    applied_settings: Settings,
 }
 
 impl<'a> App<'_> {
-    pub fn new(window: &Window) -> Self {
+    pub fn new(window: &Window, options: AppOptions) -> Result<Self, Box<dyn Error>> {
         let size = window.inner_size();
-        let mut world = World::new();
-        let mut update_dispatcher = DispatcherBuilder::new()
-            .with(GameManagerSystem::default(), "game_manager_system", &[])
-            .with(
-                AsteroidSpawnerSystem::default(),
-                "asteroid_spawner_system",
-                &["game_manager_system"],
-            )
-            .with(
-                ParticleSpawnerSystem::default(),
-                "particle_spawner_system",
-                &[],
-            )
-            .with(
-                PlayerMovementSystem::default(),
-                "player_movement_system",
-                &["game_manager_system"],
-            )
-            .with(
-                PlayerCollisionSystem::default(),
-                "player_collision_system",
-                &["game_manager_system"],
-            )
-            .with(
-                VelocityApplicatorSystem::default(),
-                "velocity_applicator",
-                &[],
-            )
-            .with(PlayerBoundsEnforcerSystem::default(), "bounds_keeper", &[])
-            .with(
-                EntityLifetimeSystem::default(),
-                "entity_lifetime_system",
-                &[],
-            )
-            .with(EntitySpinnerSystem::default(), "entity_spinner_system", &[])
-            .with(
-                RepairPackManagerSystem::default(),
-                "repair_pack_manager_system",
-                &[],
-            )
-            .build();
+        let settings = options.overrides.apply(&options.settings);
+        #[allow(unused_mut)]
+        let mut input_sources: Vec<Box<dyn InputSource>> = vec![Box::new(WinitInputSource {})];
+        #[cfg(feature = "gamepad")]
+        if options.replay.is_none() {
+            match GamepadInputSource::new() {
+                Ok(gamepads) => input_sources.push(Box::new(gamepads)),
+                Err(err) => log::warn!("Gamepad support unavailable: {}", err),
+            }
+        }
+        let mut simulation = Simulation::new((size.width, size.height), input_sources);
+        #[cfg(feature = "audio")]
+        match DeviceOutput::new() {
+            Ok(output) => simulation.set_audio_output(output),
+            Err(err) => log::warn!("Sound unavailable: {}", err),
+        }
+
+        let sprite_atlas_bytes = options.assets.load("spritesheet.png")?;
+        let background_atlas_bytes = options.assets.load("backgrounds.png")?;
 
-        let sprite_atlas_bytes = include_bytes!("../assets/spritesheet.png");
-        let background_atlas_bytes = include_bytes!("../assets/backgrounds.png");
+        let renderer = pollster::block_on(Renderer::new(
+            window,
+            &sprite_atlas_bytes,
+            (64, 64),
+            &background_atlas_bytes,
+            (320, 200),
+            settings.vsync,
+        ))?;
 
         let mut render_dispatcher = DispatcherBuilder::new()
             .with(
@@ -83,33 +127,56 @@ impl<'a> App<'_> {
                 &[],
             )
             .with(
-                RenderSystem::new(pollster::block_on(Renderer::new(
-                    window,
-                    sprite_atlas_bytes,
-                    (64, 64),
-                    background_atlas_bytes,
-                    (320, 200),
-                ))),
+                RenderSystem::new(renderer),
                 "render_system",
                 &["background_animator_system"],
             )
             .build();
 
-        update_dispatcher.setup(&mut world);
-        render_dispatcher.setup(&mut world);
+        render_dispatcher.setup(simulation.world_mut());
 
-        {
-            // Setup for the first time, since render system is not called before all the updates
-            let mut game_window_size = world.write_resource::<GameWindowSize>();
-            *game_window_size = GameWindowSize(size.width, size.height);
+        simulation.load_assets(&options.assets)?;
+        // Keys rebound in the game win over the input config file, unless they're left without
+        // players the same way a broken input config would be
+        let bindings = match settings.bindings.clone() {
+            Some(bindings) if bindings.player_count() > 0 => bindings,
+            saved => {
+                if saved.is_some() {
+                    log::warn!(
+                        "Saved key bindings have no players, using {}",
+                        options.input_config.display()
+                    );
+                }
+                InputBindings::load_or_default(&options.input_config)
+            }
+        };
+        simulation.world_mut().insert(bindings);
+        simulation.world_mut().insert(settings.clone());
+        simulation
+            .world_mut()
+            .insert(HighScores::load_or_default(Path::new(HIGH_SCORES_PATH)));
+        if let Some(seed) = options.seed {
+            simulation.set_seed(seed);
         }
 
-        Self {
-            world: world,
-            update_dispatcher,
+        let playing_replay = options.replay.is_some();
+        if let Some(replay) = options.replay {
+            simulation.play_replay(replay);
+        } else if options.record.is_some() {
+            simulation.start_recording(options.tick_rate);
+        }
+
+        Ok(Self {
+            simulation,
             render_dispatcher,
             close_requested: false,
-        }
+            record_path: options.record,
+            playing_replay,
+            frame_stats_printed: options.frame_stats.then(Instant::now),
+            settings_path: options.settings_path,
+            saved_settings: options.settings,
+            applied_settings: settings,
+        })
     }
 
     pub fn on_update(&mut self, delta_time: Duration) -> Option<ControlFlow> {
@@ -117,40 +184,162 @@ impl<'a> App<'_> {
             return Some(ControlFlow::Exit);
         }
 
-        {
-            let mut delta = self.world.write_resource::<DeltaTime>();
-            *delta = DeltaTime(delta_time);
+        self.simulation.tick(delta_time);
+
+        if self.simulation.world().read_resource::<QuitRequested>().0 {
+            return Some(ControlFlow::Exit);
         }
+        None
+    }
 
-        self.update_dispatcher.dispatch(&mut self.world);
-        self.world.maintain();
+    // Carries changes from the settings screen over to the window and the settings file. The
+    // renderer picks up its own settings while drawing. Only what the player changed is saved,
+    // settings overridden for the session keep their value from the file.
+    pub fn apply_settings(&mut self, window: &Window) {
+        // A replay keeps the window it was recorded with and the player's settings alone
+        if self.playing_replay {
+            return;
+        }
+        let settings: Settings = (*self.simulation.world().read_resource::<Settings>()).clone();
+        if settings == self.applied_settings {
+            return;
+        }
 
-        None
+        let applied = &self.applied_settings;
+        if settings.window_mode != applied.window_mode || settings.resolution != applied.resolution
+        {
+            window.set_fullscreen(settings.fullscreen(window));
+            if window.fullscreen().is_none() {
+                window.set_inner_size(settings.window_size());
+            }
+        }
+        self.saved_settings.merge_changes(applied, &settings);
+        if let Some(path) = &self.settings_path {
+            if let Err(err) = self.saved_settings.save(path) {
+                log::error!("Could not save settings to {}: {}", path.display(), err);
+            }
+        }
+        self.applied_settings = settings;
     }
 
     pub fn on_render(&mut self, _interpolation: f64) {
         // @FIXME If render is too quick than we never update
-        self.render_dispatcher.dispatch(&mut self.world);
+        self.render_dispatcher.dispatch(self.simulation.world_mut());
+
+        if let Some(printed) = self.frame_stats_printed {
+            if printed.elapsed() >= FRAME_STATS_INTERVAL {
+                if let Some(summary) = self
+                    .simulation
+                    .world()
+                    .read_resource::<FrameMetrics>()
+                    .summary()
+                {
+                    println!("{}", summary);
+                }
+                self.frame_stats_printed = Some(Instant::now());
+            }
+        }
+    }
+
+    pub fn on_exit(&mut self) {
+        // Runs in a replay were already played once
+        if !self.playing_replay {
+            let result = self
+                .simulation
+                .world()
+                .read_resource::<HighScores>()
+                .save(Path::new(HIGH_SCORES_PATH));
+            if let Err(err) = result {
+                log::error!(
+                    "Could not save high scores to {}: {}",
+                    HIGH_SCORES_PATH,
+                    err
+                );
+            }
+        }
+
+        if let (Some(path), Some(replay)) = (&self.record_path, self.simulation.take_recording()) {
+            match replay.save(path) {
+                Ok(()) => log::info!("Saved replay to {}", path.display()),
+                Err(err) => log::error!("Could not save replay to {}: {}", path.display(), err),
+            }
+        }
+    }
+
+    pub fn quicksave(&mut self) {
+        let result = self
+            .simulation
+            .save()
+            .map_err(|err| err.to_string())
+            .and_then(|data| std::fs::write(QUICKSAVE_PATH, data).map_err(|err| err.to_string()));
+        match result {
+            Ok(()) => log::info!("Saved game to {}", QUICKSAVE_PATH),
+            Err(err) => log::error!("Could not save game to {}: {}", QUICKSAVE_PATH, err),
+        }
+    }
+
+    pub fn quickload(&mut self) {
+        // The replay would only have the input, not the jump back in time
+        if self.record_path.is_some() {
+            log::warn!("Quickload is disabled while recording a replay");
+            return;
+        }
+
+        let result = std::fs::read_to_string(QUICKSAVE_PATH)
+            .map_err(|err| err.to_string())
+            .and_then(|data| self.simulation.load(&data).map_err(|err| err.to_string()));
+        match result {
+            Ok(()) => log::info!("Loaded game from {}", QUICKSAVE_PATH),
+            Err(err) => log::error!("Could not load game from {}: {}", QUICKSAVE_PATH, err),
+        }
+    }
+
+    // Saves the next rendered frame as a PNG
+    pub fn screenshot(&mut self, path: PathBuf) {
+        self.simulation
+            .world_mut()
+            .insert(ScreenshotRequest(Some(path)));
     }
 
     pub fn on_event(&mut self, event: Event<()>) {
         match event {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => self.close_requested = true,
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(SCREENSHOT_KEY),
+                            ..
+                        },
+                    ..
+                } => self.screenshot(screenshot_path()),
+                // A replay brings its own input, the window is not resizable while it plays
+                _ if self.playing_replay => (),
                 WindowEvent::Resized(physical_size) => self
-                    .world
-                    .fetch_mut::<EventChannel<GameWindowEvent>>()
-                    .single_write(GameWindowEvent::Resize(
-                        physical_size.width,
-                        physical_size.height,
-                    )),
+                    .simulation
+                    .resize(physical_size.width, physical_size.height),
                 WindowEvent::ScaleFactorChanged { new_inner_size, .. } => self
-                    .world
-                    .fetch_mut::<EventChannel<GameWindowEvent>>()
-                    .single_write(GameWindowEvent::Resize(
-                        new_inner_size.width,
-                        new_inner_size.height,
-                    )),
+                    .simulation
+                    .resize(new_inner_size.width, new_inner_size.height),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(QUICKSAVE_KEY),
+                            ..
+                        },
+                    ..
+                } => self.quicksave(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(QUICKLOAD_KEY),
+                            ..
+                        },
+                    ..
+                } => self.quickload(),
                 WindowEvent::KeyboardInput {
                     input:
                         KeyboardInput {
@@ -165,9 +354,18 @@ impl<'a> App<'_> {
                         ElementState::Released => KeyboardEvent::Released(keycode),
                     };
 
-                    self.world
-                        .fetch_mut::<EventChannel<KeyboardEvent>>()
-                        .single_write(event)
+                    self.simulation.send_keyboard(event)
+                }
+                WindowEvent::CursorMoved { position, .. } => self
+                    .simulation
+                    .send_mouse(MouseEvent::Moved(position.x as f32, position.y as f32)),
+                WindowEvent::MouseInput { state, button, .. } => {
+                    let event = match state {
+                        ElementState::Pressed => MouseEvent::Pressed(button),
+                        ElementState::Released => MouseEvent::Released(button),
+                    };
+
+                    self.simulation.send_mouse(event)
                 }
                 _ => (),
             },
@@ -175,3 +373,11 @@ impl<'a> App<'_> {
         }
     }
 }
+
+// Screenshots go to the working directory, named by when they were taken
+fn screenshot_path() -> PathBuf {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    PathBuf::from(format!("screenshot-{}.png", since_epoch.as_millis()))
+}
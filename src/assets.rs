@@ -0,0 +1,102 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+// Where game data comes from. Files in an asset directory override the built-in copies one by
+// one, anything missing there falls back to what was compiled in.
+#[derive(Debug, Clone, Default)]
+pub struct AssetSource {
+    directory: Option<PathBuf>,
+}
+
+impl AssetSource {
+    pub fn embedded() -> Self {
+        Self { directory: None }
+    }
+
+    pub fn directory(path: &Path) -> Self {
+        Self {
+            directory: Some(path.to_path_buf()),
+        }
+    }
+
+    pub fn load(&self, name: &str) -> std::io::Result<Cow<'static, [u8]>> {
+        if let Some(directory) = &self.directory {
+            let path = directory.join(name);
+            if path.exists() {
+                return std::fs::read(path).map(Cow::Owned);
+            }
+        }
+
+        embedded(name).map(Cow::Borrowed).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no asset named {}", name),
+            )
+        })
+    }
+
+    pub fn load_string(&self, name: &str) -> std::io::Result<Cow<'static, str>> {
+        match self.load(name)? {
+            Cow::Borrowed(bytes) => std::str::from_utf8(bytes).map(Cow::Borrowed),
+            Cow::Owned(bytes) => String::from_utf8(bytes)
+                .map(Cow::Owned)
+                .map_err(|err| err.utf8_error()),
+        }
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+// For files the player owns rather than game data. Defaults if the file isn't there yet, also if
+// it doesn't parse, which gets logged.
+pub fn load_ron_or_default<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(_) => return T::default(),
+    };
+    match ron::from_str(&data) {
+        Ok(value) => value,
+        Err(err) => {
+            log::warn!("Failed to parse {}: {}", path.display(), err);
+            T::default()
+        }
+    }
+}
+
+fn embedded(name: &str) -> Option<&'static [u8]> {
+    let bytes: &'static [u8] = match name {
+        "spritesheet.png" => include_bytes!("../assets/spritesheet.png"),
+        "backgrounds.png" => include_bytes!("../assets/backgrounds.png"),
+        "waves.ron" => include_bytes!("../assets/waves.ron"),
+        "enemies.ron" => include_bytes!("../assets/enemies.ron"),
+        "particles.ron" => include_bytes!("../assets/particles.ron"),
+        "input.ron" => include_bytes!("../assets/input.ron"),
+        _ => return None,
+    };
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AssetSource;
+
+    #[test]
+    fn test_directory_falls_back_to_embedded() {
+        let directory = std::env::temp_dir().join("ld49_asset_test");
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("waves.ron"), "(waves: [])").unwrap();
+
+        let assets = AssetSource::directory(&directory);
+        assert_eq!(assets.load_string("waves.ron").unwrap(), "(waves: [])");
+        assert_eq!(
+            assets.load("enemies.ron").unwrap(),
+            AssetSource::embedded().load("enemies.ron").unwrap()
+        );
+        assert!(assets.load("missing.png").is_err());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    error::Error,
+    sync::mpsc::{self, Sender},
+    time::Duration,
+};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    Sample, SampleFormat, StreamConfig,
+};
+
+use super::{AudioOutput, SharedMixer};
+
+// The default sound device through cpal. Streams can't move between threads on every platform,
+// so one thread opens the device and keeps the stream alive until the output is dropped.
+pub struct DeviceOutput {
+    sample_rate: u32,
+    // The first mixer sent starts the stream, the thread quits once this is dropped
+    mixer_sender: Sender<SharedMixer>,
+    started: bool,
+}
+
+impl DeviceOutput {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let (mixer_sender, mixer_receiver) = mpsc::channel::<SharedMixer>();
+        let (opened_sender, opened_receiver) = mpsc::channel::<Result<u32, String>>();
+
+        std::thread::Builder::new()
+            .name("audio".to_string())
+            .spawn(move || {
+                let device = match cpal::default_host().default_output_device() {
+                    Some(device) => device,
+                    None => {
+                        let _ = opened_sender.send(Err("no sound output device".to_string()));
+                        return;
+                    }
+                };
+                let supported = match device.default_output_config() {
+                    Ok(supported) => supported,
+                    Err(err) => {
+                        let _ = opened_sender.send(Err(err.to_string()));
+                        return;
+                    }
+                };
+                let _ = opened_sender.send(Ok(supported.sample_rate().0));
+
+                let mixer = match mixer_receiver.recv() {
+                    Ok(mixer) => mixer,
+                    Err(_) => return,
+                };
+                let config = supported.config();
+                let result = match supported.sample_format() {
+                    SampleFormat::F32 => build_stream::<f32>(&device, &config, mixer),
+                    SampleFormat::I16 => build_stream::<i16>(&device, &config, mixer),
+                    SampleFormat::U16 => build_stream::<u16>(&device, &config, mixer),
+                };
+                let stream = match result {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        log::error!("Could not start sound output: {}", err);
+                        return;
+                    }
+                };
+
+                // Nothing else comes through, this only returns once the sender is gone
+                while mixer_receiver.recv().is_ok() {}
+                drop(stream);
+            })?;
+
+        let sample_rate = opened_receiver
+            .recv()
+            .map_err(|_| "sound thread quit")?
+            .map_err(|err| format!("could not open sound output: {}", err))?;
+        Ok(Self {
+            sample_rate,
+            mixer_sender,
+            started: false,
+        })
+    }
+}
+
+impl AudioOutput for DeviceOutput {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn update(&mut self, mixer: &SharedMixer, _delta: Duration) {
+        if !self.started {
+            self.started = true;
+            let _ = self.mixer_sender.send(SharedMixer::clone(mixer));
+        }
+    }
+}
+
+fn build_stream<T: Sample>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    mixer: SharedMixer,
+) -> Result<cpal::Stream, Box<dyn Error>> {
+    let channels = usize::from(config.channels);
+    let mut buffer = Vec::new();
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            buffer.resize(data.len() / channels * 2, 0.0);
+            mixer.lock().unwrap().render(&mut buffer);
+            for (frame, stereo) in data.chunks_mut(channels).zip(buffer.chunks_exact(2)) {
+                for (channel, sample) in frame.iter_mut().enumerate() {
+                    // Mono gets both sides, anything past stereo stays quiet
+                    let value = match (channels, channel) {
+                        (1, _) => (stereo[0] + stereo[1]) / 2.0,
+                        (_, 0) => stereo[0],
+                        (_, 1) => stereo[1],
+                        _ => 0.0,
+                    };
+                    *sample = T::from(&value);
+                }
+            }
+        },
+        |err| log::error!("Sound output error: {}", err),
+    )?;
+    stream.play()?;
+    Ok(stream)
+}
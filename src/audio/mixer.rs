@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+// Mono samples from -1 to 1 at the mixer's sample rate, shared between every voice playing it
+pub type Clip = Arc<Vec<f32>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bus {
+    Sfx,
+    Music,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VoiceId(u64);
+
+#[derive(Debug, Clone)]
+struct Voice {
+    id: VoiceId,
+    clip: Clip,
+    bus: Bus,
+    looping: bool,
+    position: usize,
+    // From -1 (left) to 1 (right)
+    pan: f32,
+    gain: f32,
+    target_gain: f32,
+    // Gain change per frame and how many frames are left until the target
+    fade_step: f32,
+    fade_frames: u32,
+    // Dropped once faded out
+    stopping: bool,
+}
+
+impl Voice {
+    fn fade_to(&mut self, gain: f32, seconds: f32, sample_rate: u32) {
+        let frames = (seconds * sample_rate as f32).max(1.0) as u32;
+        self.target_gain = gain;
+        self.fade_step = (gain - self.gain) / frames as f32;
+        self.fade_frames = frames;
+    }
+
+    fn is_finished(&self) -> bool {
+        (self.stopping && self.gain <= 0.0) || (!self.looping && self.position >= self.clip.len())
+    }
+}
+
+// Adds up every playing voice into interleaved stereo frames, scaled by the bus volumes
+#[derive(Debug)]
+pub struct Mixer {
+    sample_rate: u32,
+    voices: Vec<Voice>,
+    next_id: u64,
+    master_volume: f32,
+    sfx_volume: f32,
+    music_volume: f32,
+}
+
+impl Mixer {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            voices: Vec::new(),
+            next_id: 0,
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 1.0,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn set_volumes(&mut self, master: f32, sfx: f32, music: f32) {
+        self.master_volume = master.clamp(0.0, 1.0);
+        self.sfx_volume = sfx.clamp(0.0, 1.0);
+        self.music_volume = music.clamp(0.0, 1.0);
+    }
+
+    // Starts silent and fades in over `fade_in` seconds, straight away at 0
+    pub fn play(
+        &mut self,
+        clip: &Clip,
+        bus: Bus,
+        gain: f32,
+        pan: f32,
+        looping: bool,
+        fade_in: f32,
+    ) -> VoiceId {
+        let id = VoiceId(self.next_id);
+        self.next_id += 1;
+        let mut voice = Voice {
+            id,
+            clip: Arc::clone(clip),
+            bus,
+            looping,
+            position: 0,
+            pan: pan.clamp(-1.0, 1.0),
+            gain,
+            target_gain: gain,
+            fade_step: 0.0,
+            fade_frames: 0,
+            stopping: false,
+        };
+        if fade_in > 0.0 {
+            voice.gain = 0.0;
+            voice.fade_to(gain, fade_in, self.sample_rate);
+        }
+        self.voices.push(voice);
+        id
+    }
+
+    pub fn set_pan(&mut self, id: VoiceId, pan: f32) {
+        if let Some(voice) = self.voice_mut(id) {
+            voice.pan = pan.clamp(-1.0, 1.0);
+        }
+    }
+
+    // Fades out over `fade_out` seconds and then lets the voice go
+    pub fn stop(&mut self, id: VoiceId, fade_out: f32) {
+        let sample_rate = self.sample_rate;
+        if let Some(voice) = self.voice_mut(id) {
+            voice.stopping = true;
+            voice.fade_to(0.0, fade_out, sample_rate);
+        }
+    }
+
+    pub fn is_playing(&self, id: VoiceId) -> bool {
+        self.voices.iter().any(|voice| voice.id == id)
+    }
+
+    pub fn voice_count(&self) -> usize {
+        self.voices.len()
+    }
+
+    // Overwrites `frames` with the next stretch of audio, left and right interleaved
+    pub fn render(&mut self, frames: &mut [f32]) {
+        frames.iter_mut().for_each(|sample| *sample = 0.0);
+        for voice in self.voices.iter_mut() {
+            let bus_volume = match voice.bus {
+                Bus::Sfx => self.sfx_volume,
+                Bus::Music => self.music_volume,
+            } * self.master_volume;
+            // Equal power, so a sound keeps its loudness as it moves across
+            let angle = (voice.pan + 1.0) * std::f32::consts::FRAC_PI_4;
+            let (left, right) = (angle.cos(), angle.sin());
+
+            for frame in frames.chunks_exact_mut(2) {
+                if voice.position >= voice.clip.len() {
+                    if !voice.looping || voice.clip.is_empty() {
+                        break;
+                    }
+                    voice.position = 0;
+                }
+                if voice.fade_frames > 0 {
+                    voice.fade_frames -= 1;
+                    voice.gain = if voice.fade_frames == 0 {
+                        voice.target_gain
+                    } else {
+                        voice.gain + voice.fade_step
+                    };
+                }
+                let sample = voice.clip[voice.position] * voice.gain * bus_volume;
+                frame[0] += sample * left;
+                frame[1] += sample * right;
+                voice.position += 1;
+            }
+        }
+        self.voices.retain(|voice| !voice.is_finished());
+        frames
+            .iter_mut()
+            .for_each(|sample| *sample = sample.clamp(-1.0, 1.0));
+    }
+
+    fn voice_mut(&mut self, id: VoiceId) -> Option<&mut Voice> {
+        self.voices.iter_mut().find(|voice| voice.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{Bus, Mixer};
+
+    #[test]
+    fn test_pan_and_fade_out() {
+        let mut mixer = Mixer::new(100);
+        let clip = Arc::new(vec![0.5; 100]);
+        let left = mixer.play(&clip, Bus::Sfx, 1.0, -1.0, true, 0.0);
+        let mut frames = vec![0.0; 20];
+        mixer.render(&mut frames);
+        assert!((frames[0] - 0.5).abs() < 1e-5);
+        assert!(frames[1].abs() < 1e-5);
+
+        // Half a second to fade out at 100 frames a second
+        mixer.stop(left, 0.5);
+        let mut frames = vec![0.0; 2 * 50];
+        mixer.render(&mut frames);
+        assert!(frames[0] > 0.4);
+        assert_eq!(frames[98], 0.0);
+        assert!(!mixer.is_playing(left));
+
+        // One-shots end with their clip
+        let once = mixer.play(&clip, Bus::Music, 1.0, 0.0, false, 0.0);
+        mixer.set_volumes(1.0, 1.0, 0.0);
+        let mut frames = vec![0.0; 2 * 150];
+        mixer.render(&mut frames);
+        assert!(frames.iter().all(|sample| *sample == 0.0));
+        assert!(!mixer.is_playing(once));
+    }
+}
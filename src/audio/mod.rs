@@ -0,0 +1,126 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[cfg(feature = "audio")]
+mod device;
+mod mixer;
+mod synth;
+
+#[cfg(feature = "audio")]
+pub use device::DeviceOutput;
+pub use mixer::{Bus, Clip, Mixer, VoiceId};
+pub use synth::{music, sound};
+
+// Shared with the output, which might pull frames from it on its own thread
+pub type SharedMixer = Arc<Mutex<Mixer>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Thrust,
+    AsteroidImpact,
+    RepairPickup,
+    LowHealthAlarm,
+    GameOver,
+}
+
+impl Sound {
+    pub const ALL: [Sound; 5] = [
+        Sound::Thrust,
+        Sound::AsteroidImpact,
+        Sound::RepairPickup,
+        Sound::LowHealthAlarm,
+        Sound::GameOver,
+    ];
+
+    // Another one right after would just pile up, like a ship scraping along an asteroid
+    pub fn min_interval(self) -> Duration {
+        match self {
+            Sound::AsteroidImpact => Duration::from_millis(200),
+            Sound::RepairPickup => Duration::from_millis(50),
+            Sound::Thrust | Sound::LowHealthAlarm | Sound::GameOver => Duration::ZERO,
+        }
+    }
+
+    pub fn gain(self) -> f32 {
+        match self {
+            Sound::Thrust => 0.35,
+            Sound::AsteroidImpact => 0.8,
+            Sound::RepairPickup | Sound::GameOver => 0.7,
+            Sound::LowHealthAlarm => 0.5,
+        }
+    }
+}
+
+// What gameplay systems write to the sound channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoundEvent {
+    // Plays once, panned to where it happened if that's anywhere in particular
+    Play(Sound, Option<glam::Vec2>),
+    // Keeps a looping sound going for another tick, one voice per sound and source entity. It
+    // fades out on the first tick nobody holds it.
+    Hold(Sound, u32, glam::Vec2),
+}
+
+// Background music, picked by the game state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MusicTrack {
+    Title,
+    Play,
+    GameOver,
+}
+
+impl MusicTrack {
+    pub const ALL: [MusicTrack; 3] = [MusicTrack::Title, MusicTrack::Play, MusicTrack::GameOver];
+}
+
+// Where mixed audio ends up
+pub trait AudioOutput: Send {
+    fn sample_rate(&self) -> u32;
+    // Called once a tick after the mixer took in the tick's sounds. Outputs playing on their
+    // own thread have nothing to do here.
+    fn update(&mut self, mixer: &SharedMixer, delta: Duration);
+}
+
+// Mixes and throws the result away, for headless runs, tests and machines without a sound device.
+// Sounds still play out in time, so the mixer doesn't fill up with voices that never end.
+pub struct NullOutput {
+    sample_rate: u32,
+    buffer: Vec<f32>,
+    // Fraction of a frame carried over between ticks
+    pending: f64,
+}
+
+impl NullOutput {
+    // Low, nobody listens to it
+    pub const SAMPLE_RATE: u32 = 8000;
+
+    pub fn new() -> Self {
+        Self {
+            sample_rate: Self::SAMPLE_RATE,
+            buffer: Vec::new(),
+            pending: 0.0,
+        }
+    }
+}
+
+impl Default for NullOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioOutput for NullOutput {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn update(&mut self, mixer: &SharedMixer, delta: Duration) {
+        self.pending += delta.as_secs_f64() * f64::from(self.sample_rate);
+        let frames = self.pending as usize;
+        self.pending -= frames as f64;
+        self.buffer.resize(frames * 2, 0.0);
+        mixer.lock().unwrap().render(&mut self.buffer);
+    }
+}
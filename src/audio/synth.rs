@@ -0,0 +1,146 @@
+use std::{f32::consts::TAU, sync::Arc};
+
+use super::{mixer::Clip, MusicTrack, Sound};
+
+// Everything is made up from a few oscillators when the audio system starts, there are no sound
+// files to ship
+
+// Same noise every time, it doesn't need to be good
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+fn sine(phase: f32) -> f32 {
+    (phase * TAU).sin()
+}
+
+fn square(phase: f32) -> f32 {
+    if phase.fract() < 0.5 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn triangle(phase: f32) -> f32 {
+    4.0 * (phase.fract() - 0.5).abs() - 1.0
+}
+
+// Semitones away from A4
+fn note(semitones: i32) -> f32 {
+    440.0 * 2.0f32.powf(semitones as f32 / 12.0)
+}
+
+fn render(sample_rate: u32, seconds: f32, mut sample: impl FnMut(f32) -> f32) -> Clip {
+    let length = (seconds * sample_rate as f32) as usize;
+    Arc::new(
+        (0..length)
+            .map(|idx| sample(idx as f32 / sample_rate as f32))
+            .collect(),
+    )
+}
+
+// Short linear ramps at either end of a tone so it doesn't click
+fn envelope(time: f32, length: f32, ramp: f32) -> f32 {
+    (time / ramp).min((length - time) / ramp).clamp(0.0, 1.0)
+}
+
+pub fn sound(sound: Sound, sample_rate: u32) -> Clip {
+    match sound {
+        // Rumbling noise, low passed so it sits under everything else. Loops.
+        Sound::Thrust => {
+            let mut noise = Noise(0x1234_5678);
+            let mut low = 0.0;
+            let length = 1.0;
+            render(sample_rate, length, |time| {
+                low += (noise.next() - low) * 0.08;
+                let wobble = 1.0 + 0.2 * sine(time * 6.0);
+                low * 2.5 * wobble * envelope(time, length, 0.01)
+            })
+        }
+        // A thump with some grit on top
+        Sound::AsteroidImpact => {
+            let mut noise = Noise(0x0bad_cafe);
+            render(sample_rate, 0.3, |time| {
+                let thump = sine(90.0 * time - 40.0 * time * time) * (-time * 18.0).exp();
+                let grit = noise.next() * (-time * 40.0).exp();
+                (thump * 0.8 + grit * 0.4) * envelope(time, 0.3, 0.002)
+            })
+        }
+        // Rising arpeggio
+        Sound::RepairPickup => {
+            let notes = [3, 7, 10, 15];
+            let step = 0.07;
+            let length = step * notes.len() as f32 + 0.15;
+            render(sample_rate, length, |time| {
+                let idx = ((time / step) as usize).min(notes.len() - 1);
+                let since = time - idx as f32 * step;
+                sine(note(notes[idx]) * time)
+                    * 0.5
+                    * (-since * 6.0).exp()
+                    * envelope(time, length, 0.005)
+            })
+        }
+        // Two beeps and a gap, loops while a ship is about to blow up
+        Sound::LowHealthAlarm => {
+            let beep = 0.12;
+            render(sample_rate, 0.6, |time| {
+                let (frequency, start) = if time < beep {
+                    (note(14), 0.0)
+                } else if time < 2.0 * beep {
+                    (note(9), beep)
+                } else {
+                    return 0.0;
+                };
+                square(frequency * time) * 0.2 * envelope(time - start, beep, 0.005)
+            })
+        }
+        // Falling tones that trail off
+        Sound::GameOver => {
+            let notes = [0, -4, -7, -12];
+            let step = 0.3;
+            let length = step * notes.len() as f32 + 0.6;
+            render(sample_rate, length, |time| {
+                let idx = ((time / step) as usize).min(notes.len() - 1);
+                let since = time - idx as f32 * step;
+                triangle(note(notes[idx] - 12) * time)
+                    * 0.5
+                    * (-since * 2.5).exp()
+                    * envelope(time, length, 0.005)
+            })
+        }
+    }
+}
+
+// A bass line under an arpeggio over four bars, made to loop
+pub fn music(track: MusicTrack, sample_rate: u32) -> Clip {
+    // Beats per minute and semitones from A4 of each bar's chord root
+    let (tempo, roots, arpeggio, level): (f32, [i32; 4], &[i32], f32) = match track {
+        MusicTrack::Title => (90.0, [-12, -16, -19, -14], &[0, 7, 12, 15], 0.25),
+        MusicTrack::Play => (132.0, [-12, -12, -16, -14], &[0, 3, 7, 12, 7, 3], 0.22),
+        MusicTrack::GameOver => (70.0, [-12, -17, -21, -19], &[0, 3, 7], 0.2),
+    };
+    let beat = 60.0 / tempo;
+    let bar = beat * 4.0;
+    let length = bar * roots.len() as f32;
+    let arpeggio_step = bar / (arpeggio.len() * 2) as f32;
+    render(sample_rate, length, |time| {
+        let root = roots[((time / bar) as usize).min(roots.len() - 1)];
+        let since_beat = time % beat;
+        let bass = triangle(note(root - 12) * time) * (-since_beat * 3.0).exp();
+
+        let step = (time / arpeggio_step) as usize;
+        let since_step = time % arpeggio_step;
+        let lead_note = root + 12 + arpeggio[step % arpeggio.len()];
+        let lead = square(note(lead_note) * time) * 0.3 * (-since_step * 8.0).exp();
+
+        (bass * 0.7 + lead) * level * envelope(time, length, 0.01)
+    })
+}
@@ -0,0 +1,211 @@
+use std::{collections::BTreeMap, error::Error, io, time::Duration};
+
+use serde::Serialize;
+use specs::{Join, WorldExt};
+
+use crate::{
+    assets::AssetSource,
+    game::{
+        component::PickupKind,
+        input::{Action, InputSource, RandomInput, ScriptedInput, WinitInputSource},
+        resource::{DamageSource, Difficulty, DifficultyPreset, GameState, RunStats, Score},
+    },
+    simulation::Simulation,
+};
+
+// Who plays the runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    // Starts the run and never touches anything again
+    Idle,
+    Random,
+    Autopilot,
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    pub runs: usize,
+    // Run `n` is played with seed `seed + n`
+    pub seed: u64,
+    pub controller: Controller,
+    pub preset: DifficultyPreset,
+    pub tick_rate: u32,
+    // Runs still going after this many ticks are cut short and marked as timed out
+    pub max_ticks_per_run: u64,
+    pub assets: AssetSource,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            runs: 100,
+            seed: 0,
+            controller: Controller::Autopilot,
+            preset: DifficultyPreset::Normal,
+            tick_rate: 60,
+            max_ticks_per_run: 60 * 60 * 10,
+            assets: AssetSource::embedded(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRecord {
+    pub run: usize,
+    pub seed: u64,
+    // Seconds from the start of the run until the last life was lost
+    pub survival_time: f32,
+    pub timed_out: bool,
+    pub score: f32,
+    pub max_entities: usize,
+    pub damage_taken: BTreeMap<&'static str, f32>,
+    pub packs_collected: BTreeMap<&'static str, u32>,
+}
+
+pub fn run_batch(config: &BatchConfig) -> Result<Vec<RunRecord>, Box<dyn Error>> {
+    (0..config.runs).map(|run| play_run(config, run)).collect()
+}
+
+pub fn play_run(config: &BatchConfig, run: usize) -> Result<RunRecord, Box<dyn Error>> {
+    let seed = config.seed.wrapping_add(run as u64);
+    let input_source: Box<dyn InputSource> = match config.controller {
+        Controller::Idle => Box::new(ScriptedInput::default().tap(1, 0, Action::Confirm)),
+        Controller::Random => Box::new(RandomInput::new(0, seed)),
+        // The autopilot types on the keyboard, so there has to be a keyboard source listening
+        Controller::Autopilot => Box::new(WinitInputSource {}),
+    };
+    let mut simulation = Simulation::new((1280, 720), vec![input_source]);
+    simulation.load_assets(&config.assets)?;
+    simulation.set_seed(seed);
+    simulation
+        .world_mut()
+        .insert(Difficulty::new(config.preset));
+    if config.controller == Controller::Autopilot {
+        simulation.add_autopilot(0);
+    }
+
+    let tick = Duration::from_nanos(1_000_000_000 / u64::from(config.tick_rate.max(1)));
+    let mut play_ticks = 0;
+    let mut max_entities = 0;
+    let mut timed_out = false;
+    loop {
+        simulation.tick(tick);
+
+        let world = simulation.world();
+        let playing = matches!(
+            *world.read_resource::<GameState>(),
+            GameState::GameStatePlay { .. }
+        );
+        if playing {
+            play_ticks += 1;
+            max_entities = max_entities.max(world.entities().join().count());
+        } else if play_ticks > 0 {
+            break;
+        }
+        if simulation.tick_count() >= config.max_ticks_per_run {
+            timed_out = true;
+            break;
+        }
+    }
+
+    let world = simulation.world();
+    let stats = world.read_resource::<RunStats>();
+    let score = world.read_resource::<Score>().points;
+    Ok(RunRecord {
+        run,
+        seed,
+        survival_time: play_ticks as f32 * tick.as_secs_f32(),
+        timed_out,
+        score,
+        max_entities,
+        damage_taken: DamageSource::ALL
+            .iter()
+            .map(|source| (source.name(), stats.damage(*source)))
+            .collect(),
+        packs_collected: PickupKind::ALL
+            .iter()
+            .map(|kind| (kind.name(), stats.packs(*kind)))
+            .collect(),
+    })
+}
+
+// One row per run, damage and packs get a column per source and kind
+pub fn write_csv(records: &[RunRecord], writer: &mut impl io::Write) -> io::Result<()> {
+    write!(
+        writer,
+        "run,seed,survival_time,timed_out,score,max_entities"
+    )?;
+    for source in DamageSource::ALL {
+        write!(writer, ",damage_{}", source.name())?;
+    }
+    for kind in PickupKind::ALL {
+        write!(writer, ",packs_{}", kind.name())?;
+    }
+    writeln!(writer)?;
+
+    for record in records {
+        write!(
+            writer,
+            "{},{},{:.3},{},{:.1},{}",
+            record.run,
+            record.seed,
+            record.survival_time,
+            record.timed_out,
+            record.score,
+            record.max_entities
+        )?;
+        for source in DamageSource::ALL {
+            write!(writer, ",{:.1}", record.damage_taken[source.name()])?;
+        }
+        for kind in PickupKind::ALL {
+            write!(writer, ",{}", record.packs_collected[kind.name()])?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+pub fn write_json(records: &[RunRecord], writer: &mut impl io::Write) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *writer, records)?;
+    writeln!(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{play_run, write_csv, BatchConfig, Controller};
+
+    #[test]
+    fn test_seeded_runs_repeat() {
+        let config = BatchConfig {
+            controller: Controller::Random,
+            max_ticks_per_run: 60 * 20,
+            ..Default::default()
+        };
+        let first = play_run(&config, 3).unwrap();
+        let second = play_run(&config, 3).unwrap();
+        assert_eq!(first.seed, 3);
+        assert_eq!(first.survival_time, second.survival_time);
+        assert_eq!(first.damage_taken, second.damage_taken);
+        assert!(first.damage_taken["radiation"] > 0.0);
+        assert!(first.max_entities > 0);
+    }
+
+    #[test]
+    fn test_csv_columns() {
+        let config = BatchConfig {
+            controller: Controller::Idle,
+            max_ticks_per_run: 60 * 5,
+            ..Default::default()
+        };
+        let record = play_run(&config, 0).unwrap();
+        let mut csv = Vec::new();
+        write_csv(&[record], &mut csv).unwrap();
+
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+        assert!(lines[0].contains("damage_asteroid"));
+        assert!(lines[0].contains("packs_repair"));
+    }
+}
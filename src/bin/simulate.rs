@@ -0,0 +1,107 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use clap::{ArgEnum, Parser};
+
+use ld49::{
+    assets::AssetSource,
+    batch::{run_batch, write_csv, write_json, BatchConfig, Controller},
+    game::resource::DifficultyPreset,
+};
+
+#[derive(Debug, Clone, Copy, ArgEnum)]
+enum ControllerArg {
+    Idle,
+    Random,
+    Autopilot,
+}
+
+#[derive(Debug, Clone, Copy, ArgEnum)]
+enum PresetArg {
+    Easy,
+    Normal,
+    Hard,
+}
+
+#[derive(Debug, Clone, Copy, ArgEnum)]
+enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Parser)]
+#[clap(about = "Plays seeded games headlessly and writes statistics for every run")]
+struct Args {
+    /// Number of runs to play
+    #[clap(long, default_value_t = 100)]
+    runs: usize,
+    /// Seed of the first run, every following run uses the next one
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+    #[clap(long, arg_enum, default_value = "autopilot")]
+    controller: ControllerArg,
+    #[clap(long, arg_enum, default_value = "normal")]
+    difficulty: PresetArg,
+    #[clap(long, arg_enum, default_value = "csv")]
+    format: Format,
+    /// File to write the statistics to, standard output when left out
+    #[clap(long)]
+    output: Option<PathBuf>,
+    /// Runs lasting longer than this many seconds are cut short
+    #[clap(long, default_value_t = 600)]
+    max_seconds: u64,
+    /// Simulation updates per second
+    #[clap(long, default_value_t = 60)]
+    tick_rate: u32,
+    /// Directory with asset files that replace the built-in ones
+    #[clap(long)]
+    assets: Option<PathBuf>,
+}
+
+fn main() {
+    env_logger::init();
+
+    if let Err(err) = run(Args::parse()) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let config = BatchConfig {
+        runs: args.runs,
+        seed: args.seed,
+        controller: match args.controller {
+            ControllerArg::Idle => Controller::Idle,
+            ControllerArg::Random => Controller::Random,
+            ControllerArg::Autopilot => Controller::Autopilot,
+        },
+        preset: match args.difficulty {
+            PresetArg::Easy => DifficultyPreset::Easy,
+            PresetArg::Normal => DifficultyPreset::Normal,
+            PresetArg::Hard => DifficultyPreset::Hard,
+        },
+        tick_rate: args.tick_rate,
+        max_ticks_per_run: args.max_seconds * u64::from(args.tick_rate),
+        assets: match &args.assets {
+            Some(directory) => AssetSource::directory(directory),
+            None => AssetSource::embedded(),
+        },
+    };
+    let records = run_batch(&config)?;
+
+    let mut writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout()),
+    };
+    match args.format {
+        Format::Csv => write_csv(&records, &mut writer)?,
+        Format::Json => write_json(&records, &mut writer)?,
+    }
+    writer.flush()?;
+    Ok(())
+}
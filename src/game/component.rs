@@ -1,8 +1,15 @@
-use specs::{Component, VecStorage};
+// The ConvertSaveload derive expects an error type by this name
+use std::convert::Infallible as NoError;
 
-use crate::physics::AABB;
+use serde::{Deserialize, Serialize};
+use specs::{
+    saveload::{ConvertSaveload, Marker},
+    Component, ConvertSaveload, Entity, VecStorage,
+};
 
-#[derive(Component, Debug)]
+use crate::{game::enemy::EnemyVariant, physics::AABB};
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 #[storage(VecStorage)]
 pub struct Transform {
     pub position: glam::Vec2,
@@ -39,7 +46,7 @@ impl Transform {
     }
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 #[storage(VecStorage)]
 pub struct Velocity {
     pub direction: glam::Vec2,
@@ -55,32 +62,118 @@ impl Default for Velocity {
     }
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 #[storage(VecStorage)]
 pub struct Display {
     pub sprite_idx: u32,
+    pub tint: [f32; 4],
+}
+
+impl Display {
+    pub const NO_TINT: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    pub fn new(sprite_idx: u32) -> Self {
+        Self {
+            sprite_idx,
+            tint: Self::NO_TINT,
+        }
+    }
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 #[storage(VecStorage)]
 pub struct Player {
+    // Which seat at the keyboard this ship belongs to
+    pub slot: usize,
     pub health: f32,
+    pub weapon_level: u32,
+    pub fire_cooldown: f32,
+}
+
+impl Player {
+    pub fn new(slot: usize) -> Self {
+        Self {
+            slot,
+            ..Default::default()
+        }
+    }
 }
 
 impl Default for Player {
     fn default() -> Self {
-        Self { health: 100.0 }
+        Self {
+            slot: 0,
+            health: 100.0,
+            weapon_level: 0,
+            fire_cooldown: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StatusEffectKind {
+    Shield,
+    SpeedBoost,
+    SlowMo,
+    // Granted after a respawn, the ship blinks while it lasts
+    Invulnerable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub remaining: f32,
+}
+
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+#[storage(VecStorage)]
+pub struct StatusEffects {
+    pub active: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, kind: StatusEffectKind, duration: f32) {
+        // Picking up the same effect again refreshes it, durations don't stack
+        match self.active.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => effect.remaining = effect.remaining.max(duration),
+            None => self.active.push(StatusEffect {
+                kind,
+                remaining: duration,
+            }),
+        }
+    }
+
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.active.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn remaining(&self, kind: StatusEffectKind) -> Option<f32> {
+        self.active
+            .iter()
+            .find(|effect| effect.kind == kind)
+            .map(|effect| effect.remaining)
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        for effect in self.active.iter_mut() {
+            effect.remaining -= delta;
+        }
+        self.active.retain(|effect| effect.remaining > 0.0);
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ColliderTag {
     Player,
     Asteroid,
-    Health,
+    Pickup,
+    Enemy,
+    Projectile,
+    PlayerProjectile,
+    Boss,
 }
 
-#[derive(Component, Debug, PartialEq)]
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[storage(VecStorage)]
 pub struct Collider {
     pub tag: ColliderTag,
@@ -94,16 +187,195 @@ impl Collider {
             bounding_box: Default::default(),
         }
     }
+
+    pub fn intersects(
+        &self,
+        transform: &Transform,
+        other: &Self,
+        other_transform: &Transform,
+    ) -> bool {
+        self.bounding_box
+            .translate(transform.position)
+            .scale(transform.scale)
+            .intersect(
+                &other
+                    .bounding_box
+                    .translate(other_transform.position)
+                    .scale(other_transform.scale),
+            )
+    }
 }
 
-#[derive(Component, Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PickupKind {
+    Repair,
+    Shield,
+    SpeedBoost,
+    WeaponUpgrade,
+    ScoreMultiplier,
+    SlowMo,
+}
+
+impl PickupKind {
+    pub const ALL: [PickupKind; 6] = [
+        PickupKind::Repair,
+        PickupKind::Shield,
+        PickupKind::SpeedBoost,
+        PickupKind::WeaponUpgrade,
+        PickupKind::ScoreMultiplier,
+        PickupKind::SlowMo,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PickupKind::Repair => "repair",
+            PickupKind::Shield => "shield",
+            PickupKind::SpeedBoost => "speed_boost",
+            PickupKind::WeaponUpgrade => "weapon_upgrade",
+            PickupKind::ScoreMultiplier => "score_multiplier",
+            PickupKind::SlowMo => "slow_mo",
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[storage(VecStorage)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    // Meaning depends on the kind: health restored, seconds of effect, levels or multiplier bonus
+    pub effect: f32,
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 #[storage(VecStorage)]
 pub struct Lifetime {
     pub remaining: f32,
 }
 
-#[derive(Component, Debug)]
+// Keeps particles coming off the entity, using one of the presets in particles.ron
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[storage(VecStorage)]
+pub struct Emitter {
+    pub preset: String,
+    // Where on the entity particles come from, in the same -1 to 1 space as its sprite
+    pub offset: glam::Vec2,
+    pub enabled: bool,
+}
+
+impl Emitter {
+    pub fn new(preset: &str, offset: glam::Vec2) -> Self {
+        Self {
+            preset: preset.to_string(),
+            offset,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 #[storage(VecStorage)]
 pub struct Spinner {
     pub speed: f32,
 }
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[storage(VecStorage)]
+pub struct Enemy {
+    pub variant: EnemyVariant,
+    pub health: f32,
+    pub fire_clock: f32,
+    // Which way it circles the player, so a group doesn't strafe in lockstep
+    pub strafe_sign: f32,
+}
+
+impl Enemy {
+    pub fn new(variant: &EnemyVariant, strafe_sign: f32) -> Self {
+        Self {
+            variant: variant.clone(),
+            health: variant.health,
+            fire_clock: variant
+                .weapon
+                .as_ref()
+                .map_or(0.0, |weapon| weapon.interval),
+            strafe_sign,
+        }
+    }
+
+    pub fn health_fraction(&self) -> f32 {
+        self.health / self.variant.health
+    }
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[storage(VecStorage)]
+pub struct Projectile {
+    pub damage: f32,
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[storage(VecStorage)]
+pub struct Boss {
+    pub health: f32,
+    pub max_health: f32,
+    pub attack_clock: f32,
+    pub next_attack: usize,
+    pub beam_shots_left: u32,
+    pub beam_clock: f32,
+    pub age: f32,
+}
+
+impl Boss {
+    pub fn new(health: f32, attack_interval: f32) -> Self {
+        Self {
+            health,
+            max_health: health,
+            attack_clock: attack_interval,
+            next_attack: 0,
+            beam_shots_left: 0,
+            beam_clock: 0.0,
+            age: 0.0,
+        }
+    }
+}
+
+// Points at its core, so it goes through saveload markers instead of plain serde
+#[derive(Component, Debug, ConvertSaveload)]
+#[storage(VecStorage)]
+pub struct BossPart {
+    pub core: Entity,
+    pub angle: f32,
+    pub radius: f32,
+    pub health: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StatusEffectKind, StatusEffects};
+
+    #[test]
+    fn test_status_effect_refresh() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffectKind::Shield, 2.0);
+        effects.apply(StatusEffectKind::Shield, 5.0);
+        effects.apply(StatusEffectKind::Shield, 1.0);
+
+        assert_eq!(effects.active.len(), 1);
+        assert_eq!(effects.active[0].remaining, 5.0);
+    }
+
+    #[test]
+    fn test_status_effect_expiry() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffectKind::Shield, 1.0);
+        effects.apply(StatusEffectKind::SlowMo, 3.0);
+
+        effects.tick(2.0);
+
+        assert!(!effects.has(StatusEffectKind::Shield));
+        assert!(effects.has(StatusEffectKind::SlowMo));
+
+        effects.tick(1.0);
+
+        assert!(effects.active.is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+use super::component::Display;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnemyBehavior {
+    pub max_speed: f32,
+    // How quickly velocity can change, in speed units per second
+    pub acceleration: f32,
+    // Distance the ship tries to keep from the player, zero rams straight in
+    #[serde(default)]
+    pub preferred_distance: f32,
+    // Sideways component mixed into the approach, zero never strafes
+    #[serde(default)]
+    pub strafe: f32,
+    // Runs away once health drops below this fraction of its maximum
+    #[serde(default)]
+    pub flee_below: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnemyWeapon {
+    pub interval: f32,
+    pub projectile_speed: f32,
+    pub damage: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnemyVariant {
+    pub name: String,
+    pub weight: u32,
+    pub sprite_idx: u32,
+    // Keeps enemies apart from the player ships, which share the sprite
+    #[serde(default = "default_tint")]
+    pub tint: [f32; 4],
+    pub size: f32,
+    pub health: f32,
+    // Damage per second while touching the player
+    pub contact_damage: f32,
+    pub behavior: EnemyBehavior,
+    #[serde(default)]
+    pub weapon: Option<EnemyWeapon>,
+}
+
+fn default_tint() -> [f32; 4] {
+    Display::NO_TINT
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnemyRoster {
+    // Seconds of play before the first enemy shows up
+    pub first_spawn: f32,
+    pub spawn_interval: f32,
+    pub max_alive: usize,
+    pub variants: Vec<EnemyVariant>,
+}
+
+impl Default for EnemyRoster {
+    fn default() -> Self {
+        Self::from_ron_str(include_str!("../../assets/enemies.ron")).unwrap()
+    }
+}
+
+impl EnemyRoster {
+    pub fn from_ron_str(data: &str) -> Result<Self, ron::Error> {
+        ron::from_str(data)
+    }
+
+    pub fn pick<R: rand::Rng>(&self, rng: &mut R) -> Option<&EnemyVariant> {
+        let total_weight: u32 = self.variants.iter().map(|variant| variant.weight).sum();
+        if total_weight == 0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0..total_weight);
+        for variant in &self.variants {
+            if roll < variant.weight {
+                return Some(variant);
+            }
+            roll -= variant.weight;
+        }
+
+        None
+    }
+}
+
+// Direction an enemy wants to move in, not normalized, zero means "stay put"
+pub fn steering_direction(
+    behavior: &EnemyBehavior,
+    position: glam::Vec2,
+    target: glam::Vec2,
+    health_fraction: f32,
+    strafe_sign: f32,
+) -> glam::Vec2 {
+    let to_target = target - position;
+    let distance = to_target.length();
+    if distance < f32::EPSILON {
+        return glam::Vec2::ZERO;
+    }
+    let towards = to_target / distance;
+
+    if health_fraction < behavior.flee_below {
+        return -towards;
+    }
+
+    // Seek when too far, back off when too close, hover around the preferred distance
+    let approach = if behavior.preferred_distance <= 0.0 {
+        1.0
+    } else {
+        ((distance - behavior.preferred_distance) / behavior.preferred_distance).clamp(-1.0, 1.0)
+    };
+
+    towards * approach + towards.perp() * behavior.strafe * strafe_sign
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{steering_direction, Display, EnemyBehavior, EnemyRoster};
+
+    fn behavior() -> EnemyBehavior {
+        EnemyBehavior {
+            max_speed: 4.0,
+            acceleration: 10.0,
+            preferred_distance: 200.0,
+            strafe: 0.0,
+            flee_below: 0.3,
+        }
+    }
+
+    #[test]
+    fn test_default_roster_parses() {
+        let roster = EnemyRoster::default();
+        assert!(!roster.variants.is_empty());
+        // Untinted is what player one looks like
+        assert!(roster
+            .variants
+            .iter()
+            .all(|variant| variant.tint != Display::NO_TINT));
+    }
+
+    #[test]
+    fn test_seek_and_keep_distance() {
+        let behavior = behavior();
+        let target = glam::Vec2::ZERO;
+
+        let far = steering_direction(&behavior, glam::vec2(1000.0, 0.0), target, 1.0, 1.0);
+        assert!(far.x < 0.0);
+
+        let close = steering_direction(&behavior, glam::vec2(50.0, 0.0), target, 1.0, 1.0);
+        assert!(close.x > 0.0);
+    }
+
+    #[test]
+    fn test_flee_when_damaged() {
+        let behavior = behavior();
+
+        let fleeing = steering_direction(
+            &behavior,
+            glam::vec2(1000.0, 0.0),
+            glam::Vec2::ZERO,
+            0.1,
+            1.0,
+        );
+        assert!(fleeing.x > 0.0);
+    }
+}
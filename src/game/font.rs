@@ -0,0 +1,102 @@
+use crate::renderer::HudQuad;
+
+// A tiny pixel font for the HUD and menus, drawn as one square quad per lit cell. Rows from the
+// top, the leftmost column in the highest bit.
+pub const GLYPH_WIDTH: u32 = 3;
+// A column of space after each glyph
+pub const GLYPH_ADVANCE: u32 = 4;
+pub const GLYPH_HEIGHT: u32 = 5;
+
+// HUD and menu sizes are in pixels at this window height and scale with it, so they take the same
+// share of the screen at any resolution
+const REFERENCE_HEIGHT: f32 = 720.0;
+const MIN_SCALE: f32 = 0.5;
+
+pub fn ui_scale(window_height: f32) -> f32 {
+    (window_height / REFERENCE_HEIGHT).max(MIN_SCALE)
+}
+
+// Letters are all capitals, lowercase x is the small multiplication sign
+pub fn glyph(c: char) -> Option<[u8; GLYPH_HEIGHT as usize]> {
+    Some(match c {
+        'x' => [0b000, 0b101, 0b010, 0b101, 0b000],
+        c if c.is_ascii_lowercase() => return glyph(c.to_ascii_uppercase()),
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        _ => return None,
+    })
+}
+
+// Characters without a glyph are skipped
+pub fn text_size(text: &str, cell: f32) -> glam::Vec2 {
+    let glyphs = text.chars().filter(|c| glyph(*c).is_some()).count() as u32;
+    let width = (glyphs * GLYPH_ADVANCE).saturating_sub(GLYPH_ADVANCE - GLYPH_WIDTH);
+    glam::vec2(width as f32, GLYPH_HEIGHT as f32) * cell
+}
+
+// Quads for the text with its top left corner at the position, each font cell `cell` pixels wide
+pub fn text(
+    quads: &mut Vec<HudQuad>,
+    position: glam::Vec2,
+    text: &str,
+    cell: f32,
+    color: [f32; 4],
+) {
+    for (idx, glyph) in text.chars().filter_map(glyph).enumerate() {
+        let origin = position + glam::vec2(idx as f32 * GLYPH_ADVANCE as f32 * cell, 0.0);
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    let offset = glam::vec2(column as f32, row as f32) * cell;
+                    quads.push(HudQuad::solid(
+                        origin + offset,
+                        glam::Vec2::splat(cell),
+                        color,
+                    ));
+                }
+            }
+        }
+    }
+}
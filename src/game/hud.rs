@@ -0,0 +1,250 @@
+use crate::{
+    game::{
+        component::StatusEffectKind,
+        font,
+        resource::{GameStateForRenderer, PlayerBar, RunHud},
+    },
+    renderer::HudQuad,
+};
+
+// Sizes are in pixels at the reference height of `font::ui_scale`
+
+// Gap between the widgets and the window edges
+const MARGIN: f32 = 16.0;
+
+const HEALTH_BAR_SIZE: glam::Vec2 = glam::const_vec2!([260.0, 14.0]);
+const ROW_GAP: f32 = 6.0;
+// Effects show as a strip next to the bar that shrinks as they run out
+const EFFECT_WIDTH_PER_SECOND: f32 = 6.0;
+const EFFECT_MAX_SECONDS: f32 = 10.0;
+
+// Score digits are drawn from square cells
+const SCORE_CELL: f32 = 5.0;
+const MULTIPLIER_CELL: f32 = 3.0;
+
+const LIFE_ICON_SIZE: f32 = 28.0;
+// Same as the player's ship in the sprite atlas
+const LIFE_SPRITE: u32 = 0;
+
+const RADIATION_METER_SIZE: glam::Vec2 = glam::const_vec2!([180.0, 12.0]);
+
+const TRACK_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.15];
+const SHIELD_COLOR: [f32; 4] = [0.4, 0.9, 1.0, 1.0];
+const BOOST_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+const SLOW_MO_COLOR: [f32; 4] = [0.6, 0.7, 1.0, 1.0];
+const INVULNERABLE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.6];
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const RADIATION_LOW_COLOR: [f32; 4] = [1.0, 0.9, 0.2, 1.0];
+const RADIATION_HIGH_COLOR: [f32; 4] = [1.0, 0.2, 0.1, 1.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+// Collects the quads of every widget, in window pixels
+struct HudBuilder {
+    window_size: glam::Vec2,
+    scale: f32,
+    quads: Vec<HudQuad>,
+}
+
+impl HudBuilder {
+    // Top left corner of a widget of the given size, placed against a corner of the window
+    fn place(&self, anchor: Anchor, size: glam::Vec2) -> glam::Vec2 {
+        let margin = MARGIN * self.scale;
+        let right = self.window_size.x - margin - size.x;
+        let bottom = self.window_size.y - margin - size.y;
+        match anchor {
+            Anchor::TopLeft => glam::vec2(margin, margin),
+            Anchor::TopRight => glam::vec2(right, margin),
+            Anchor::BottomLeft => glam::vec2(margin, bottom),
+            Anchor::BottomRight => glam::vec2(right, bottom),
+        }
+    }
+
+    fn rect(&mut self, position: glam::Vec2, size: glam::Vec2, color: [f32; 4]) {
+        self.quads.push(HudQuad::solid(position, size, color));
+    }
+
+    // Track with a fill from the left, fraction from 0 to 1
+    fn bar(&mut self, position: glam::Vec2, size: glam::Vec2, fraction: f32, color: [f32; 4]) {
+        self.rect(position, size, TRACK_COLOR);
+        let fill = glam::vec2(size.x * fraction.clamp(0.0, 1.0), size.y);
+        if fill.x > 0.0 {
+            self.rect(position, fill, color);
+        }
+    }
+
+    fn text(&mut self, position: glam::Vec2, text: &str, cell: f32, color: [f32; 4]) {
+        font::text(&mut self.quads, position, text, cell, color);
+    }
+}
+
+// Everything the HUD draws this frame, laid out for the given window size
+pub fn layout(state: &GameStateForRenderer, window_size: (u32, u32)) -> Vec<HudQuad> {
+    let window_size = glam::vec2(window_size.0 as f32, window_size.1 as f32);
+    let mut hud = HudBuilder {
+        window_size,
+        scale: font::ui_scale(window_size.y),
+        quads: Vec::new(),
+    };
+
+    health_bars(&mut hud, &state.player_bars);
+    if let Some(run) = &state.run_hud {
+        score(&mut hud, run);
+        lives(&mut hud, run);
+        radiation_meter(&mut hud, run);
+    }
+    hud.quads
+}
+
+// One bar per player stacked down from the top left, with their effects next to it
+fn health_bars(hud: &mut HudBuilder, bars: &[PlayerBar]) {
+    let bar_size = HEALTH_BAR_SIZE * hud.scale;
+    let row_gap = ROW_GAP * hud.scale;
+    let origin = hud.place(Anchor::TopLeft, bar_size);
+    for (idx, bar) in bars.iter().enumerate() {
+        let position = origin + glam::vec2(0.0, idx as f32 * (bar_size.y + row_gap));
+
+        // Shield wins over boost
+        let has = |kind| bar.effects.iter().any(|(effect, _)| *effect == kind);
+        let color = if has(StatusEffectKind::Shield) {
+            SHIELD_COLOR
+        } else if has(StatusEffectKind::SpeedBoost) {
+            BOOST_COLOR
+        } else {
+            bar.tint
+        };
+        hud.bar(position, bar_size, bar.health, color);
+
+        let mut x = position.x + bar_size.x + row_gap;
+        for (kind, remaining) in bar.effects.iter() {
+            let width = remaining.clamp(0.0, EFFECT_MAX_SECONDS) * EFFECT_WIDTH_PER_SECOND;
+            let size = glam::vec2(width.max(1.0), HEALTH_BAR_SIZE.y) * hud.scale;
+            hud.rect(glam::vec2(x, position.y), size, effect_color(*kind));
+            x += size.x + row_gap;
+        }
+    }
+}
+
+fn effect_color(kind: StatusEffectKind) -> [f32; 4] {
+    match kind {
+        StatusEffectKind::Shield => SHIELD_COLOR,
+        StatusEffectKind::SpeedBoost => BOOST_COLOR,
+        StatusEffectKind::SlowMo => SLOW_MO_COLOR,
+        StatusEffectKind::Invulnerable => INVULNERABLE_COLOR,
+    }
+}
+
+// Points in the top right, with the multiplier under them while it's above 1
+fn score(hud: &mut HudBuilder, run: &RunHud) {
+    let points = run.score.to_string();
+    let cell = SCORE_CELL * hud.scale;
+    let size = font::text_size(&points, cell);
+    let position = hud.place(Anchor::TopRight, size);
+    hud.text(position, &points, cell, TEXT_COLOR);
+
+    if run.multiplier > 1.0 {
+        let multiplier = format!("x{}", (run.multiplier * 10.0).round() / 10.0);
+        let cell = MULTIPLIER_CELL * hud.scale;
+        let multiplier_size = font::text_size(&multiplier, cell);
+        let below = glam::vec2(
+            position.x + size.x - multiplier_size.x,
+            position.y + size.y + ROW_GAP * hud.scale,
+        );
+        hud.text(below, &multiplier, cell, BOOST_COLOR);
+    }
+}
+
+// Ships left in the bottom left, a row per pool with the bottom row for the first
+fn lives(hud: &mut HudBuilder, run: &RunHud) {
+    let icon = LIFE_ICON_SIZE * hud.scale;
+    let row_gap = ROW_GAP * hud.scale;
+    let widest = run.lives.iter().map(|(count, _)| *count).max().unwrap_or(0);
+    let rows = run.lives.len() as f32;
+    let size = glam::vec2(
+        widest as f32 * icon,
+        rows * icon + (rows - 1.0).max(0.0) * row_gap,
+    );
+    let origin = hud.place(Anchor::BottomLeft, size);
+    for (row, (count, tint)) in run.lives.iter().enumerate() {
+        let y = origin.y + size.y - (row as f32 + 1.0) * icon - row as f32 * row_gap;
+        for idx in 0..*count {
+            let position = glam::vec2(origin.x + idx as f32 * icon, y);
+            hud.quads.push(HudQuad::sprite(
+                LIFE_SPRITE,
+                position,
+                glam::Vec2::splat(icon),
+                *tint,
+            ));
+        }
+    }
+}
+
+// Bottom right, going from yellow to red as the radiation gets worse
+fn radiation_meter(hud: &mut HudBuilder, run: &RunHud) {
+    let size = RADIATION_METER_SIZE * hud.scale;
+    let position = hud.place(Anchor::BottomRight, size);
+    let mut color = [0.0; 4];
+    for (channel, value) in color.iter_mut().enumerate() {
+        *value = RADIATION_LOW_COLOR[channel]
+            + (RADIATION_HIGH_COLOR[channel] - RADIATION_LOW_COLOR[channel]) * run.radiation;
+    }
+    hud.bar(position, size, run.radiation, color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::layout;
+    use crate::game::resource::{GameStateForRenderer, PlayerBar, RunHud};
+
+    #[test]
+    fn test_widgets_stay_in_their_corners() {
+        let state = GameStateForRenderer {
+            player_bars: vec![PlayerBar {
+                health: 0.5,
+                tint: [1.0; 4],
+                effects: Vec::new(),
+            }],
+            run_hud: Some(RunHud {
+                score: 1234,
+                multiplier: 1.0,
+                lives: vec![(3, [1.0; 4])],
+                radiation: 0.5,
+            }),
+            ..Default::default()
+        };
+
+        for (width, height) in [(1280, 720), (1920, 1080), (800, 600), (320, 200)] {
+            let quads = layout(&state, (width, height));
+            let (width, height) = (width as f32, height as f32);
+            let right = quads
+                .iter()
+                .map(|quad| quad.position[0] + quad.size[0])
+                .fold(0.0, f32::max);
+            let bottom = quads
+                .iter()
+                .map(|quad| quad.position[1] + quad.size[1])
+                .fold(0.0, f32::max);
+            let left = quads
+                .iter()
+                .map(|quad| quad.position[0])
+                .fold(width, f32::min);
+            let top = quads
+                .iter()
+                .map(|quad| quad.position[1])
+                .fold(height, f32::min);
+
+            // The same margin all around, a little less when the window is small
+            let margin = left;
+            assert!(margin > 0.0 && margin <= 16.0 * 1.5);
+            assert!((top - margin).abs() < 1e-3);
+            assert!((width - right - margin).abs() < 1e-3);
+            assert!((height - bottom - margin).abs() < 1e-3);
+        }
+    }
+}
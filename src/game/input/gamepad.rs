@@ -0,0 +1,92 @@
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+
+use super::{Action, ActionEvent, InputFrame, InputSource};
+
+// How far the stick has to lean before the ship starts turning
+const STICK_TURN_THRESHOLD: f32 = 0.5;
+
+// Controllers through gilrs, each new pad takes the next free player slot
+pub struct GamepadInputSource {
+    gilrs: Gilrs,
+    slots: Vec<GamepadId>,
+    // Which way each slot's stick is currently turning the ship
+    stick_turn: Vec<i8>,
+}
+
+impl GamepadInputSource {
+    pub fn new() -> Result<Self, Box<gilrs::Error>> {
+        Ok(Self {
+            gilrs: Gilrs::new().map_err(Box::new)?,
+            slots: Vec::new(),
+            stick_turn: Vec::new(),
+        })
+    }
+
+    fn slot_for(&mut self, id: GamepadId) -> usize {
+        match self.slots.iter().position(|slot_id| *slot_id == id) {
+            Some(slot) => slot,
+            None => {
+                self.slots.push(id);
+                self.stick_turn.push(0);
+                self.slots.len() - 1
+            }
+        }
+    }
+}
+
+fn button_actions(button: Button) -> &'static [Action] {
+    match button {
+        Button::South | Button::RightTrigger => &[Action::Fire],
+        Button::RightTrigger2 | Button::DPadUp => &[Action::Thrust],
+        Button::LeftTrigger2 | Button::DPadDown => &[Action::Brake],
+        Button::DPadLeft => &[Action::RotateLeft],
+        Button::DPadRight => &[Action::RotateRight],
+        Button::Start => &[Action::Confirm, Action::Pause],
+        _ => &[],
+    }
+}
+
+impl InputSource for GamepadInputSource {
+    fn poll(&mut self, _frame: &InputFrame, events: &mut Vec<ActionEvent>) {
+        while let Some(event) = self.gilrs.next_event() {
+            let slot = self.slot_for(event.id);
+            match event.event {
+                EventType::ButtonPressed(button, _) => events.extend(
+                    button_actions(button)
+                        .iter()
+                        .map(|action| ActionEvent::Pressed(slot, *action)),
+                ),
+                EventType::ButtonReleased(button, _) => events.extend(
+                    button_actions(button)
+                        .iter()
+                        .map(|action| ActionEvent::Released(slot, *action)),
+                ),
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => {
+                    let turn = if value < -STICK_TURN_THRESHOLD {
+                        1
+                    } else if value > STICK_TURN_THRESHOLD {
+                        -1
+                    } else {
+                        0
+                    };
+                    let previous = self.stick_turn[slot];
+                    if turn == previous {
+                        continue;
+                    }
+                    match previous {
+                        1 => events.push(ActionEvent::Released(slot, Action::RotateLeft)),
+                        -1 => events.push(ActionEvent::Released(slot, Action::RotateRight)),
+                        _ => (),
+                    }
+                    match turn {
+                        1 => events.push(ActionEvent::Pressed(slot, Action::RotateLeft)),
+                        -1 => events.push(ActionEvent::Pressed(slot, Action::RotateRight)),
+                        _ => (),
+                    }
+                    self.stick_turn[slot] = turn;
+                }
+                _ => (),
+            }
+        }
+    }
+}
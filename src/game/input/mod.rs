@@ -0,0 +1,242 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use crate::assets::load_ron_or_default;
+
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod source;
+
+#[cfg(feature = "gamepad")]
+pub use gamepad::GamepadInputSource;
+pub use source::{
+    ActionEvent, InputFrame, InputSource, RandomInput, ScriptedInput, WinitInputSource,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Thrust,
+    Brake,
+    RotateLeft,
+    RotateRight,
+    Fire,
+    Confirm,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::Thrust,
+        Action::Brake,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Fire,
+        Action::Confirm,
+        Action::Pause,
+    ];
+
+    fn idx(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ActionState {
+    pub pressed: bool,
+    // Only set for the tick the action changed on
+    pub just_pressed: bool,
+    pub just_released: bool,
+}
+
+// Action states per player slot, refreshed at the start of every tick
+#[derive(Debug, Default)]
+pub struct ActionStates {
+    slots: Vec<[ActionState; Action::ALL.len()]>,
+}
+
+impl ActionStates {
+    pub fn get(&self, slot: usize, action: Action) -> ActionState {
+        self.slots
+            .get(slot)
+            .map(|states| states[action.idx()])
+            .unwrap_or_default()
+    }
+
+    pub fn pressed(&self, slot: usize, action: Action) -> bool {
+        self.get(slot, action).pressed
+    }
+
+    pub fn just_pressed(&self, slot: usize, action: Action) -> bool {
+        self.get(slot, action).just_pressed
+    }
+
+    // For menu style actions that don't care which player asked
+    pub fn any_just_pressed(&self, action: Action) -> bool {
+        self.slots
+            .iter()
+            .any(|states| states[action.idx()].just_pressed)
+    }
+
+    pub fn apply(&mut self, event: ActionEvent) {
+        match event {
+            ActionEvent::Pressed(slot, action) => self.press(slot, action),
+            ActionEvent::Released(slot, action) => self.release(slot, action),
+        }
+    }
+
+    pub fn press(&mut self, slot: usize, action: Action) {
+        let state = self.state_mut(slot, action);
+        if !state.pressed {
+            state.pressed = true;
+            state.just_pressed = true;
+        }
+    }
+
+    pub fn release(&mut self, slot: usize, action: Action) {
+        let state = self.state_mut(slot, action);
+        if state.pressed {
+            state.pressed = false;
+            state.just_released = true;
+        }
+    }
+
+    // Drops the edge flags from the previous tick, held actions stay pressed
+    pub fn begin_tick(&mut self) {
+        for state in self.slots.iter_mut().flatten() {
+            state.just_pressed = false;
+            state.just_released = false;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+
+    fn state_mut(&mut self, slot: usize, action: Action) -> &mut ActionState {
+        if self.slots.len() <= slot {
+            self.slots
+                .resize(slot + 1, [ActionState::default(); Action::ALL.len()]);
+        }
+        &mut self.slots[slot][action.idx()]
+    }
+}
+
+// There's only one mouse, it belongs to player one
+pub const MOUSE_SLOT: usize = 0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputBindings {
+    // One binding map per player slot, this is also how many players can share the keyboard
+    pub players: Vec<HashMap<Action, Vec<VirtualKeyCode>>>,
+    // Only used with the mouse control scheme
+    #[serde(default)]
+    pub mouse: HashMap<Action, Vec<MouseButton>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self::from_ron_str(include_str!("../../../assets/input.ron")).unwrap()
+    }
+}
+
+impl InputBindings {
+    pub fn from_ron_str(data: &str) -> Result<Self, ron::Error> {
+        ron::from_str(data)
+    }
+
+    // User bindings if there are any, the built-in ones if the file is missing or broken
+    pub fn load_or_default(path: &Path) -> Self {
+        let bindings: Self = load_ron_or_default(path);
+        if bindings.players.is_empty() {
+            log::warn!("{} has no players, using default bindings", path.display());
+            return Self::default();
+        }
+        bindings
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
+    // Every (slot, action) the key is bound to, a key may drive several
+    pub fn actions_for_key(
+        &self,
+        key: VirtualKeyCode,
+    ) -> impl Iterator<Item = (usize, Action)> + '_ {
+        self.players
+            .iter()
+            .enumerate()
+            .flat_map(move |(slot, actions)| {
+                actions
+                    .iter()
+                    .filter(move |(_, keys)| keys.contains(&key))
+                    .map(move |(action, _)| (slot, *action))
+            })
+    }
+
+    pub fn actions_for_mouse_button(
+        &self,
+        button: MouseButton,
+    ) -> impl Iterator<Item = Action> + '_ {
+        self.mouse
+            .iter()
+            .filter(move |(_, buttons)| buttons.contains(&button))
+            .map(|(action, _)| *action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::{MouseButton, VirtualKeyCode};
+
+    use super::{Action, ActionStates, InputBindings};
+
+    #[test]
+    fn test_default_bindings_parse() {
+        let bindings = InputBindings::default();
+        assert_eq!(bindings.player_count(), 2);
+
+        let actions: Vec<_> = bindings.actions_for_key(VirtualKeyCode::W).collect();
+        assert_eq!(actions, vec![(0, Action::Thrust)]);
+        let actions: Vec<_> = bindings.actions_for_key(VirtualKeyCode::Up).collect();
+        assert_eq!(actions, vec![(1, Action::Thrust)]);
+        let actions: Vec<_> = bindings
+            .actions_for_mouse_button(MouseButton::Left)
+            .collect();
+        assert_eq!(actions, vec![Action::Fire]);
+    }
+
+    #[test]
+    fn test_rebinding() {
+        // AZERTY players want ZQSD
+        let bindings =
+            InputBindings::from_ron_str("(players: [{ Thrust: [Z, Up], RotateLeft: [Q] }])")
+                .unwrap();
+        let actions: Vec<_> = bindings.actions_for_key(VirtualKeyCode::Z).collect();
+        assert_eq!(actions, vec![(0, Action::Thrust)]);
+        assert_eq!(bindings.actions_for_key(VirtualKeyCode::W).count(), 0);
+    }
+
+    #[test]
+    fn test_action_edges() {
+        let mut states = ActionStates::default();
+        states.begin_tick();
+        states.press(1, Action::Fire);
+        assert!(states.pressed(1, Action::Fire));
+        assert!(states.just_pressed(1, Action::Fire));
+        assert!(!states.pressed(0, Action::Fire));
+
+        // Key repeat doesn't count as a fresh press
+        states.begin_tick();
+        states.press(1, Action::Fire);
+        assert!(states.pressed(1, Action::Fire));
+        assert!(!states.just_pressed(1, Action::Fire));
+
+        states.begin_tick();
+        states.release(1, Action::Fire);
+        assert!(!states.pressed(1, Action::Fire));
+        assert!(states.get(1, Action::Fire).just_released);
+    }
+}
@@ -0,0 +1,215 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::game::resource::{KeyboardEvent, MouseEvent};
+
+use super::{Action, InputBindings, MOUSE_SLOT};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ActionEvent {
+    // Player slot and the action they started or stopped
+    Pressed(usize, Action),
+    Released(usize, Action),
+}
+
+// Everything a source might want to look at for the current tick
+pub struct InputFrame<'a> {
+    pub keyboard: &'a [KeyboardEvent],
+    pub mouse: &'a [MouseEvent],
+    pub bindings: &'a InputBindings,
+    pub mouse_enabled: bool,
+}
+
+// Something that produces actions, polled once per tick by the InputSystem
+pub trait InputSource {
+    fn poll(&mut self, frame: &InputFrame, events: &mut Vec<ActionEvent>);
+}
+
+// Keyboard and mouse buttons coming in through winit window events
+#[derive(Default)]
+pub struct WinitInputSource {}
+
+impl InputSource for WinitInputSource {
+    fn poll(&mut self, frame: &InputFrame, events: &mut Vec<ActionEvent>) {
+        for event in frame.keyboard {
+            match *event {
+                KeyboardEvent::Pressed(key) => events.extend(
+                    frame
+                        .bindings
+                        .actions_for_key(key)
+                        .map(|(slot, action)| ActionEvent::Pressed(slot, action)),
+                ),
+                KeyboardEvent::Released(key) => events.extend(
+                    frame
+                        .bindings
+                        .actions_for_key(key)
+                        .map(|(slot, action)| ActionEvent::Released(slot, action)),
+                ),
+            }
+        }
+
+        for event in frame.mouse {
+            match *event {
+                MouseEvent::Pressed(button) if frame.mouse_enabled => events.extend(
+                    frame
+                        .bindings
+                        .actions_for_mouse_button(button)
+                        .map(|action| ActionEvent::Pressed(MOUSE_SLOT, action)),
+                ),
+                // Released even when disabled, so switching schemes can't leave a button stuck
+                MouseEvent::Released(button) => events.extend(
+                    frame
+                        .bindings
+                        .actions_for_mouse_button(button)
+                        .map(|action| ActionEvent::Released(MOUSE_SLOT, action)),
+                ),
+                _ => (),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScriptStep {
+    pub tick: u64,
+    pub event: ActionEvent,
+}
+
+// Replays a fixed list of actions at given ticks, for driving the game without a window
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScriptedInput {
+    steps: Vec<ScriptStep>,
+    #[serde(skip)]
+    tick: u64,
+}
+
+impl ScriptedInput {
+    pub fn from_ron_str(data: &str) -> Result<Self, ron::Error> {
+        let mut script: Self = ron::from_str(data)?;
+        script.steps.sort_by_key(|step| step.tick);
+        Ok(script)
+    }
+
+    pub fn press(self, tick: u64, slot: usize, action: Action) -> Self {
+        self.with_step(tick, ActionEvent::Pressed(slot, action))
+    }
+
+    pub fn release(self, tick: u64, slot: usize, action: Action) -> Self {
+        self.with_step(tick, ActionEvent::Released(slot, action))
+    }
+
+    // Press on one tick, release on the next
+    pub fn tap(self, tick: u64, slot: usize, action: Action) -> Self {
+        self.press(tick, slot, action)
+            .release(tick + 1, slot, action)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.steps.iter().all(|step| step.tick < self.tick)
+    }
+
+    fn with_step(mut self, tick: u64, event: ActionEvent) -> Self {
+        let idx = self.steps.partition_point(|step| step.tick <= tick);
+        self.steps.insert(idx, ScriptStep { tick, event });
+        self
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn poll(&mut self, _frame: &InputFrame, events: &mut Vec<ActionEvent>) {
+        let tick = self.tick;
+        events.extend(
+            self.steps
+                .iter()
+                .filter(|step| step.tick == tick)
+                .map(|step| step.event),
+        );
+        self.tick += 1;
+    }
+}
+
+// Chance per tick that a random input lets go of or presses down any one action
+const RANDOM_TOGGLE_CHANCE: f64 = 0.05;
+
+// Mashes buttons for one player slot, a baseline for how long the game lasts without a plan
+pub struct RandomInput {
+    slot: usize,
+    rng: StdRng,
+    held: Vec<Action>,
+}
+
+impl RandomInput {
+    pub fn new(slot: usize, seed: u64) -> Self {
+        Self {
+            slot,
+            rng: StdRng::seed_from_u64(seed),
+            held: Vec::new(),
+        }
+    }
+}
+
+impl InputSource for RandomInput {
+    fn poll(&mut self, _frame: &InputFrame, events: &mut Vec<ActionEvent>) {
+        // Never pauses, a paused game would just sit there until the next lucky press
+        for action in Action::ALL
+            .iter()
+            .filter(|action| **action != Action::Pause)
+        {
+            if !self.rng.gen_bool(RANDOM_TOGGLE_CHANCE) {
+                continue;
+            }
+            if let Some(idx) = self.held.iter().position(|held| held == action) {
+                self.held.swap_remove(idx);
+                events.push(ActionEvent::Released(self.slot, *action));
+            } else {
+                self.held.push(*action);
+                events.push(ActionEvent::Pressed(self.slot, *action));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ActionEvent, InputFrame, InputSource, ScriptedInput};
+    use crate::game::input::{Action, InputBindings};
+
+    #[test]
+    fn test_scripted_input() {
+        let bindings = InputBindings::default();
+        let frame = InputFrame {
+            keyboard: &[],
+            mouse: &[],
+            bindings: &bindings,
+            mouse_enabled: false,
+        };
+        let mut script = ScriptedInput::from_ron_str(
+            "(steps: [
+                (tick: 2, event: Released(0, Thrust)),
+                (tick: 0, event: Pressed(0, Thrust)),
+            ])",
+        )
+        .unwrap()
+        .tap(1, 1, Action::Fire);
+
+        let mut polled = Vec::new();
+        for _ in 0..3 {
+            let mut events = Vec::new();
+            script.poll(&frame, &mut events);
+            polled.push(events);
+        }
+
+        assert_eq!(
+            polled,
+            vec![
+                vec![ActionEvent::Pressed(0, Action::Thrust)],
+                vec![ActionEvent::Pressed(1, Action::Fire)],
+                vec![
+                    ActionEvent::Released(0, Action::Thrust),
+                    ActionEvent::Released(1, Action::Fire)
+                ],
+            ]
+        );
+        assert!(script.is_finished());
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{
+    game::{font, input::Action},
+    renderer::HudQuad,
+};
+
+// Sizes are in pixels at the reference height of `font::ui_scale`, same as the HUD
+const TITLE_CELL: f32 = 8.0;
+const LINE_CELL: f32 = 3.0;
+const ITEM_CELL: f32 = 4.0;
+const LINE_GAP: f32 = 6.0;
+const ITEM_GAP: f32 = 4.0;
+const ITEM_PADDING: f32 = 8.0;
+const MIN_ITEM_WIDTH: f32 = 240.0;
+// Between the title, the lines and the items
+const SECTION_GAP: f32 = 24.0;
+const PANEL_PADDING: f32 = 24.0;
+
+// Dims the frozen game behind the pause and game over menus
+const BACKDROP_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+const PANEL_COLOR: [f32; 4] = [0.05, 0.05, 0.1, 0.8];
+const ITEM_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.08];
+const SELECTED_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 0.35];
+const TITLE_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+const LINE_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuScreen {
+    Title,
+    Settings,
+    GameSettings,
+    VideoSettings,
+    AudioSettings,
+    ControlSettings,
+    HighScores,
+    Pause,
+    GameOver,
+}
+
+impl MenuScreen {
+    // Screens reached from the title screen, they stay up until a run starts
+    pub fn is_title(self) -> bool {
+        !matches!(self, MenuScreen::Pause | MenuScreen::GameOver)
+    }
+
+    // Where going back leads
+    pub fn parent(self) -> Option<MenuScreen> {
+        match self {
+            MenuScreen::Settings | MenuScreen::HighScores => Some(MenuScreen::Title),
+            MenuScreen::GameSettings
+            | MenuScreen::VideoSettings
+            | MenuScreen::AudioSettings
+            | MenuScreen::ControlSettings => Some(MenuScreen::Settings),
+            MenuScreen::Title | MenuScreen::Pause | MenuScreen::GameOver => None,
+        }
+    }
+}
+
+// Values that step back and forth on the settings screens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Difficulty,
+    Players,
+    ControlScheme,
+    LivesMode,
+    Resolution,
+    WindowMode,
+    Vsync,
+    Bloom,
+    Crt,
+    ChromaticAberration,
+    ScreenShake,
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    Start,
+    Open(MenuScreen),
+    Back,
+    // Closes the game
+    Quit,
+    Resume,
+    Restart,
+    // Gives up on the run and goes back to the title screen
+    QuitRun,
+    Retry,
+    MainMenu,
+    Change(Setting),
+    // Waits for the next key and binds it to the action for player one
+    Rebind(Action),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuItem {
+    pub label: String,
+    pub action: MenuAction,
+}
+
+impl MenuItem {
+    pub fn new(label: impl Into<String>, action: MenuAction) -> Self {
+        Self {
+            label: label.into(),
+            action,
+        }
+    }
+}
+
+// The screen and selection stay put while the text is filled in again every tick, so the
+// values on it are always current
+#[derive(Debug, Clone, PartialEq)]
+pub struct Menu {
+    pub screen: MenuScreen,
+    pub selected: usize,
+    pub title: String,
+    // Text shown between the title and the items, like run stats
+    pub lines: Vec<String>,
+    pub items: Vec<MenuItem>,
+}
+
+impl Menu {
+    pub fn new(screen: MenuScreen) -> Self {
+        Self {
+            screen,
+            selected: 0,
+            title: String::new(),
+            lines: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    // Wraps around at either end
+    pub fn move_selection(&mut self, step: i32) {
+        let count = self.items.len() as i32;
+        if count > 0 {
+            self.selected = (self.selected as i32 + step).rem_euclid(count) as usize;
+        }
+    }
+
+    pub fn selected_action(&self) -> Option<MenuAction> {
+        self.items.get(self.selected).map(|item| item.action)
+    }
+}
+
+// The menu on screen, if any
+#[derive(Debug, Default)]
+pub struct ActiveMenu(pub Option<Menu>);
+
+// What the player picked this tick that the game manager has to carry out
+#[derive(Debug, Default)]
+pub struct MenuCommand(pub Option<MenuAction>);
+
+type Rect = (glam::Vec2, glam::Vec2);
+
+// Where everything goes, shared by drawing and mouse picking so they always agree
+struct MenuGeometry {
+    scale: f32,
+    panel: Rect,
+    title: glam::Vec2,
+    lines: Vec<glam::Vec2>,
+    items: Vec<Rect>,
+}
+
+// Centered in the window, stacked title, lines and then items
+fn geometry(menu: &Menu, window_size: glam::Vec2) -> MenuGeometry {
+    let scale = font::ui_scale(window_size.y);
+    let title_size = font::text_size(&menu.title, TITLE_CELL * scale);
+    let line_sizes: Vec<_> = menu
+        .lines
+        .iter()
+        .map(|line| font::text_size(line, LINE_CELL * scale))
+        .collect();
+    let item_padding = ITEM_PADDING * scale;
+    let item_width = menu
+        .items
+        .iter()
+        .map(|item| font::text_size(&item.label, ITEM_CELL * scale).x + 2.0 * item_padding)
+        .fold(MIN_ITEM_WIDTH * scale, f32::max);
+    let item_height = font::GLYPH_HEIGHT as f32 * ITEM_CELL * scale + 2.0 * item_padding;
+
+    let width = line_sizes
+        .iter()
+        .map(|size| size.x)
+        .fold(title_size.x.max(item_width), f32::max);
+    let section_gap = SECTION_GAP * scale;
+    let stack = |count: usize, height: f32, gap: f32| {
+        if count == 0 {
+            0.0
+        } else {
+            section_gap + count as f32 * (height + gap) - gap
+        }
+    };
+    let line_height = font::GLYPH_HEIGHT as f32 * LINE_CELL * scale;
+    let height = title_size.y
+        + stack(line_sizes.len(), line_height, LINE_GAP * scale)
+        + stack(menu.items.len(), item_height, ITEM_GAP * scale);
+
+    let padding = PANEL_PADDING * scale;
+    let panel_size = glam::vec2(width, height) + glam::Vec2::splat(2.0 * padding);
+    let panel_position = (window_size - panel_size) / 2.0;
+    let center_x = window_size.x / 2.0;
+
+    let mut y = panel_position.y + padding;
+    let title = glam::vec2(center_x - title_size.x / 2.0, y);
+    y += title_size.y;
+
+    if !line_sizes.is_empty() {
+        y += section_gap;
+    }
+    let lines = line_sizes
+        .iter()
+        .map(|size| {
+            let position = glam::vec2(center_x - size.x / 2.0, y);
+            y += line_height + LINE_GAP * scale;
+            position
+        })
+        .collect();
+    if !line_sizes.is_empty() {
+        y -= LINE_GAP * scale;
+    }
+
+    if !menu.items.is_empty() {
+        y += section_gap;
+    }
+    let items = menu
+        .items
+        .iter()
+        .map(|_| {
+            let rect = (
+                glam::vec2(center_x - item_width / 2.0, y),
+                glam::vec2(item_width, item_height),
+            );
+            y += item_height + ITEM_GAP * scale;
+            rect
+        })
+        .collect();
+
+    MenuGeometry {
+        scale,
+        panel: (panel_position, panel_size),
+        title,
+        lines,
+        items,
+    }
+}
+
+pub fn layout(menu: &Menu, window_size: (u32, u32)) -> Vec<HudQuad> {
+    let window_size = glam::vec2(window_size.0 as f32, window_size.1 as f32);
+    let geometry = geometry(menu, window_size);
+    let mut quads = Vec::new();
+
+    if matches!(menu.screen, MenuScreen::Pause | MenuScreen::GameOver) {
+        quads.push(HudQuad::solid(
+            glam::Vec2::ZERO,
+            window_size,
+            BACKDROP_COLOR,
+        ));
+    }
+    let (panel_position, panel_size) = geometry.panel;
+    quads.push(HudQuad::solid(panel_position, panel_size, PANEL_COLOR));
+
+    let scale = geometry.scale;
+    font::text(
+        &mut quads,
+        geometry.title,
+        &menu.title,
+        TITLE_CELL * scale,
+        TITLE_COLOR,
+    );
+    for (line, position) in menu.lines.iter().zip(geometry.lines.iter()) {
+        font::text(&mut quads, *position, line, LINE_CELL * scale, LINE_COLOR);
+    }
+    for (idx, (item, (position, size))) in menu.items.iter().zip(geometry.items).enumerate() {
+        let color = if idx == menu.selected {
+            SELECTED_COLOR
+        } else {
+            ITEM_COLOR
+        };
+        quads.push(HudQuad::solid(position, size, color));
+
+        let cell = ITEM_CELL * scale;
+        let text_size = font::text_size(&item.label, cell);
+        let text_position = position + (size - text_size) / 2.0;
+        font::text(&mut quads, text_position, &item.label, cell, TEXT_COLOR);
+    }
+    quads
+}
+
+// The item under a point in window pixels
+pub fn item_at(menu: &Menu, window_size: (u32, u32), point: glam::Vec2) -> Option<usize> {
+    let window_size = glam::vec2(window_size.0 as f32, window_size.1 as f32);
+    geometry(menu, window_size)
+        .items
+        .iter()
+        .position(|(position, size)| {
+            let end = *position + *size;
+            point.x >= position.x && point.y >= position.y && point.x < end.x && point.y < end.y
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        item_at, layout, Menu, MenuAction, MenuItem, MenuScreen, ITEM_COLOR, SELECTED_COLOR,
+    };
+
+    fn pause_menu() -> Menu {
+        Menu {
+            title: "PAUSED".to_string(),
+            lines: vec!["SCORE 1234".to_string()],
+            items: vec![
+                MenuItem::new("RESUME", MenuAction::Resume),
+                MenuItem::new("RESTART", MenuAction::Restart),
+                MenuItem::new("QUIT", MenuAction::QuitRun),
+            ],
+            ..Menu::new(MenuScreen::Pause)
+        }
+    }
+
+    #[test]
+    fn test_items_are_picked_where_they_are_drawn() {
+        let menu = pause_menu();
+        for window_size in [(1280, 720), (800, 600), (320, 200)] {
+            let quads = layout(&menu, window_size);
+            let item_quads: Vec<_> = quads
+                .iter()
+                .filter(|quad| quad.color == ITEM_COLOR || quad.color == SELECTED_COLOR)
+                .collect();
+            assert_eq!(item_quads.len(), menu.items.len());
+
+            for (idx, quad) in item_quads.iter().enumerate() {
+                let center = glam::vec2(
+                    quad.position[0] + quad.size[0] / 2.0,
+                    quad.position[1] + quad.size[1] / 2.0,
+                );
+                assert_eq!(item_at(&menu, window_size, center), Some(idx));
+                assert!(quad.position[0] >= 0.0 && quad.position[1] >= 0.0);
+                assert!(quad.position[1] + quad.size[1] <= window_size.1 as f32);
+            }
+            assert_eq!(item_at(&menu, window_size, glam::vec2(1.0, 1.0)), None);
+        }
+    }
+
+    #[test]
+    fn test_selection_wraps() {
+        let mut menu = pause_menu();
+        menu.move_selection(-1);
+        assert_eq!(menu.selected_action(), Some(MenuAction::QuitRun));
+        menu.move_selection(1);
+        assert_eq!(menu.selected_action(), Some(MenuAction::Resume));
+    }
+}
@@ -1,3 +1,11 @@
 pub mod component;
+pub mod enemy;
+pub mod font;
+pub mod hud;
+pub mod input;
+pub mod menu;
+pub mod particles;
 pub mod resource;
+pub mod save;
 pub mod system;
+pub mod wave;
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+// Emitters stop spawning once this many particles are alive
+pub const MAX_PARTICLES: usize = 50_000;
+
+// How an emitter spawns particles and how they look over their life. Ranges are picked from per
+// particle, start and end values are blended from birth to death.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmitterSettings {
+    pub sprite_idx: u32,
+    // Particles per second
+    pub rate: f32,
+    // Seconds
+    pub lifetime: (f32, f32),
+    // Pixels per second
+    pub speed: (f32, f32),
+    // Middle of the cone particles leave in, and how far to either side of it they can go in
+    // radians
+    pub direction: glam::Vec2,
+    pub spread: f32,
+    // Half size of the box around the emitter particles appear in
+    pub area: glam::Vec2,
+    pub color: ([f32; 4], [f32; 4]),
+    pub size: (f32, f32),
+    // Particles thrown out at once when used for a one-off effect
+    pub burst: usize,
+}
+
+impl Default for EmitterSettings {
+    fn default() -> Self {
+        Self {
+            sprite_idx: 2,
+            rate: 10.0,
+            lifetime: (1.0, 1.0),
+            speed: (0.0, 0.0),
+            direction: glam::vec2(0.0, 1.0),
+            spread: std::f32::consts::PI,
+            area: glam::Vec2::ZERO,
+            color: ([1.0; 4], [1.0; 4]),
+            size: (2.0, 2.0),
+            burst: 0,
+        }
+    }
+}
+
+// Names of the effects the game uses, defined in particles.ron
+pub const THRUST_EFFECT: &str = "thrust";
+pub const ASTEROID_HIT_EFFECT: &str = "asteroid_hit";
+pub const ASTEROID_DEBRIS_EFFECT: &str = "asteroid_debris";
+pub const REPAIR_EFFECT: &str = "repair_sparkles";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ParticlePresets(pub HashMap<String, EmitterSettings>);
+
+impl Default for ParticlePresets {
+    fn default() -> Self {
+        Self::from_ron_str(include_str!("../../assets/particles.ron")).unwrap()
+    }
+}
+
+impl ParticlePresets {
+    pub fn from_ron_str(data: &str) -> Result<Self, ron::Error> {
+        ron::from_str(data)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EmitterSettings> {
+        self.0.get(name)
+    }
+}
+
+// One-off effects asked for by the game systems this tick, the particle system plays them
+#[derive(Debug, Default)]
+pub struct ParticleBursts(pub Vec<(&'static str, glam::Vec2)>);
+
+impl ParticleBursts {
+    pub fn push(&mut self, preset: &'static str, position: glam::Vec2) {
+        self.0.push((preset, position));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmitterId(usize);
+
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    pub settings: EmitterSettings,
+    pub position: glam::Vec2,
+    pub enabled: bool,
+    // Fraction of a particle carried over between updates
+    pending: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Particle {
+    position: glam::Vec2,
+    velocity: glam::Vec2,
+    age: f32,
+    lifetime: f32,
+    sprite_idx: u32,
+    color: ([f32; 4], [f32; 4]),
+    size: (f32, f32),
+}
+
+// Particles live here instead of in the world, they are only for show and there can be a lot of
+// them. Nothing in the game reads them back and they are left out of saves.
+#[derive(Debug)]
+pub struct ParticleField {
+    particles: Vec<Particle>,
+    emitters: Vec<Option<ParticleEmitter>>,
+    // Separate from GameRng so effects don't change how a seeded run plays
+    rng: ChaCha8Rng,
+}
+
+impl Default for ParticleField {
+    fn default() -> Self {
+        Self {
+            particles: Vec::new(),
+            emitters: Vec::new(),
+            rng: ChaCha8Rng::seed_from_u64(0),
+        }
+    }
+}
+
+impl ParticleField {
+    pub fn add_emitter(&mut self, settings: EmitterSettings, position: glam::Vec2) -> EmitterId {
+        let emitter = Some(ParticleEmitter {
+            settings,
+            position,
+            enabled: true,
+            pending: 0.0,
+        });
+        match self.emitters.iter().position(Option::is_none) {
+            Some(idx) => {
+                self.emitters[idx] = emitter;
+                EmitterId(idx)
+            }
+            None => {
+                self.emitters.push(emitter);
+                EmitterId(self.emitters.len() - 1)
+            }
+        }
+    }
+
+    pub fn remove_emitter(&mut self, id: EmitterId) {
+        if let Some(slot) = self.emitters.get_mut(id.0) {
+            *slot = None;
+        }
+    }
+
+    pub fn emitter_mut(&mut self, id: EmitterId) -> Option<&mut ParticleEmitter> {
+        self.emitters.get_mut(id.0).and_then(Option::as_mut)
+    }
+
+    // Spawns particles all at once, for things that happen in an instant
+    pub fn burst(&mut self, settings: &EmitterSettings, position: glam::Vec2, count: usize) {
+        for _ in 0..count {
+            self.spawn(settings, position);
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for particle in self.particles.iter_mut() {
+            particle.age += delta_time;
+            particle.position += particle.velocity * delta_time;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        // Taken out for the loop so spawning can borrow the rest of the field
+        let mut emitters = std::mem::take(&mut self.emitters);
+        for emitter in emitters.iter_mut().flatten() {
+            if !emitter.enabled {
+                emitter.pending = 0.0;
+                continue;
+            }
+            emitter.pending += emitter.settings.rate * delta_time;
+            while emitter.pending >= 1.0 {
+                emitter.pending -= 1.0;
+                self.spawn(&emitter.settings, emitter.position);
+            }
+        }
+        self.emitters = emitters;
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    // Sprite, model matrix and tint of every live particle, the same as the renderer takes
+    pub fn instances(&self) -> impl Iterator<Item = (u32, [[f32; 4]; 4], [f32; 4])> + '_ {
+        self.particles.iter().map(|particle| {
+            let t = particle.age / particle.lifetime;
+            let size = lerp(particle.size.0, particle.size.1, t);
+            let (start, end) = particle.color;
+            let mut tint = [0.0; 4];
+            for channel in 0..4 {
+                tint[channel] = lerp(start[channel], end[channel], t);
+            }
+            let model_matrix = glam::Mat4::from_scale_rotation_translation(
+                glam::vec3(size, size, 1.0),
+                glam::Quat::IDENTITY,
+                glam::vec3(particle.position.x, particle.position.y, -1.0),
+            );
+            (particle.sprite_idx, model_matrix.to_cols_array_2d(), tint)
+        })
+    }
+
+    fn spawn(&mut self, settings: &EmitterSettings, position: glam::Vec2) {
+        if self.particles.len() >= MAX_PARTICLES {
+            return;
+        }
+
+        let offset = glam::vec2(
+            self.rng.gen_range(-1.0..=1.0) * settings.area.x,
+            self.rng.gen_range(-1.0..=1.0) * settings.area.y,
+        );
+        let angle = self.rng.gen_range(-1.0..=1.0) * settings.spread;
+        let direction = glam::Mat2::from_angle(angle) * settings.direction.normalize_or_zero();
+        let speed = self.gen_between(settings.speed);
+        let lifetime = self.gen_between(settings.lifetime).max(f32::EPSILON);
+
+        self.particles.push(Particle {
+            position: position + offset,
+            velocity: direction * speed,
+            age: 0.0,
+            lifetime,
+            sprite_idx: settings.sprite_idx,
+            color: settings.color,
+            size: settings.size,
+        });
+    }
+
+    fn gen_between(&mut self, (min, max): (f32, f32)) -> f32 {
+        if max > min {
+            self.rng.gen_range(min..max)
+        } else {
+            min
+        }
+    }
+}
+
+fn lerp(start: f32, end: f32, t: f32) -> f32 {
+    start + (end - start) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EmitterSettings, ParticleField, ParticlePresets, ASTEROID_DEBRIS_EFFECT,
+        ASTEROID_HIT_EFFECT, REPAIR_EFFECT, THRUST_EFFECT,
+    };
+
+    #[test]
+    fn test_presets_cover_the_game() {
+        let presets = ParticlePresets::default();
+        for name in [
+            THRUST_EFFECT,
+            ASTEROID_HIT_EFFECT,
+            ASTEROID_DEBRIS_EFFECT,
+            REPAIR_EFFECT,
+        ] {
+            assert!(presets.get(name).is_some(), "no preset named {}", name);
+        }
+        assert!(presets.get(THRUST_EFFECT).unwrap().rate > 0.0);
+        assert!(presets.get(ASTEROID_DEBRIS_EFFECT).unwrap().burst > 0);
+    }
+
+    #[test]
+    fn test_emitter_keeps_its_rate() {
+        let mut field = ParticleField::default();
+        let id = field.add_emitter(
+            EmitterSettings {
+                rate: 30.0,
+                lifetime: (2.0, 2.0),
+                ..Default::default()
+            },
+            glam::Vec2::ZERO,
+        );
+
+        for _ in 0..60 {
+            field.update(1.0 / 60.0);
+        }
+        assert!((29..=30).contains(&field.len()));
+
+        // Everything spawned dies off once the emitter is off
+        field.emitter_mut(id).unwrap().enabled = false;
+        for _ in 0..150 {
+            field.update(1.0 / 60.0);
+        }
+        assert!(field.is_empty());
+    }
+
+    #[test]
+    fn test_particles_fade_over_their_life() {
+        let mut field = ParticleField::default();
+        let settings = EmitterSettings {
+            lifetime: (1.0, 1.0),
+            speed: (60.0, 60.0),
+            direction: glam::vec2(1.0, 0.0),
+            spread: 0.0,
+            color: ([1.0; 4], [1.0, 1.0, 1.0, 0.0]),
+            size: (4.0, 2.0),
+            ..Default::default()
+        };
+        field.burst(&settings, glam::vec2(10.0, 0.0), 3);
+        field.update(0.5);
+
+        for (_, model_matrix, tint) in field.instances() {
+            let model_matrix = glam::Mat4::from_cols_array_2d(&model_matrix);
+            let (scale, _, translation) = model_matrix.to_scale_rotation_translation();
+            assert!((translation.x - 40.0).abs() < 1e-4);
+            assert!((scale.x - 3.0).abs() < 1e-4);
+            assert!((tint[3] - 0.5).abs() < 1e-4);
+        }
+        assert_eq!(field.len(), 3);
+    }
+}
@@ -1,26 +1,61 @@
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 use specs::Entity;
-use winit::event::VirtualKeyCode;
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use super::component::{PickupKind, StatusEffectKind};
+use crate::assets::load_ron_or_default;
 
 #[derive(Default)]
 pub struct DeltaTime(pub Duration);
 
+// The frame time before `TimeScale` is applied, for timers that keep real time during slow-mo
+#[derive(Default)]
+pub struct UnscaledDeltaTime(pub Duration);
+
 #[derive(Debug)]
 pub enum WindowEvent {
     Resize(u32, u32),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum KeyboardEvent {
     Pressed(VirtualKeyCode),
     Released(VirtualKeyCode),
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MouseEvent {
+    // Cursor position in window pixels, origin at the top left
+    Moved(f32, f32),
+    Pressed(MouseButton),
+    Released(MouseButton),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ControlScheme {
+    #[default]
+    Keyboard,
+    // Player one turns towards the cursor and uses the mouse bindings
+    Mouse,
+}
+
+// Cursor in world coordinates, None until the mouse moved over the window
+#[derive(Debug, Default)]
+pub struct Cursor(pub Option<glam::Vec2>);
+
 #[derive(Debug)]
 pub enum GameState {
     GameStateInit {},
-    GameStatePlay { player_entity: Entity },
+    GameStatePlay { player_entities: Vec<Entity> },
     GameStateEnd {},
 }
 
@@ -30,11 +65,738 @@ impl Default for GameState {
     }
 }
 
+// Freezes the update systems, input keeps being processed
 #[derive(Debug, Default)]
+pub struct Paused(pub bool);
+
+// Ships in the next run, picked on the title screen
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayerCount(pub usize);
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
 pub struct GameWindowSize(pub u32, pub u32);
 
+// Set when the player picks Quit, the app closes the window once it sees it
+#[derive(Debug, Default)]
+pub struct QuitRequested(pub bool);
+
+// Where to save the next rendered frame, the render system takes it once the frame is drawn
+#[derive(Debug, Default)]
+pub struct ScreenshotRequest(pub Option<PathBuf>);
+
+#[derive(Debug, Clone)]
+pub struct PlayerBar {
+    pub health: f32,
+    pub tint: [f32; 4],
+    // Active effects and their seconds left
+    pub effects: Vec<(StatusEffectKind, f32)>,
+}
+
+// The parts of the HUD only shown during a run
+#[derive(Debug, Clone, Default)]
+pub struct RunHud {
+    pub score: u32,
+    pub multiplier: f32,
+    // Ships left, counting the ones in play, and their tint, one entry per pool
+    pub lives: Vec<(u32, [f32; 4])>,
+    // 0 to 1, how hard the star is hitting right now
+    pub radiation: f32,
+}
+
 #[derive(Debug, Default)]
 pub struct GameStateForRenderer {
-    pub player_health: f32,
+    // One health bar per player, ordered by slot
+    pub player_bars: Vec<PlayerBar>,
     pub background_idx: u32,
+    pub slow_mo_active: bool,
+    // 0 to 1, jumps up when a player takes a hit and fades out
+    pub damage_flash: f32,
+    // None on the title screen, kept on the game over screen
+    pub run_hud: Option<RunHud>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WaveStatus {
+    pub wave_idx: Option<usize>,
+    pub calm: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum BossPhase {
+    #[default]
+    Dormant,
+    // The star acts up for a bit before the boss shows
+    Flare,
+    Fight,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BossStatus {
+    pub phase: BossPhase,
+    pub health_fraction: f32,
+}
+
+// All gameplay randomness goes through here, so a seed reproduces a run. Same generator as
+// `StdRng`, but spelled out since that one can't be saved mid-stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRng(pub ChaCha12Rng);
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        GameRng(ChaCha12Rng::seed_from_u64(seed))
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng(ChaCha12Rng::from_entropy())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale(1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum LivesMode {
+    // Everyone draws from the same pool
+    #[default]
+    Shared,
+    Separate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lives {
+    pub mode: LivesMode,
+    // A single pool when shared, one per player slot otherwise
+    pub pools: Vec<u32>,
+    pub next_extra_life_at: f32,
+}
+
+impl Lives {
+    pub const STARTING: u32 = 3;
+    pub const MAX: u32 = 9;
+    pub const SCORE_PER_EXTRA_LIFE: f32 = 1000.0;
+
+    pub fn new(mode: LivesMode, player_count: usize) -> Self {
+        let pool_count = match mode {
+            LivesMode::Shared => 1,
+            LivesMode::Separate => player_count.max(1),
+        };
+        Self {
+            mode,
+            pools: vec![Self::STARTING; pool_count],
+            next_extra_life_at: Self::SCORE_PER_EXTRA_LIFE,
+        }
+    }
+
+    fn pool_idx(&self, slot: usize) -> usize {
+        match self.mode {
+            LivesMode::Shared => 0,
+            LivesMode::Separate => slot.min(self.pools.len() - 1),
+        }
+    }
+
+    pub fn remaining(&self, slot: usize) -> u32 {
+        self.pools[self.pool_idx(slot)]
+    }
+
+    // Spends the ship the given player just lost, false once their pool has none left to respawn with
+    pub fn take(&mut self, slot: usize) -> bool {
+        let idx = self.pool_idx(slot);
+        self.pools[idx] = self.pools[idx].saturating_sub(1);
+        self.pools[idx] > 0
+    }
+
+    // Hands out extra lives to every pool for each score milestone passed, returns how many were awarded
+    pub fn award_for_score(&mut self, score: &Score) -> u32 {
+        let mut awarded = 0;
+        while score.points >= self.next_extra_life_at {
+            self.next_extra_life_at += Self::SCORE_PER_EXTRA_LIFE;
+            for pool in self.pools.iter_mut() {
+                if *pool < Self::MAX {
+                    *pool += 1;
+                    awarded += 1;
+                }
+            }
+        }
+        awarded
+    }
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self::new(LivesMode::default(), 1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageSource {
+    Radiation,
+    Asteroid,
+    Enemy,
+    Boss,
+    Projectile,
+}
+
+impl DamageSource {
+    pub const ALL: [DamageSource; 5] = [
+        DamageSource::Radiation,
+        DamageSource::Asteroid,
+        DamageSource::Enemy,
+        DamageSource::Boss,
+        DamageSource::Projectile,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageSource::Radiation => "radiation",
+            DamageSource::Asteroid => "asteroid",
+            DamageSource::Enemy => "enemy",
+            DamageSource::Boss => "boss",
+            DamageSource::Projectile => "projectile",
+        }
+    }
+}
+
+// Tally of what happened to the players over the current run, reset when a run starts
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RunStats {
+    pub damage_taken: HashMap<DamageSource, f32>,
+    pub packs_collected: HashMap<PickupKind, u32>,
+}
+
+impl RunStats {
+    pub fn add_damage(&mut self, source: DamageSource, amount: f32) {
+        *self.damage_taken.entry(source).or_default() += amount;
+    }
+
+    pub fn add_pack(&mut self, kind: PickupKind) {
+        *self.packs_collected.entry(kind).or_default() += 1;
+    }
+
+    pub fn damage(&self, source: DamageSource) -> f32 {
+        self.damage_taken.get(&source).copied().unwrap_or(0.0)
+    }
+
+    pub fn packs(&self, kind: PickupKind) -> u32 {
+        self.packs_collected.get(&kind).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub points: f32,
+    pub multiplier: f32,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self {
+            points: 0.0,
+            multiplier: 1.0,
+        }
+    }
+}
+
+pub const MAX_HIGH_SCORES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: u32,
+    pub preset: DifficultyPreset,
+    pub players: usize,
+}
+
+// Best runs so far, highest first. The app keeps them between sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+    // Where the last finished run made it onto the list, for the game over screen
+    #[serde(skip)]
+    pub latest: Option<usize>,
+}
+
+impl HighScores {
+    // Returns the rank the run got, if it was good enough to make the list
+    pub fn record(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+        self.latest = if rank < MAX_HIGH_SCORES {
+            self.entries.insert(rank, entry);
+            self.entries.truncate(MAX_HIGH_SCORES);
+            Some(rank)
+        } else {
+            None
+        };
+        self.latest
+    }
+
+    pub fn best(&self) -> Option<u32> {
+        self.entries.first().map(|entry| entry.score)
+    }
+
+    // Starts a fresh list if the file is missing or broken
+    pub fn load_or_default(path: &Path) -> Self {
+        load_ron_or_default(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let data = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct PickupSpawnEntry {
+    pub kind: PickupKind,
+    pub weight: u32,
+    pub effect: f32,
+    pub sprite_idx: u32,
+}
+
+#[derive(Debug)]
+pub struct PickupSpawnTable {
+    pub entries: Vec<PickupSpawnEntry>,
+}
+
+impl Default for PickupSpawnTable {
+    fn default() -> Self {
+        // Spritesheet only has one pickup sprite for now, so kinds share it
+        Self {
+            entries: vec![
+                PickupSpawnEntry {
+                    kind: PickupKind::Repair,
+                    weight: 6,
+                    effect: 30.0,
+                    sprite_idx: 1,
+                },
+                PickupSpawnEntry {
+                    kind: PickupKind::Shield,
+                    weight: 2,
+                    effect: 5.0,
+                    sprite_idx: 1,
+                },
+                PickupSpawnEntry {
+                    kind: PickupKind::SpeedBoost,
+                    weight: 2,
+                    effect: 5.0,
+                    sprite_idx: 1,
+                },
+                PickupSpawnEntry {
+                    kind: PickupKind::WeaponUpgrade,
+                    weight: 1,
+                    effect: 1.0,
+                    sprite_idx: 1,
+                },
+                PickupSpawnEntry {
+                    kind: PickupKind::ScoreMultiplier,
+                    weight: 1,
+                    effect: 0.5,
+                    sprite_idx: 1,
+                },
+                PickupSpawnEntry {
+                    kind: PickupKind::SlowMo,
+                    weight: 1,
+                    effect: 4.0,
+                    sprite_idx: 1,
+                },
+            ],
+        }
+    }
+}
+
+impl PickupSpawnTable {
+    pub fn entry(&self, kind: PickupKind) -> Option<&PickupSpawnEntry> {
+        self.entries.iter().find(|entry| entry.kind == kind)
+    }
+
+    pub fn pick<R: Rng>(&self, rng: &mut R) -> Option<&PickupSpawnEntry> {
+        let total_weight: u32 = self.entries.iter().map(|entry| entry.weight).sum();
+        if total_weight == 0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0..total_weight);
+        for entry in &self.entries {
+            if roll < entry.weight {
+                return Some(entry);
+            }
+            roll -= entry.weight;
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DifficultyPreset {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl DifficultyPreset {
+    pub const ALL: [DifficultyPreset; 3] = [
+        DifficultyPreset::Easy,
+        DifficultyPreset::Normal,
+        DifficultyPreset::Hard,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DifficultyPreset::Easy => "easy",
+            DifficultyPreset::Normal => "normal",
+            DifficultyPreset::Hard => "hard",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DifficultyDriver {
+    // Seconds of play until the curve tops out
+    Time(f32),
+    // Points until the curve tops out
+    Score(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyParams {
+    pub spawn_interval: f32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+    pub min_size: f32,
+    pub max_size: f32,
+    pub radiation_damage: f32,
+}
+
+impl DifficultyParams {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a * (1.0 - t) + b * t;
+        Self {
+            spawn_interval: mix(self.spawn_interval, other.spawn_interval),
+            min_speed: mix(self.min_speed, other.min_speed),
+            max_speed: mix(self.max_speed, other.max_speed),
+            min_size: mix(self.min_size, other.min_size),
+            max_size: mix(self.max_size, other.max_size),
+            radiation_damage: mix(self.radiation_damage, other.radiation_damage),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyCurve {
+    pub driver: DifficultyDriver,
+    // Shapes the ramp, 1.0 is linear, higher values keep it easy for longer
+    pub exponent: f32,
+    pub start: DifficultyParams,
+    pub end: DifficultyParams,
+}
+
+impl DifficultyCurve {
+    // Every beaten boss pushes the whole curve up a notch
+    pub fn for_orbit(self, orbit: u32) -> Self {
+        let scale = |params: DifficultyParams| DifficultyParams {
+            spawn_interval: params.spawn_interval * 0.85_f32.powi(orbit as i32),
+            min_speed: params.min_speed * 1.1_f32.powi(orbit as i32),
+            max_speed: params.max_speed * 1.1_f32.powi(orbit as i32),
+            radiation_damage: params.radiation_damage * 1.15_f32.powi(orbit as i32),
+            ..params
+        };
+
+        Self {
+            start: scale(self.start),
+            end: scale(self.end),
+            ..self
+        }
+    }
+
+    pub fn for_preset(preset: DifficultyPreset) -> Self {
+        // Normal starts with the original jam rules
+        let normal_start = DifficultyParams {
+            spawn_interval: 1.0,
+            min_speed: 1.0,
+            max_speed: 5.0,
+            min_size: 60.0,
+            max_size: 100.0,
+            radiation_damage: 4.8,
+        };
+
+        match preset {
+            DifficultyPreset::Easy => Self {
+                driver: DifficultyDriver::Time(300.0),
+                exponent: 1.5,
+                start: DifficultyParams {
+                    spawn_interval: 1.5,
+                    max_speed: 4.0,
+                    radiation_damage: 3.0,
+                    ..normal_start
+                },
+                end: DifficultyParams {
+                    spawn_interval: 0.8,
+                    max_speed: 6.0,
+                    max_size: 110.0,
+                    radiation_damage: 4.8,
+                    ..normal_start
+                },
+            },
+            DifficultyPreset::Normal => Self {
+                driver: DifficultyDriver::Time(180.0),
+                exponent: 1.0,
+                start: normal_start,
+                end: DifficultyParams {
+                    spawn_interval: 0.4,
+                    min_speed: 2.0,
+                    max_speed: 8.0,
+                    max_size: 130.0,
+                    radiation_damage: 7.0,
+                    ..normal_start
+                },
+            },
+            DifficultyPreset::Hard => Self {
+                driver: DifficultyDriver::Time(120.0),
+                exponent: 0.8,
+                start: DifficultyParams {
+                    spawn_interval: 0.7,
+                    min_speed: 2.0,
+                    max_speed: 6.0,
+                    radiation_damage: 6.0,
+                    ..normal_start
+                },
+                end: DifficultyParams {
+                    spawn_interval: 0.25,
+                    min_speed: 3.0,
+                    max_speed: 10.0,
+                    max_size: 150.0,
+                    radiation_damage: 9.0,
+                    ..normal_start
+                },
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Difficulty {
+    pub preset: DifficultyPreset,
+    pub orbit: u32,
+    pub curve: DifficultyCurve,
+    pub elapsed: f32,
+    pub level: f32,
+    pub current: DifficultyParams,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self::new(DifficultyPreset::Normal)
+    }
+}
+
+impl Difficulty {
+    pub fn new(preset: DifficultyPreset) -> Self {
+        let curve = DifficultyCurve::for_preset(preset);
+        Self {
+            preset,
+            orbit: 0,
+            curve,
+            elapsed: 0.0,
+            level: 0.0,
+            current: curve.start,
+        }
+    }
+
+    pub fn advance_orbit(&mut self) {
+        let orbit = self.orbit + 1;
+        *self = Self::new(self.preset);
+        self.orbit = orbit;
+        self.curve = self.curve.for_orbit(orbit);
+        self.current = self.curve.start;
+    }
+
+    pub fn update(&mut self, delta: f32, score: &Score) {
+        self.elapsed += delta;
+
+        let progress = match self.curve.driver {
+            DifficultyDriver::Time(full_at) => self.elapsed / full_at,
+            DifficultyDriver::Score(full_at) => score.points / full_at,
+        };
+
+        self.level = progress.clamp(0.0, 1.0).powf(self.curve.exponent);
+        self.current = self.curve.start.lerp(&self.curve.end, self.level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{
+        Difficulty, DifficultyDriver, DifficultyPreset, HighScore, HighScores, Lives, LivesMode,
+        PickupKind, PickupSpawnEntry, PickupSpawnTable, Score, MAX_HIGH_SCORES,
+    };
+
+    fn entry(kind: PickupKind, weight: u32) -> PickupSpawnEntry {
+        PickupSpawnEntry {
+            kind,
+            weight,
+            effect: 0.0,
+            sprite_idx: 1,
+        }
+    }
+
+    #[test]
+    fn test_pick_empty_table() {
+        let table = PickupSpawnTable { entries: vec![] };
+        let mut rng = StdRng::seed_from_u64(0);
+
+        assert!(table.pick(&mut rng).is_none());
+    }
+
+    #[test]
+    fn test_pick_skips_zero_weight() {
+        let table = PickupSpawnTable {
+            entries: vec![
+                entry(PickupKind::Repair, 0),
+                entry(PickupKind::Shield, 1),
+                entry(PickupKind::SpeedBoost, 0),
+            ],
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            assert_eq!(table.pick(&mut rng).unwrap().kind, PickupKind::Shield);
+        }
+    }
+
+    #[test]
+    fn test_pick_respects_weights() {
+        let table = PickupSpawnTable {
+            entries: vec![entry(PickupKind::Repair, 3), entry(PickupKind::Shield, 1)],
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let repairs = (0..4000)
+            .filter(|_| table.pick(&mut rng).unwrap().kind == PickupKind::Repair)
+            .count();
+
+        assert!(repairs > 2800 && repairs < 3200);
+    }
+
+    #[test]
+    fn test_difficulty_ramps_with_time() {
+        let mut difficulty = Difficulty::new(DifficultyPreset::Normal);
+        let score = Score::default();
+        let start = difficulty.current;
+
+        difficulty.update(0.0, &score);
+        assert_eq!(difficulty.current, start);
+
+        difficulty.update(10_000.0, &score);
+        assert_eq!(difficulty.level, 1.0);
+        assert_eq!(difficulty.current, difficulty.curve.end);
+        assert!(difficulty.current.spawn_interval < start.spawn_interval);
+    }
+
+    #[test]
+    fn test_difficulty_orbit_is_harder() {
+        let mut difficulty = Difficulty::new(DifficultyPreset::Easy);
+        let first_orbit = difficulty.current;
+
+        difficulty.advance_orbit();
+
+        assert_eq!(difficulty.orbit, 1);
+        assert_eq!(difficulty.preset, DifficultyPreset::Easy);
+        assert!(difficulty.current.spawn_interval < first_orbit.spawn_interval);
+        assert!(difficulty.current.max_speed > first_orbit.max_speed);
+        assert!(difficulty.current.radiation_damage > first_orbit.radiation_damage);
+    }
+
+    #[test]
+    fn test_difficulty_score_driver() {
+        let mut difficulty = Difficulty::new(DifficultyPreset::Normal);
+        difficulty.curve.driver = DifficultyDriver::Score(100.0);
+        difficulty.curve.exponent = 1.0;
+        let score = Score {
+            points: 50.0,
+            multiplier: 1.0,
+        };
+
+        difficulty.update(1_000.0, &score);
+        assert_eq!(difficulty.level, 0.5);
+    }
+
+    #[test]
+    fn test_lives_awarded_per_milestone() {
+        let mut lives = Lives::default();
+        let mut score = Score {
+            points: Lives::SCORE_PER_EXTRA_LIFE - 1.0,
+            ..Default::default()
+        };
+
+        assert_eq!(lives.award_for_score(&score), 0);
+
+        score.points = Lives::SCORE_PER_EXTRA_LIFE * 2.5;
+        assert_eq!(lives.award_for_score(&score), 2);
+        assert_eq!(lives.remaining(0), Lives::STARTING + 2);
+
+        // Milestones are only paid out once
+        assert_eq!(lives.award_for_score(&score), 0);
+    }
+
+    #[test]
+    fn test_lives_pools() {
+        let mut shared = Lives::new(LivesMode::Shared, 2);
+        assert!(shared.take(1));
+        assert_eq!(shared.remaining(0), Lives::STARTING - 1);
+
+        let mut separate = Lives::new(LivesMode::Separate, 2);
+        for _ in 1..Lives::STARTING {
+            assert!(separate.take(1));
+        }
+        assert!(!separate.take(1));
+        assert_eq!(separate.remaining(0), Lives::STARTING);
+
+        // Every pool gets paid for a milestone
+        let score = Score {
+            points: Lives::SCORE_PER_EXTRA_LIFE,
+            ..Default::default()
+        };
+        assert_eq!(separate.award_for_score(&score), 2);
+        assert_eq!(separate.remaining(1), 1);
+    }
+
+    #[test]
+    fn test_high_scores_stay_sorted_and_capped() {
+        let entry = |score| HighScore {
+            score,
+            preset: DifficultyPreset::Normal,
+            players: 1,
+        };
+        let mut scores = HighScores::default();
+        for score in 0..MAX_HIGH_SCORES as u32 {
+            scores.record(entry(score * 10 + 10));
+        }
+        assert_eq!(scores.best(), Some(MAX_HIGH_SCORES as u32 * 10));
+
+        // Too low to make it, then a new best
+        assert_eq!(scores.record(entry(5)), None);
+        assert_eq!(scores.record(entry(1000)), Some(0));
+        assert_eq!(scores.entries.len(), MAX_HIGH_SCORES);
+        assert_eq!(scores.entries.last().unwrap().score, 20);
+    }
 }
@@ -0,0 +1,318 @@
+use std::convert::Infallible;
+
+use serde::{Deserialize, Serialize};
+use specs::{
+    saveload::{
+        DeserializeComponents, EntityData, Marker, MarkerAllocator, SerializeComponents,
+        SimpleMarker, SimpleMarkerAllocator,
+    },
+    world::EntitiesRes,
+    Entity, Join, ReadStorage, World, WorldExt, WriteStorage,
+};
+
+use super::{
+    component::{
+        Boss, BossPart, BossPartSaveloadData, Collider, Display, Emitter, Enemy, Lifetime, Pickup,
+        Player, Projectile, Spinner, StatusEffects, Transform, Velocity,
+    },
+    resource::{
+        BossStatus, ControlScheme, Difficulty, GameRng, GameState, Lives, PlayerCount, RunStats,
+        Score, TimeScale, WaveStatus,
+    },
+    system::{AsteroidSpawnerState, BossState, EnemySpawnerState, PickupState, WaveSchedulerState},
+};
+
+pub struct Saveable;
+
+pub type SaveMarker = SimpleMarker<Saveable>;
+
+type SavedStorages<'a> = (
+    ReadStorage<'a, Transform>,
+    ReadStorage<'a, Velocity>,
+    ReadStorage<'a, Display>,
+    ReadStorage<'a, Player>,
+    ReadStorage<'a, StatusEffects>,
+    ReadStorage<'a, Collider>,
+    ReadStorage<'a, Pickup>,
+    ReadStorage<'a, Lifetime>,
+    ReadStorage<'a, Spinner>,
+    ReadStorage<'a, Enemy>,
+    ReadStorage<'a, Projectile>,
+    ReadStorage<'a, Boss>,
+    ReadStorage<'a, BossPart>,
+    ReadStorage<'a, Emitter>,
+);
+
+type LoadedStorages<'a> = (
+    WriteStorage<'a, Transform>,
+    WriteStorage<'a, Velocity>,
+    WriteStorage<'a, Display>,
+    WriteStorage<'a, Player>,
+    WriteStorage<'a, StatusEffects>,
+    WriteStorage<'a, Collider>,
+    WriteStorage<'a, Pickup>,
+    WriteStorage<'a, Lifetime>,
+    WriteStorage<'a, Spinner>,
+    WriteStorage<'a, Enemy>,
+    WriteStorage<'a, Projectile>,
+    WriteStorage<'a, Boss>,
+    WriteStorage<'a, BossPart>,
+    WriteStorage<'a, Emitter>,
+);
+
+type SavedComponents = (
+    Option<Transform>,
+    Option<Velocity>,
+    Option<Display>,
+    Option<Player>,
+    Option<StatusEffects>,
+    Option<Collider>,
+    Option<Pickup>,
+    Option<Lifetime>,
+    Option<Spinner>,
+    Option<Enemy>,
+    Option<Projectile>,
+    Option<Boss>,
+    Option<BossPartSaveloadData<SaveMarker>>,
+    Option<Emitter>,
+);
+
+#[derive(Serialize, Deserialize)]
+enum SavedGameState {
+    Init,
+    Play(Vec<SaveMarker>),
+    End,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedResources {
+    game_state: SavedGameState,
+    player_count: PlayerCount,
+    control_scheme: ControlScheme,
+    score: Score,
+    difficulty: Difficulty,
+    lives: Lives,
+    run_stats: RunStats,
+    rng: GameRng,
+    time_scale: TimeScale,
+    wave_status: WaveStatus,
+    boss_status: BossStatus,
+    asteroid_spawner: AsteroidSpawnerState,
+    enemy_spawner: EnemySpawnerState,
+    pickup: PickupState,
+    boss: BossState,
+    wave_scheduler: WaveSchedulerState,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    resources: SavedResources,
+    entities: Vec<EntityData<SaveMarker, SavedComponents>>,
+}
+
+// Everything that makes up a game in progress, as RON. Input, window and renderer state are
+// left out, those belong to whoever is running the game.
+pub fn save_world(world: &mut World) -> Result<String, ron::Error> {
+    setup(world);
+    // Anything still queued up in LazyUpdate would be missing from the save otherwise
+    world.maintain();
+
+    let entities = world.entities();
+    let mut markers = world.write_storage::<SaveMarker>();
+    let mut allocator = world.write_resource::<SimpleMarkerAllocator<Saveable>>();
+    for entity in entities.join() {
+        allocator.mark(entity, &mut markers);
+    }
+
+    let storages = world.system_data::<SavedStorages>();
+    let saved_entities = (&entities, &markers)
+        .join()
+        .map(|(entity, marker)| EntityData {
+            marker: *marker,
+            components: storages
+                .serialize_entity(entity, |entity| markers.get(entity).copied())
+                .unwrap_or_else(|err: Infallible| match err {}),
+        })
+        .collect();
+
+    let to_marker = |entity: &Entity| *markers.get(*entity).unwrap();
+    let game_state = match &*world.read_resource::<GameState>() {
+        GameState::GameStateInit {} => SavedGameState::Init,
+        GameState::GameStatePlay { player_entities } => {
+            SavedGameState::Play(player_entities.iter().map(to_marker).collect())
+        }
+        GameState::GameStateEnd {} => SavedGameState::End,
+    };
+
+    let save = SaveFile {
+        resources: SavedResources {
+            game_state,
+            player_count: cloned::<PlayerCount>(world),
+            control_scheme: cloned::<ControlScheme>(world),
+            score: cloned::<Score>(world),
+            difficulty: cloned::<Difficulty>(world),
+            lives: cloned::<Lives>(world),
+            run_stats: cloned::<RunStats>(world),
+            rng: cloned::<GameRng>(world),
+            time_scale: cloned::<TimeScale>(world),
+            wave_status: cloned::<WaveStatus>(world),
+            boss_status: cloned::<BossStatus>(world),
+            asteroid_spawner: cloned::<AsteroidSpawnerState>(world),
+            enemy_spawner: cloned::<EnemySpawnerState>(world),
+            pickup: cloned::<PickupState>(world),
+            boss: cloned::<BossState>(world),
+            wave_scheduler: cloned::<WaveSchedulerState>(world),
+        },
+        entities: saved_entities,
+    };
+    ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default())
+}
+
+// Replaces every entity and the saved resources with the ones from `data`. The world is left
+// alone when the data doesn't parse.
+pub fn load_world(world: &mut World, data: &str) -> Result<(), ron::Error> {
+    let save: SaveFile = ron::from_str(data)?;
+
+    setup(world);
+    world.delete_all();
+    world.maintain();
+    // Start handing out ids from scratch like a new world would. Reused ids come back in a
+    // different order, and the order entities were created in is the order systems see them in.
+    *world.entities_mut() = EntitiesRes::default();
+    world.insert(SimpleMarkerAllocator::<Saveable>::new());
+
+    let game_state = {
+        let entities = world.entities();
+        let mut markers = world.write_storage::<SaveMarker>();
+        let mut allocator = world.write_resource::<SimpleMarkerAllocator<Saveable>>();
+
+        // Everything gets created up front, a boss part might come before its core
+        let new_entities: Vec<Entity> = save.entities.iter().map(|_| entities.create()).collect();
+        for (entity, data) in new_entities.iter().zip(save.entities.iter()) {
+            let marker = allocator.allocate(*entity, Some(data.marker.id()));
+            markers.insert(*entity, marker).unwrap();
+        }
+
+        let mut storages = world.system_data::<LoadedStorages>();
+        for (entity, data) in new_entities.into_iter().zip(save.entities) {
+            storages
+                .deserialize_entity(entity, data.components, |marker| {
+                    Some(allocator.retrieve_entity(marker, &mut markers, &entities))
+                })
+                .unwrap_or_else(|err: Infallible| match err {});
+        }
+
+        match &save.resources.game_state {
+            SavedGameState::Init => GameState::GameStateInit {},
+            SavedGameState::Play(player_markers) => GameState::GameStatePlay {
+                player_entities: player_markers
+                    .iter()
+                    .map(|marker| allocator.retrieve_entity(*marker, &mut markers, &entities))
+                    .collect(),
+            },
+            SavedGameState::End => GameState::GameStateEnd {},
+        }
+    };
+    world.maintain();
+
+    let resources = save.resources;
+    world.insert(game_state);
+    world.insert(resources.player_count);
+    world.insert(resources.control_scheme);
+    world.insert(resources.score);
+    world.insert(resources.difficulty);
+    world.insert(resources.lives);
+    world.insert(resources.run_stats);
+    world.insert(resources.rng);
+    world.insert(resources.time_scale);
+    world.insert(resources.wave_status);
+    world.insert(resources.boss_status);
+    world.insert(resources.asteroid_spawner);
+    world.insert(resources.enemy_spawner);
+    world.insert(resources.pickup);
+    world.insert(resources.boss);
+    world.insert(resources.wave_scheduler);
+    Ok(())
+}
+
+fn cloned<T: Clone + Send + Sync + 'static>(world: &World) -> T {
+    (*world.read_resource::<T>()).clone()
+}
+
+fn setup(world: &mut World) {
+    if !world.has_value::<SimpleMarkerAllocator<Saveable>>() {
+        world.register::<SaveMarker>();
+        world.insert(SimpleMarkerAllocator::<Saveable>::new());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use specs::{Join, WorldExt};
+
+    use crate::{
+        game::{
+            component::Transform,
+            input::{Action, ScriptedInput},
+        },
+        simulation::Simulation,
+    };
+
+    const TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+    fn positions(simulation: &Simulation) -> Vec<glam::Vec2> {
+        let transforms = simulation.world().read_storage::<Transform>();
+        transforms
+            .join()
+            .map(|transform| transform.position)
+            .collect()
+    }
+
+    #[test]
+    fn test_load_continues_the_same() {
+        let script = ScriptedInput::default()
+            .tap(1, 0, Action::Confirm)
+            .press(5, 0, Action::Fire)
+            .press(5, 0, Action::RotateLeft)
+            .release(200, 0, Action::Fire)
+            .release(200, 0, Action::RotateLeft);
+        let mut original = Simulation::new((1280, 720), vec![Box::new(script)]);
+        original.set_seed(11);
+        for _ in 0..300 {
+            original.tick(TICK);
+        }
+        let save = original.save().unwrap();
+
+        for _ in 0..300 {
+            original.tick(TICK);
+        }
+        let expected = positions(&original);
+        assert!(expected.len() > 10);
+
+        // Into a fresh world
+        let mut loaded = Simulation::new((1280, 720), Vec::new());
+        loaded.load(&save).unwrap();
+        for _ in 0..300 {
+            loaded.tick(TICK);
+        }
+        assert_eq!(positions(&loaded), expected);
+
+        // And back into the one that kept going, like a quickload
+        original.load(&save).unwrap();
+        for _ in 0..300 {
+            original.tick(TICK);
+        }
+        assert_eq!(positions(&original), expected);
+    }
+
+    #[test]
+    fn test_bad_save_leaves_world_alone() {
+        let mut simulation = Simulation::new((1280, 720), Vec::new());
+        simulation.tick(TICK);
+        let before = positions(&simulation);
+        assert!(simulation.load("(resources: ())").is_err());
+        assert_eq!(positions(&simulation), before);
+    }
+}
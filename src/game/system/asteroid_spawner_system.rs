@@ -1,25 +1,31 @@
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
 use crate::game::{
     component::{Collider, ColliderTag, Display, Lifetime, Spinner, Transform, Velocity},
-    resource::{DeltaTime, GameState, GameWindowSize},
+    resource::{
+        DeltaTime, Difficulty, DifficultyParams, GameRng, GameState, GameWindowSize, WaveStatus,
+    },
 };
 
-pub struct AsteroidSpawnerSystem {
-    spawn_timeout: f32,
-    spawn_clock: f32,
+#[derive(Debug)]
+pub struct AsteroidSpawn {
+    pub position: glam::Vec2,
+    pub direction: glam::Vec2,
+    pub speed: f32,
+    pub size: f32,
+    pub lifetime: f32,
 }
 
-impl Default for AsteroidSpawnerSystem {
-    fn default() -> Self {
-        Self {
-            spawn_timeout: 1.0,
-            spawn_clock: 0.0,
-        }
-    }
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AsteroidSpawnerState {
+    pub spawn_clock: f32,
 }
 
+#[derive(Default)]
+pub struct AsteroidSpawnerSystem {}
+
 impl<'a> System<'a> for AsteroidSpawnerSystem {
     type SystemData = (
         Entities<'a>,
@@ -27,20 +33,44 @@ impl<'a> System<'a> for AsteroidSpawnerSystem {
         Read<'a, GameState>,
         Read<'a, DeltaTime>,
         Read<'a, GameWindowSize>,
+        Read<'a, Difficulty>,
+        Read<'a, WaveStatus>,
+        Write<'a, GameRng>,
+        Write<'a, AsteroidSpawnerState>,
     );
 
     fn setup(&mut self, world: &mut World) {
         Self::SystemData::setup(world);
     }
 
-    fn run(&mut self, (entities, updater, game_state, dt, game_window_size): Self::SystemData) {
+    fn run(
+        &mut self,
+        (
+            entities,
+            updater,
+            game_state,
+            dt,
+            game_window_size,
+            difficulty,
+            wave_status,
+            mut rng,
+            mut state,
+        ): Self::SystemData,
+    ) {
         match *game_state {
             GameState::GameStatePlay { .. } => {
-                self.spawn_clock -= dt.0.as_secs_f32();
+                // Calm waves give the player a breather
+                if wave_status.calm {
+                    return;
+                }
+
+                state.spawn_clock -= dt.0.as_secs_f32();
 
-                if self.spawn_clock < 0.0 {
-                    self.spawn_asteroid(&game_window_size, &entities, &updater);
-                    self.spawn_clock = self.spawn_timeout
+                if state.spawn_clock < 0.0 {
+                    let spawn =
+                        Self::random_spawn(&difficulty.current, &game_window_size, &mut rng.0);
+                    Self::spawn_asteroid(&spawn, &mut rng.0, &entities, &updater);
+                    state.spawn_clock = difficulty.current.spawn_interval
                 }
             }
             _ => (),
@@ -49,29 +79,39 @@ impl<'a> System<'a> for AsteroidSpawnerSystem {
 }
 
 impl AsteroidSpawnerSystem {
-    fn spawn_asteroid<'a>(
-        &self,
+    fn random_spawn<R: Rng>(
+        params: &DifficultyParams,
         game_window_size: &GameWindowSize,
-        entities: &Entities,
-        updater: &Read<'a, LazyUpdate>,
-    ) {
-        let asteroid = entities.create();
-        let mut rng = rand::thread_rng();
-
-        let max_random_speed = 5.0;
-
-        let transform_pos = glam::vec2(
+        rng: &mut R,
+    ) -> AsteroidSpawn {
+        let position = glam::vec2(
             rng.gen_range(-(game_window_size.0 as f32 / 2.0)..(game_window_size.0 as f32 / 2.0)),
             game_window_size.1 as f32,
         );
 
-        let velocity_dir = glam::vec2(rng.gen_range(-0.1..0.1), -1.0).normalize();
-        let velocity_speed = rng.gen_range(1.0..max_random_speed);
+        let direction = glam::vec2(rng.gen_range(-0.1..0.1), -1.0).normalize();
+        let speed = rng.gen_range(params.min_speed..params.max_speed);
 
-        let scale = glam::vec2(
-            100.0 - ((max_random_speed - velocity_speed) * 10.0),
-            100.0 - ((max_random_speed - velocity_speed) * 10.0),
-        );
+        // Faster rocks are bigger, spread across the size range of the current difficulty
+        let speed_factor = (speed - params.min_speed) / (params.max_speed - params.min_speed);
+        let size = params.min_size + (params.max_size - params.min_size) * speed_factor;
+
+        AsteroidSpawn {
+            position,
+            direction,
+            speed,
+            size,
+            lifetime: 10.0 * speed,
+        }
+    }
+
+    pub fn spawn_asteroid<'a, R: Rng>(
+        spawn: &AsteroidSpawn,
+        rng: &mut R,
+        entities: &Entities,
+        updater: &Read<'a, LazyUpdate>,
+    ) -> Entity {
+        let asteroid = entities.create();
 
         let sprite_idx = rng.gen_range(2..=4);
         let speen_speed = rng.gen_range(0.1..5.0);
@@ -79,26 +119,28 @@ impl AsteroidSpawnerSystem {
         updater.insert(
             asteroid,
             Transform {
-                position: transform_pos,
-                scale,
+                position: spawn.position,
+                scale: glam::vec2(spawn.size, spawn.size),
                 ..Default::default()
             },
         );
         updater.insert(
             asteroid,
             Velocity {
-                direction: velocity_dir,
-                speed: velocity_speed,
+                direction: spawn.direction,
+                speed: spawn.speed,
             },
         );
-        updater.insert(asteroid, Display { sprite_idx });
+        updater.insert(asteroid, Display::new(sprite_idx));
         updater.insert(asteroid, Spinner { speed: speen_speed });
         updater.insert(asteroid, Collider::new(ColliderTag::Asteroid));
         updater.insert(
             asteroid,
             Lifetime {
-                remaining: 10.0 * velocity_speed,
+                remaining: spawn.lifetime,
             },
         );
+
+        asteroid
     }
 }
@@ -0,0 +1,242 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use specs::{prelude::*, shrev::EventChannel};
+
+use crate::{
+    audio::{
+        self, AudioOutput, Bus, Clip, Mixer, MusicTrack, NullOutput, SharedMixer, Sound,
+        SoundEvent, VoiceId,
+    },
+    game::resource::{GameState, GameWindowSize, UnscaledDeltaTime},
+    settings::Settings,
+};
+
+// How long one track takes to fade over into the next
+const MUSIC_CROSSFADE: f32 = 1.5;
+// Held loops come in quickly and trail off a little once let go
+const LOOP_FADE_IN: f32 = 0.05;
+const LOOP_FADE_OUT: f32 = 0.15;
+// Sounds right at the edge of the window don't go all the way to one side
+const PAN_WIDTH: f32 = 0.8;
+
+// Plays what the gameplay systems put on the sound channel and the music for the game state.
+// Runs every tick, paused or not, so the music keeps going and held loops stop while paused.
+pub struct AudioSystem {
+    output: Box<dyn AudioOutput>,
+    mixer: SharedMixer,
+    reader: Option<ReaderId<SoundEvent>>,
+    sounds: HashMap<Sound, Clip>,
+    music: HashMap<MusicTrack, Clip>,
+    playing_music: Option<(MusicTrack, VoiceId)>,
+    // Loops held last tick, by sound and source entity
+    held: HashMap<(Sound, u32), VoiceId>,
+    // When each sound last started, on the system's own clock
+    last_played: HashMap<Sound, Duration>,
+    clock: Duration,
+}
+
+impl AudioSystem {
+    pub fn new(output: impl AudioOutput + 'static) -> Self {
+        let sample_rate = output.sample_rate();
+        Self {
+            output: Box::new(output),
+            mixer: Arc::new(Mutex::new(Mixer::new(sample_rate))),
+            reader: None,
+            sounds: Sound::ALL
+                .iter()
+                .map(|&sound| (sound, audio::sound(sound, sample_rate)))
+                .collect(),
+            music: MusicTrack::ALL
+                .iter()
+                .map(|&track| (track, audio::music(track, sample_rate)))
+                .collect(),
+            playing_music: None,
+            held: HashMap::new(),
+            last_played: HashMap::new(),
+            clock: Duration::ZERO,
+        }
+    }
+
+    pub fn mixer(&self) -> &SharedMixer {
+        &self.mixer
+    }
+}
+
+impl Default for AudioSystem {
+    fn default() -> Self {
+        Self::new(NullOutput::new())
+    }
+}
+
+impl<'a> System<'a> for AudioSystem {
+    type SystemData = (
+        Read<'a, EventChannel<SoundEvent>>,
+        Read<'a, GameState>,
+        Read<'a, GameWindowSize>,
+        Read<'a, Settings>,
+        // Slow motion doesn't slow down the sound
+        Read<'a, UnscaledDeltaTime>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<SoundEvent>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(&mut self, (events, game_state, window_size, settings, dt): Self::SystemData) {
+        self.clock += dt.0;
+
+        {
+            let mut mixer = self.mixer.lock().unwrap();
+            mixer.set_volumes(
+                settings.master_volume,
+                settings.sfx_volume,
+                settings.music_volume,
+            );
+
+            let track = match *game_state {
+                GameState::GameStateInit {} => MusicTrack::Title,
+                GameState::GameStatePlay { .. } => MusicTrack::Play,
+                GameState::GameStateEnd {} => MusicTrack::GameOver,
+            };
+            if self.playing_music.map(|(playing, _)| playing) != Some(track) {
+                if let Some((_, voice)) = self.playing_music.take() {
+                    mixer.stop(voice, MUSIC_CROSSFADE);
+                }
+                let voice = mixer.play(
+                    &self.music[&track],
+                    Bus::Music,
+                    1.0,
+                    0.0,
+                    true,
+                    MUSIC_CROSSFADE,
+                );
+                self.playing_music = Some((track, voice));
+            }
+
+            let half_width = (window_size.0 as f32 / 2.0).max(1.0);
+            let pan = |position: glam::Vec2| (position.x / half_width).clamp(-1.0, 1.0) * PAN_WIDTH;
+            let mut still_held = HashSet::new();
+            for event in events.read(self.reader.as_mut().unwrap()) {
+                match *event {
+                    SoundEvent::Play(sound, position) => {
+                        let too_soon = self.last_played.get(&sound).is_some_and(|&played| {
+                            self.clock.saturating_sub(played) < sound.min_interval()
+                        });
+                        if too_soon {
+                            continue;
+                        }
+                        self.last_played.insert(sound, self.clock);
+                        mixer.play(
+                            &self.sounds[&sound],
+                            Bus::Sfx,
+                            sound.gain(),
+                            position.map_or(0.0, pan),
+                            false,
+                            0.0,
+                        );
+                    }
+                    SoundEvent::Hold(sound, source, position) => {
+                        let key = (sound, source);
+                        still_held.insert(key);
+                        match self.held.get(&key) {
+                            Some(&voice) if mixer.is_playing(voice) => {
+                                mixer.set_pan(voice, pan(position))
+                            }
+                            _ => {
+                                let voice = mixer.play(
+                                    &self.sounds[&sound],
+                                    Bus::Sfx,
+                                    sound.gain(),
+                                    pan(position),
+                                    true,
+                                    LOOP_FADE_IN,
+                                );
+                                self.held.insert(key, voice);
+                            }
+                        }
+                    }
+                }
+            }
+            self.held.retain(|key, voice| {
+                let keep = still_held.contains(key);
+                if !keep {
+                    mixer.stop(*voice, LOOP_FADE_OUT);
+                }
+                keep
+            });
+        }
+
+        self.output.update(&self.mixer, dt.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use specs::{prelude::*, shrev::EventChannel};
+
+    use super::AudioSystem;
+    use crate::{
+        audio::{Sound, SoundEvent},
+        game::resource::{GameState, UnscaledDeltaTime},
+    };
+
+    const TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+    #[test]
+    fn test_held_loops_and_music_crossfade() {
+        let mut world = World::new();
+        let mut system = AudioSystem::default();
+        System::setup(&mut system, &mut world);
+        world.insert(UnscaledDeltaTime(TICK));
+
+        let hold = |world: &mut World| {
+            world
+                .fetch_mut::<EventChannel<SoundEvent>>()
+                .single_write(SoundEvent::Hold(Sound::Thrust, 1, glam::vec2(100.0, 0.0)));
+        };
+        let voices = |system: &AudioSystem| system.mixer().lock().unwrap().voice_count();
+
+        // Title music and the thrust loop
+        hold(&mut world);
+        system.run_now(&world);
+        assert_eq!(voices(&system), 2);
+        hold(&mut world);
+        system.run_now(&world);
+        assert_eq!(voices(&system), 2);
+
+        // Let go, the loop fades out within a second
+        for _ in 0..60 {
+            system.run_now(&world);
+        }
+        assert_eq!(voices(&system), 1);
+
+        // Both tracks play while they cross over, then only the new one
+        world.insert(GameState::GameStateEnd {});
+        system.run_now(&world);
+        assert_eq!(voices(&system), 2);
+        for _ in 0..120 {
+            system.run_now(&world);
+        }
+        assert_eq!(voices(&system), 1);
+
+        // Scraping along an asteroid every tick only hits now and then
+        for _ in 0..6 {
+            world
+                .fetch_mut::<EventChannel<SoundEvent>>()
+                .single_write(SoundEvent::Play(Sound::AsteroidImpact, None));
+            system.run_now(&world);
+        }
+        assert_eq!(voices(&system), 2);
+    }
+}
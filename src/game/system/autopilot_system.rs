@@ -0,0 +1,255 @@
+use std::collections::HashSet;
+
+use specs::{prelude::*, shrev::EventChannel};
+
+use crate::game::{
+    component::{Collider, ColliderTag, Pickup, PickupKind, Player, Transform, Velocity},
+    input::{Action, InputBindings},
+    resource::{GameState, GameWindowSize, KeyboardEvent},
+};
+
+// How far ahead threats are extrapolated, in ticks
+const LOOKAHEAD_TICKS: f32 = 90.0;
+// Extra room on top of the collider sizes before something counts as a threat
+const DANGER_MARGIN: f32 = 40.0;
+// Colliders are a bit bigger than the sprite scale, see AABB::default
+const COLLIDER_EXTENT: f32 = 0.6;
+// Facing has to be this close to the wanted direction before thrusting, in radians
+const THRUST_ANGLE: f32 = 0.6;
+const TURN_DEADZONE: f32 = 0.15;
+// Below this much health repair packs are worth taking risks for
+const HUNGRY_HEALTH: f32 = 70.0;
+const CRUISE_SPEED: f32 = 6.0;
+
+// Plays the game by pressing the same keys a human would, meant for soak testing
+pub struct AutopilotSystem {
+    slot: usize,
+    held: HashSet<Action>,
+}
+
+impl AutopilotSystem {
+    pub fn new(slot: usize) -> Self {
+        Self {
+            slot,
+            held: HashSet::new(),
+        }
+    }
+}
+
+struct Ship {
+    position: glam::Vec2,
+    velocity: glam::Vec2,
+    facing: glam::Vec2,
+    radius: f32,
+    health: f32,
+}
+
+fn radius(transform: &Transform) -> f32 {
+    transform.scale.max_element() * COLLIDER_EXTENT
+}
+
+fn velocity_vector(velocity: Option<&Velocity>) -> glam::Vec2 {
+    velocity.map_or(glam::Vec2::ZERO, |velocity| {
+        velocity.direction * velocity.speed
+    })
+}
+
+impl<'a> System<'a> for AutopilotSystem {
+    type SystemData = (
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Velocity>,
+        ReadStorage<'a, Collider>,
+        ReadStorage<'a, Pickup>,
+        Read<'a, GameState>,
+        Read<'a, GameWindowSize>,
+        Read<'a, InputBindings>,
+        Write<'a, EventChannel<KeyboardEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            player_storage,
+            transform_storage,
+            velocity_storage,
+            collider_storage,
+            pickup_storage,
+            game_state,
+            window_size,
+            bindings,
+            mut keyboard,
+        ): Self::SystemData,
+    ) {
+        let mut wanted = HashSet::new();
+
+        match *game_state {
+            GameState::GameStatePlay { .. } => {
+                let ship = (
+                    &player_storage,
+                    &transform_storage,
+                    (&velocity_storage).maybe(),
+                )
+                    .join()
+                    .find(|(player, _, _)| player.slot == self.slot)
+                    .map(|(player, transform, velocity)| Ship {
+                        position: transform.position,
+                        velocity: velocity_vector(velocity),
+                        facing: transform.get_facing_vector(),
+                        radius: radius(transform),
+                        health: player.health,
+                    });
+
+                if let Some(ship) = ship {
+                    let (direction, shoot) = plan(
+                        &ship,
+                        (
+                            &transform_storage,
+                            (&velocity_storage).maybe(),
+                            &collider_storage,
+                            (&pickup_storage).maybe(),
+                        )
+                            .join(),
+                        &window_size,
+                    );
+                    steer(&ship, direction, &mut wanted);
+                    if shoot {
+                        wanted.insert(Action::Fire);
+                    }
+                }
+            }
+            // Keep tapping through the title and game over screens, the release on the
+            // following tick makes every other tick a fresh press
+            _ => {
+                if !self.held.contains(&Action::Confirm) {
+                    wanted.insert(Action::Confirm);
+                }
+            }
+        }
+
+        let keys = match bindings.players.get(self.slot) {
+            Some(keys) => keys,
+            None => return,
+        };
+        let key_for = |action: &Action| keys.get(action).and_then(|keys| keys.first()).copied();
+
+        for action in self.held.difference(&wanted) {
+            if let Some(key) = key_for(action) {
+                keyboard.single_write(KeyboardEvent::Released(key));
+            }
+        }
+        for action in wanted.difference(&self.held) {
+            if let Some(key) = key_for(action) {
+                keyboard.single_write(KeyboardEvent::Pressed(key));
+            }
+        }
+        self.held = wanted;
+    }
+}
+
+// Where the ship wants to head, zero to sit still, and whether there's anything worth shooting
+fn plan<'a>(
+    ship: &Ship,
+    others: impl Iterator<
+        Item = (
+            &'a Transform,
+            Option<&'a Velocity>,
+            &'a Collider,
+            Option<&'a Pickup>,
+        ),
+    >,
+    window_size: &GameWindowSize,
+) -> (glam::Vec2, bool) {
+    let mut avoidance = glam::Vec2::ZERO;
+    let mut closest_repair: Option<(f32, glam::Vec2)> = None;
+    let mut shoot = false;
+
+    for (transform, velocity, collider, pickup) in others {
+        let offset = transform.position - ship.position;
+        match collider.tag {
+            ColliderTag::Asteroid
+            | ColliderTag::Enemy
+            | ColliderTag::Projectile
+            | ColliderTag::Boss => {
+                if matches!(collider.tag, ColliderTag::Enemy | ColliderTag::Boss) {
+                    shoot = true;
+                }
+
+                // Closest approach assuming both keep going the way they are
+                let relative_velocity = velocity_vector(velocity) - ship.velocity;
+                let time = if relative_velocity.length_squared() > f32::EPSILON {
+                    (-offset.dot(relative_velocity) / relative_velocity.length_squared())
+                        .clamp(0.0, LOOKAHEAD_TICKS)
+                } else {
+                    0.0
+                };
+                let closest = offset + relative_velocity * time;
+                let safe_distance = ship.radius + radius(transform) + DANGER_MARGIN;
+                if closest.length() < safe_distance {
+                    // Sooner and closer threats push harder
+                    let urgency =
+                        (1.0 - time / LOOKAHEAD_TICKS) * (1.0 - closest.length() / safe_distance);
+                    let away = if closest.length_squared() > f32::EPSILON {
+                        -closest.normalize()
+                    } else {
+                        relative_velocity.perp().normalize_or_zero()
+                    };
+                    avoidance += away * urgency;
+                }
+            }
+            ColliderTag::Pickup => {
+                let is_repair = pickup.is_some_and(|pickup| pickup.kind == PickupKind::Repair);
+                if is_repair || ship.health >= HUNGRY_HEALTH {
+                    let distance = offset.length();
+                    let closer = match closest_repair {
+                        Some((best, _)) => distance < best,
+                        None => true,
+                    };
+                    if closer {
+                        closest_repair = Some((distance, offset));
+                    }
+                }
+            }
+            ColliderTag::Player | ColliderTag::PlayerProjectile => (),
+        }
+    }
+
+    if avoidance.length_squared() > f32::EPSILON {
+        return (avoidance.normalize(), shoot);
+    }
+    if let Some((_, offset)) = closest_repair {
+        return (offset.normalize_or_zero(), shoot);
+    }
+
+    // Nothing to do, drift back towards the middle where there's room to dodge
+    let half_size = glam::vec2(window_size.0 as f32, window_size.1 as f32) / 2.0;
+    if ship.position.abs().cmpgt(half_size * 0.5).any() {
+        return (-ship.position.normalize_or_zero(), shoot);
+    }
+    (glam::Vec2::ZERO, shoot)
+}
+
+fn steer(ship: &Ship, direction: glam::Vec2, wanted: &mut HashSet<Action>) {
+    if direction == glam::Vec2::ZERO {
+        if ship.velocity.length() > CRUISE_SPEED / 2.0 {
+            wanted.insert(Action::Brake);
+        }
+        return;
+    }
+
+    let angle = ship.facing.angle_between(direction);
+    if angle > TURN_DEADZONE {
+        wanted.insert(Action::RotateLeft);
+    } else if angle < -TURN_DEADZONE {
+        wanted.insert(Action::RotateRight);
+    }
+
+    if angle.abs() < THRUST_ANGLE {
+        if ship.velocity.length() < CRUISE_SPEED {
+            wanted.insert(Action::Thrust);
+        }
+    } else if ship.velocity.dot(direction) < 0.0 {
+        // Going the wrong way, bleed off speed while turning around
+        wanted.insert(Action::Brake);
+    }
+}
@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::game::{
+    component::{Boss, BossPart, Collider, ColliderTag, Display, Player, Spinner, Transform},
+    resource::{BossPhase, BossStatus, DeltaTime, Difficulty, GameState, GameWindowSize},
+    system::projectile_system::{ProjectileSpawn, ProjectileSystem},
+};
+
+const BOSS_FIRST_APPEARANCE: f32 = 90.0;
+const BOSS_INTERVAL: f32 = 120.0;
+const FLARE_DURATION: f32 = 3.0;
+
+const CORE_HEALTH: f32 = 300.0;
+const CORE_SIZE: f32 = 150.0;
+const CORE_SPRITE_IDX: u32 = 4;
+const CORE_SWAY_SPEED: f32 = 0.4;
+
+const PART_COUNT: u32 = 4;
+const PART_HEALTH: f32 = 60.0;
+const PART_SIZE: f32 = 50.0;
+const PART_SPRITE_IDX: u32 = 3;
+const PART_ORBIT_RADIUS: f32 = 130.0;
+const PART_ORBIT_SPEED: f32 = 0.6;
+
+const ATTACK_INTERVAL: f32 = 2.5;
+const BURST_PROJECTILES: u32 = 16;
+const BURST_SPEED: f32 = 4.0;
+const BURST_DAMAGE: f32 = 6.0;
+const BEAM_SHOTS: u32 = 10;
+const BEAM_SHOT_INTERVAL: f32 = 0.06;
+const BEAM_SPEED: f32 = 10.0;
+const BEAM_DAMAGE: f32 = 4.0;
+const VOLLEY_SPEED: f32 = 6.0;
+const VOLLEY_DAMAGE: f32 = 5.0;
+const PROJECTILE_SIZE: f32 = 12.0;
+
+#[derive(Debug, Clone, Copy)]
+enum BossAttack {
+    // Ring of shots from the core
+    RadialBurst,
+    // Rapid stream of shots from the core straight at the player
+    TargetedBeam,
+    // Every remaining part takes one aimed shot
+    PartVolley,
+}
+
+const ATTACK_CYCLE: [BossAttack; 3] = [
+    BossAttack::RadialBurst,
+    BossAttack::PartVolley,
+    BossAttack::TargetedBeam,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BossState {
+    pub clock: f32,
+    pub started: bool,
+}
+
+impl Default for BossState {
+    fn default() -> Self {
+        Self {
+            clock: BOSS_FIRST_APPEARANCE,
+            started: false,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BossSystem {}
+
+impl<'a> System<'a> for BossSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Boss>,
+        WriteStorage<'a, BossPart>,
+        WriteStorage<'a, Transform>,
+        ReadStorage<'a, Player>,
+        Read<'a, LazyUpdate>,
+        Read<'a, GameState>,
+        Read<'a, DeltaTime>,
+        Read<'a, GameWindowSize>,
+        Write<'a, BossStatus>,
+        Write<'a, Difficulty>,
+        Write<'a, BossState>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut boss_storage,
+            mut part_storage,
+            mut transform_storage,
+            player_storage,
+            updater,
+            game_state,
+            dt,
+            game_window_size,
+            mut boss_status,
+            mut difficulty,
+            mut state,
+        ): Self::SystemData,
+    ) {
+        if !matches!(*game_state, GameState::GameStatePlay { .. }) {
+            if state.started {
+                for (entity, _) in (&entities, &boss_storage).join() {
+                    entities.delete(entity).unwrap();
+                }
+                for (entity, _) in (&entities, &part_storage).join() {
+                    entities.delete(entity).unwrap();
+                }
+            }
+            *state = BossState::default();
+            *boss_status = BossStatus::default();
+            return;
+        }
+        state.started = true;
+
+        let delta = dt.0.as_secs_f32();
+        let half_height = game_window_size.1 as f32 / 2.0;
+        let half_width = game_window_size.0 as f32 / 2.0;
+
+        match boss_status.phase {
+            BossPhase::Dormant => {
+                state.clock -= delta;
+                if state.clock < 0.0 {
+                    boss_status.phase = BossPhase::Flare;
+                    state.clock = FLARE_DURATION;
+                }
+            }
+            BossPhase::Flare => {
+                state.clock -= delta;
+                if state.clock < 0.0 {
+                    let position = glam::vec2(0.0, half_height * 0.55);
+                    spawn_boss(position, &entities, &updater);
+                    boss_status.phase = BossPhase::Fight;
+                    boss_status.health_fraction = 1.0;
+                }
+            }
+            BossPhase::Fight => {
+                // LazyUpdate hasn't landed the boss yet on the tick it spawns
+                let core = match (&entities, &boss_storage).join().next() {
+                    Some((entity, _)) => entity,
+                    None => return,
+                };
+
+                for (entity, part) in (&entities, &part_storage).join() {
+                    if part.health <= 0.0 {
+                        entities.delete(entity).unwrap();
+                    }
+                }
+
+                let boss = boss_storage.get_mut(core).unwrap();
+                boss_status.health_fraction = boss.health / boss.max_health;
+
+                if boss.health <= 0.0 {
+                    entities.delete(core).unwrap();
+                    for (entity, _) in (&entities, &part_storage).join() {
+                        entities.delete(entity).unwrap();
+                    }
+                    difficulty.advance_orbit();
+                    boss_status.phase = BossPhase::Dormant;
+                    boss_status.health_fraction = 0.0;
+                    state.clock = BOSS_INTERVAL;
+                    return;
+                }
+
+                boss.age += delta;
+                let core_position = glam::vec2(
+                    (boss.age * CORE_SWAY_SPEED).sin() * half_width * 0.5,
+                    half_height * 0.55,
+                );
+                transform_storage.get_mut(core).unwrap().position = core_position;
+
+                let mut part_positions = Vec::new();
+                for (part, transform) in (&mut part_storage, &mut transform_storage).join() {
+                    if part.core != core || part.health <= 0.0 {
+                        continue;
+                    }
+                    part.angle += PART_ORBIT_SPEED * delta;
+                    transform.position = core_position
+                        + glam::vec2(part.angle.cos(), part.angle.sin()) * part.radius;
+                    part_positions.push(transform.position);
+                }
+
+                let target = (&player_storage, &transform_storage)
+                    .join()
+                    .next()
+                    .map(|(_, transform)| transform.position);
+
+                let boss = boss_storage.get_mut(core).unwrap();
+                // Faster attacks in the later orbits
+                let attack_interval = ATTACK_INTERVAL * 0.9_f32.powi(difficulty.orbit as i32);
+                run_attacks(
+                    boss,
+                    core_position,
+                    &part_positions,
+                    target,
+                    attack_interval,
+                    delta,
+                    &entities,
+                    &updater,
+                );
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_attacks<'a>(
+    boss: &mut Boss,
+    core_position: glam::Vec2,
+    part_positions: &[glam::Vec2],
+    target: Option<glam::Vec2>,
+    attack_interval: f32,
+    delta: f32,
+    entities: &Entities,
+    updater: &Read<'a, LazyUpdate>,
+) {
+    let aim_from =
+        |from: glam::Vec2| target.map_or(-glam::Vec2::Y, |to| (to - from).normalize_or_zero());
+    let shoot = |position: glam::Vec2, direction: glam::Vec2, speed: f32, damage: f32| {
+        ProjectileSystem::spawn_projectile(
+            ProjectileSpawn {
+                position,
+                direction,
+                speed,
+                size: PROJECTILE_SIZE,
+                damage,
+                tag: ColliderTag::Projectile,
+            },
+            entities,
+            updater,
+        );
+    };
+
+    // A beam in progress keeps going shot by shot
+    if boss.beam_shots_left > 0 {
+        boss.beam_clock -= delta;
+        if boss.beam_clock < 0.0 {
+            shoot(
+                core_position,
+                aim_from(core_position),
+                BEAM_SPEED,
+                BEAM_DAMAGE,
+            );
+            boss.beam_shots_left -= 1;
+            boss.beam_clock = BEAM_SHOT_INTERVAL;
+        }
+    }
+
+    boss.attack_clock -= delta;
+    if boss.attack_clock > 0.0 {
+        return;
+    }
+    boss.attack_clock = attack_interval;
+
+    let attack = ATTACK_CYCLE[boss.next_attack % ATTACK_CYCLE.len()];
+    boss.next_attack += 1;
+
+    match attack {
+        BossAttack::RadialBurst => {
+            // Offset every other burst so there's no safe spot to camp in
+            let offset = (boss.next_attack % 2) as f32 * 0.5;
+            for idx in 0..BURST_PROJECTILES {
+                let angle =
+                    (idx as f32 + offset) / BURST_PROJECTILES as f32 * std::f32::consts::TAU;
+                shoot(
+                    core_position,
+                    glam::vec2(angle.cos(), angle.sin()),
+                    BURST_SPEED,
+                    BURST_DAMAGE,
+                );
+            }
+        }
+        BossAttack::TargetedBeam => {
+            boss.beam_shots_left = BEAM_SHOTS;
+            boss.beam_clock = 0.0;
+        }
+        BossAttack::PartVolley => {
+            for position in part_positions {
+                shoot(*position, aim_from(*position), VOLLEY_SPEED, VOLLEY_DAMAGE);
+            }
+        }
+    }
+}
+
+fn spawn_boss<'a>(position: glam::Vec2, entities: &Entities, updater: &Read<'a, LazyUpdate>) {
+    let core = entities.create();
+
+    updater.insert(
+        core,
+        Transform {
+            position,
+            scale: glam::vec2(CORE_SIZE, CORE_SIZE),
+            ..Default::default()
+        },
+    );
+    updater.insert(core, Display::new(CORE_SPRITE_IDX));
+    updater.insert(core, Spinner { speed: 0.5 });
+    updater.insert(core, Collider::new(ColliderTag::Boss));
+    updater.insert(core, Boss::new(CORE_HEALTH, ATTACK_INTERVAL));
+
+    for idx in 0..PART_COUNT {
+        let part = entities.create();
+        let angle = idx as f32 / PART_COUNT as f32 * std::f32::consts::TAU;
+
+        updater.insert(
+            part,
+            Transform {
+                position: position + glam::vec2(angle.cos(), angle.sin()) * PART_ORBIT_RADIUS,
+                scale: glam::vec2(PART_SIZE, PART_SIZE),
+                ..Default::default()
+            },
+        );
+        updater.insert(part, Display::new(PART_SPRITE_IDX));
+        updater.insert(part, Spinner { speed: -2.0 });
+        updater.insert(part, Collider::new(ColliderTag::Boss));
+        updater.insert(
+            part,
+            BossPart {
+                core,
+                angle,
+                radius: PART_ORBIT_RADIUS,
+                health: PART_HEALTH,
+            },
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use specs::prelude::*;
+
+use crate::game::{
+    component::{Collider, ColliderTag, Enemy, Player, Transform, Velocity},
+    enemy::steering_direction,
+    resource::DeltaTime,
+    system::projectile_system::{ProjectileSpawn, ProjectileSystem},
+};
+
+const ASTEROID_DAMAGE_TO_ENEMIES: f32 = 40.0;
+const PROJECTILE_SIZE: f32 = 10.0;
+
+#[derive(Default)]
+pub struct EnemyAiSystem {}
+
+impl<'a> System<'a> for EnemyAiSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Enemy>,
+        ReadStorage<'a, Player>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, Velocity>,
+        ReadStorage<'a, Collider>,
+        Read<'a, LazyUpdate>,
+        Read<'a, DeltaTime>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut enemy_storage,
+            player_storage,
+            mut transform_storage,
+            mut velocity_storage,
+            collider_storage,
+            updater,
+            dt,
+        ): Self::SystemData,
+    ) {
+        let delta = dt.0.as_secs_f32();
+
+        // Asteroids don't care who they hit
+        for (entity, enemy, enemy_transform, enemy_collider) in (
+            &entities,
+            &mut enemy_storage,
+            &transform_storage,
+            &collider_storage,
+        )
+            .join()
+        {
+            for (other_transform, other_collider) in (&transform_storage, &collider_storage).join()
+            {
+                if other_collider.tag == ColliderTag::Asteroid
+                    && enemy_collider.intersects(enemy_transform, other_collider, other_transform)
+                {
+                    enemy.health -= ASTEROID_DAMAGE_TO_ENEMIES * delta;
+                }
+            }
+
+            if enemy.health <= 0.0 {
+                entities.delete(entity).unwrap();
+            }
+        }
+
+        let player_positions: Vec<glam::Vec2> = (&player_storage, &transform_storage)
+            .join()
+            .map(|(_, transform)| transform.position)
+            .collect();
+
+        for (enemy, transform, velocity) in (
+            &mut enemy_storage,
+            &mut transform_storage,
+            &mut velocity_storage,
+        )
+            .join()
+        {
+            let target = player_positions.iter().copied().min_by(|a, b| {
+                a.distance_squared(transform.position)
+                    .total_cmp(&b.distance_squared(transform.position))
+            });
+            let target = match target {
+                Some(target) => target,
+                // Nobody to chase, drift to a stop
+                None => {
+                    velocity.speed =
+                        (velocity.speed - enemy.variant.behavior.acceleration * delta).max(0.0);
+                    continue;
+                }
+            };
+
+            let behavior = &enemy.variant.behavior;
+            let desired = steering_direction(
+                behavior,
+                transform.position,
+                target,
+                enemy.health_fraction(),
+                enemy.strafe_sign,
+            )
+            .clamp_length_max(1.0)
+                * behavior.max_speed;
+
+            let current = velocity.direction * velocity.speed;
+            let steered =
+                current + (desired - current).clamp_length_max(behavior.acceleration * delta);
+            velocity.speed = steered.length();
+            if velocity.speed > f32::EPSILON {
+                velocity.direction = steered / velocity.speed;
+            }
+
+            // Always keep the nose pointed at the player, that's where the guns are
+            let to_target = target - transform.position;
+            if to_target.length_squared() > f32::EPSILON {
+                transform.rotation =
+                    glam::Quat::from_rotation_z(f32::atan2(-to_target.x, to_target.y));
+            }
+
+            if let Some(weapon) = &enemy.variant.weapon {
+                enemy.fire_clock -= delta;
+                if enemy.fire_clock < 0.0 {
+                    let facing = transform.get_facing_vector();
+                    ProjectileSystem::spawn_projectile(
+                        ProjectileSpawn {
+                            position: transform.position + facing * transform.scale.max_element(),
+                            direction: facing,
+                            speed: weapon.projectile_speed,
+                            size: PROJECTILE_SIZE,
+                            damage: weapon.damage,
+                            tag: ColliderTag::Projectile,
+                        },
+                        &entities,
+                        &updater,
+                    );
+                    enemy.fire_clock = weapon.interval;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::game::{
+    component::{Collider, ColliderTag, Display, Enemy, Transform, Velocity},
+    enemy::{EnemyRoster, EnemyVariant},
+    resource::{DeltaTime, GameRng, GameState, GameWindowSize},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnemySpawnerState {
+    pub spawn_clock: f32,
+    pub started: bool,
+}
+
+#[derive(Default)]
+pub struct EnemySpawnerSystem {}
+
+impl<'a> System<'a> for EnemySpawnerSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Enemy>,
+        Read<'a, LazyUpdate>,
+        Read<'a, GameState>,
+        Read<'a, DeltaTime>,
+        Read<'a, GameWindowSize>,
+        Read<'a, EnemyRoster>,
+        Write<'a, GameRng>,
+        Write<'a, EnemySpawnerState>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            enemy_storage,
+            updater,
+            game_state,
+            dt,
+            game_window_size,
+            roster,
+            mut game_rng,
+            mut state,
+        ): Self::SystemData,
+    ) {
+        match *game_state {
+            GameState::GameStatePlay { .. } => {
+                if !state.started {
+                    state.started = true;
+                    state.spawn_clock = roster.first_spawn;
+                }
+
+                state.spawn_clock -= dt.0.as_secs_f32();
+
+                if state.spawn_clock < 0.0 {
+                    let alive = (&enemy_storage).join().count();
+                    let rng = &mut game_rng.0;
+
+                    if alive < roster.max_alive {
+                        if let Some(variant) = roster.pick(rng) {
+                            Self::spawn_enemy(variant, &game_window_size, &entities, &updater, rng);
+                        }
+                    }
+                    state.spawn_clock = roster.spawn_interval
+                }
+            }
+            GameState::GameStateEnd {} => {
+                state.started = false;
+                for (entity, _) in (&entities, &enemy_storage).join() {
+                    entities.delete(entity).unwrap();
+                }
+            }
+            _ => state.started = false,
+        }
+    }
+}
+
+impl EnemySpawnerSystem {
+    pub fn spawn_enemy<'a, R: Rng>(
+        variant: &EnemyVariant,
+        game_window_size: &GameWindowSize,
+        entities: &Entities,
+        updater: &Read<'a, LazyUpdate>,
+        rng: &mut R,
+    ) -> Entity {
+        let enemy = entities.create();
+
+        // Come in from a random point just off one of the side edges
+        let half_width = game_window_size.0 as f32 / 2.0;
+        let half_height = game_window_size.1 as f32 / 2.0;
+        let side = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+        let position = glam::vec2(
+            side * (half_width + variant.size),
+            rng.gen_range(-half_height..half_height),
+        );
+        let strafe_sign = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+
+        updater.insert(
+            enemy,
+            Transform {
+                position,
+                scale: glam::vec2(variant.size, variant.size),
+                ..Default::default()
+            },
+        );
+        updater.insert(
+            enemy,
+            Velocity {
+                direction: glam::vec2(-side, 0.0),
+                speed: 0.0,
+            },
+        );
+        updater.insert(
+            enemy,
+            Display {
+                tint: variant.tint,
+                ..Display::new(variant.sprite_idx)
+            },
+        );
+        updater.insert(enemy, Collider::new(ColliderTag::Enemy));
+        updater.insert(enemy, Enemy::new(variant, strafe_sign));
+
+        enemy
+    }
+}
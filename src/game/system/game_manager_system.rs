@@ -1,94 +1,479 @@
 use specs::{prelude::*, shrev::EventChannel};
-use winit::event::VirtualKeyCode;
 
-use crate::game::{
-    component::{Collider, ColliderTag, Display, Player, Transform, Velocity},
-    resource::{DeltaTime, GameState, GameStateForRenderer, KeyboardEvent},
+use crate::{
+    audio::{Sound, SoundEvent},
+    game::{
+        component::{
+            Collider, ColliderTag, Display, Emitter, Player, StatusEffectKind, StatusEffects,
+            Transform, Velocity,
+        },
+        menu::{MenuAction, MenuCommand},
+        particles::{ParticleBursts, ASTEROID_DEBRIS_EFFECT, THRUST_EFFECT},
+        resource::{
+            BossStatus, DamageSource, DeltaTime, Difficulty, GameState, GameStateForRenderer,
+            HighScore, HighScores, Lives, LivesMode, PlayerBar, PlayerCount, RunHud, RunStats,
+            Score, WaveStatus,
+        },
+        system::{BossState, EnemySpawnerState, WaveSchedulerState},
+    },
 };
 
-const GAMERULE_PLAYER_TICK_DAMAGE: f32 = 4.8;
+const GAMERULE_SCORE_PER_SECOND: f32 = 10.0;
+const GAMERULE_RESPAWN_INVULNERABILITY: f32 = 3.0;
+// Asteroids this close to the center get cleared out when the ship respawns
+const GAMERULE_RESPAWN_CLEAR_RADIUS: f32 = 250.0;
+// Horizontal gap between ships when more than one player is in the run
+const PLAYER_SPAWN_SPACING: f32 = 200.0;
+// Exhaust comes out of the flame at the bottom of the ship sprite
+const PLAYER_EXHAUST_OFFSET: glam::Vec2 = glam::const_vec2!([0.0, -0.8]);
+
+// How much of the damage flash a full health bar's worth of damage gives, and how fast it fades
+// per second. Radiation on its own is too slow to show up.
+const DAMAGE_FLASH_SCALE: f32 = 4.0;
+const DAMAGE_FLASH_FADE: f32 = 1.5;
+// The alarm goes off below this much health, out of the 100 a ship starts with
+const LOW_HEALTH_ALARM: f32 = 25.0;
+// Radiation damage per second that fills the HUD meter
+const RADIATION_METER_FULL: f32 = 12.0;
+
+// Player one keeps the untinted ship, also caps how many players can join
+const PLAYER_TINTS: [[f32; 4]; 4] = [
+    [1.0, 1.0, 1.0, 1.0],
+    [1.0, 0.55, 0.45, 1.0],
+    [0.5, 1.0, 0.55, 1.0],
+    [0.6, 0.7, 1.0, 1.0],
+];
+pub(super) const MAX_PLAYERS: usize = PLAYER_TINTS.len();
 
 #[derive(Default)]
-pub struct GameManagerSystem {
-    reader: Option<ReaderId<KeyboardEvent>>,
-}
+pub struct GameManagerSystem {}
 
 impl<'a> System<'a> for GameManagerSystem {
     type SystemData = (
         WriteStorage<'a, Player>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, Velocity>,
+        WriteStorage<'a, StatusEffects>,
+        ReadStorage<'a, Collider>,
+        ReadStorage<'a, Display>,
         Entities<'a>,
         Read<'a, LazyUpdate>,
         Write<'a, GameState>,
         Write<'a, GameStateForRenderer>,
+        Write<'a, Score>,
+        Write<'a, Difficulty>,
+        Write<'a, Lives>,
         Read<'a, DeltaTime>,
-        Read<'a, EventChannel<KeyboardEvent>>,
+        Write<'a, MenuCommand>,
+        Write<'a, RunStats>,
+        Read<'a, PlayerCount>,
+        Write<'a, ParticleBursts>,
+        Write<'a, HighScores>,
+        Write<'a, EventChannel<SoundEvent>>,
+        Write<'a, EnemySpawnerState>,
+        Write<'a, WaveSchedulerState>,
+        Write<'a, WaveStatus>,
+        Write<'a, BossState>,
+        Write<'a, BossStatus>,
     );
 
-    fn setup(&mut self, world: &mut World) {
-        Self::SystemData::setup(world);
-        self.reader = Some(
-            world
-                .fetch_mut::<EventChannel<KeyboardEvent>>()
-                .register_reader(),
-        );
-    }
-
     fn run(
         &mut self,
         (
             mut player_storage,
+            mut transform_storage,
+            mut velocity_storage,
+            mut status_storage,
+            collider_storage,
+            display_storage,
             entities,
             updater,
             mut game_state,
             mut game_state_renderer,
+            mut score,
+            mut difficulty,
+            mut lives,
             dt,
-            events,
+            mut command,
+            mut stats,
+            player_count,
+            mut bursts,
+            mut high_scores,
+            mut sounds,
+            mut enemy_spawner,
+            mut wave_scheduler,
+            mut wave_status,
+            mut boss,
+            mut boss_status,
         ): Self::SystemData,
     ) {
+        let delta = dt.0.as_secs_f32();
+        game_state_renderer.damage_flash =
+            (game_state_renderer.damage_flash - DAMAGE_FLASH_FADE * delta).max(0.0);
+
         match *game_state {
             GameState::GameStateInit {} => {
-                // @REFACTOR
-                for event in events.read(&mut self.reader.as_mut().unwrap()) {
-                    match event {
-                        &KeyboardEvent::Pressed(VirtualKeyCode::Return) => {
-                            let player_entity = spawn_player(&entities, &updater);
-                            *game_state = GameState::GameStatePlay { player_entity };
-                        }
-                        _ => (),
-                    }
+                if command.0.take() == Some(MenuAction::Start) {
+                    *game_state = start_run(
+                        &player_count,
+                        &mut score,
+                        &mut difficulty,
+                        &mut lives,
+                        &mut stats,
+                        &entities,
+                        &updater,
+                    );
+                    return;
                 }
+
+                game_state_renderer.player_bars.clear();
+                game_state_renderer.run_hud = None;
             }
-            GameState::GameStatePlay { player_entity } => {
-                let player_component = player_storage.get_mut(player_entity).unwrap();
-                player_component.health -= GAMERULE_PLAYER_TICK_DAMAGE * dt.0.as_secs_f32();
-                if player_component.health <= 0.0 {
-                    entities.delete(player_entity).unwrap();
-                    *game_state = GameState::GameStateEnd {};
+            GameState::GameStatePlay {
+                ref mut player_entities,
+            } => {
+                // Leaving from the pause menu skips the game over screen, so the run is cleared
+                // out here instead of by the other systems once they see the game over state
+                let leaving = command.0.take();
+                if matches!(
+                    leaving,
+                    Some(MenuAction::Restart) | Some(MenuAction::QuitRun)
+                ) {
+                    end_run(
+                        &entities,
+                        &collider_storage,
+                        &mut enemy_spawner,
+                        &mut wave_scheduler,
+                        &mut wave_status,
+                        &mut boss,
+                        &mut boss_status,
+                    );
+                    *game_state = if leaving == Some(MenuAction::Restart) {
+                        start_run(
+                            &player_count,
+                            &mut score,
+                            &mut difficulty,
+                            &mut lives,
+                            &mut stats,
+                            &entities,
+                            &updater,
+                        )
+                    } else {
+                        GameState::GameStateInit {}
+                    };
+                    return;
                 }
-                game_state_renderer.player_health = player_component.health / 100.0;
-            }
-            GameState::GameStateEnd {} => {
-                // @REFACTOR
-                for event in events.read(&mut self.reader.as_mut().unwrap()) {
-                    match event {
-                        &KeyboardEvent::Pressed(VirtualKeyCode::Return) => {
-                            *game_state = GameState::GameStateInit {};
+
+                score.points += GAMERULE_SCORE_PER_SECOND * score.multiplier * delta;
+                difficulty.update(delta, &score);
+                lives.award_for_score(&score);
+
+                let mut downed = Vec::new();
+                for &player_entity in player_entities.iter() {
+                    let invulnerable = status_storage
+                        .get(player_entity)
+                        .is_some_and(|effects| effects.has(StatusEffectKind::Invulnerable));
+                    let player_component = player_storage.get_mut(player_entity).unwrap();
+                    if !invulnerable {
+                        let damage = difficulty.current.radiation_damage * delta;
+                        player_component.health -= damage;
+                        stats.add_damage(DamageSource::Radiation, damage);
+                    }
+
+                    if player_component.health <= 0.0 {
+                        let slot = player_component.slot;
+                        if lives.take(slot) {
+                            *player_component = Player {
+                                weapon_level: player_component.weapon_level,
+                                ..Player::new(slot)
+                            };
+                            let spawn_position = spawn_position(slot, player_count.0);
+                            respawn_player(
+                                player_entity,
+                                spawn_position,
+                                &mut transform_storage,
+                                &mut velocity_storage,
+                                &mut status_storage,
+                            );
+                            clear_respawn_area(
+                                spawn_position,
+                                &entities,
+                                &transform_storage,
+                                &collider_storage,
+                                &mut bursts,
+                            );
+                        } else {
+                            entities.delete(player_entity).unwrap();
+                            downed.push(player_entity);
                         }
-                        _ => (),
                     }
                 }
+                player_entities.retain(|entity| !downed.contains(entity));
+
+                for &player_entity in player_entities.iter() {
+                    let health = player_storage.get(player_entity).unwrap().health;
+                    if let (true, Some(transform)) = (
+                        health < LOW_HEALTH_ALARM,
+                        transform_storage.get(player_entity),
+                    ) {
+                        sounds.single_write(SoundEvent::Hold(
+                            Sound::LowHealthAlarm,
+                            player_entity.id(),
+                            transform.position,
+                        ));
+                    }
+                }
+
+                let mut player_bars: Vec<(usize, PlayerBar)> = player_entities
+                    .iter()
+                    .map(|&player_entity| {
+                        let player_component = player_storage.get(player_entity).unwrap();
+                        let effects = status_storage.get(player_entity);
+                        let bar = PlayerBar {
+                            health: player_component.health / 100.0,
+                            tint: display_storage
+                                .get(player_entity)
+                                .map_or(Display::NO_TINT, |display| display.tint),
+                            effects: effects
+                                .map(|effects| {
+                                    effects
+                                        .active
+                                        .iter()
+                                        .map(|effect| (effect.kind, effect.remaining))
+                                        .collect()
+                                })
+                                .unwrap_or_default(),
+                        };
+                        (player_component.slot, bar)
+                    })
+                    .collect();
+                player_bars.sort_by_key(|(slot, _)| *slot);
+                // Bars only line up with last tick's while nobody dropped out
+                if player_bars.len() == game_state_renderer.player_bars.len() {
+                    let health_lost = game_state_renderer
+                        .player_bars
+                        .iter()
+                        .zip(player_bars.iter())
+                        .map(|(old, (_, new))| (old.health - new.health).max(0.0))
+                        .fold(0.0, f32::max);
+                    game_state_renderer.damage_flash = (game_state_renderer.damage_flash
+                        + health_lost * DAMAGE_FLASH_SCALE)
+                        .min(1.0);
+                }
+                game_state_renderer.player_bars =
+                    player_bars.into_iter().map(|(_, bar)| bar).collect();
+                game_state_renderer.run_hud = Some(RunHud {
+                    score: score.points as u32,
+                    multiplier: score.multiplier,
+                    lives: lives
+                        .pools
+                        .iter()
+                        .enumerate()
+                        .map(|(pool, &count)| {
+                            let tint = match lives.mode {
+                                LivesMode::Shared => Display::NO_TINT,
+                                LivesMode::Separate => PLAYER_TINTS[pool],
+                            };
+                            (count, tint)
+                        })
+                        .collect(),
+                    radiation: (difficulty.current.radiation_damage / RADIATION_METER_FULL)
+                        .min(1.0),
+                });
+
+                // The run is over once the last player is out of lives
+                if player_entities.is_empty() {
+                    high_scores.record(HighScore {
+                        score: score.points as u32,
+                        preset: difficulty.preset,
+                        players: player_count.0,
+                    });
+                    sounds.single_write(SoundEvent::Play(Sound::GameOver, None));
+                    *game_state = GameState::GameStateEnd {};
+                }
             }
+            GameState::GameStateEnd {} => match command.0.take() {
+                Some(MenuAction::Retry) => {
+                    *game_state = start_run(
+                        &player_count,
+                        &mut score,
+                        &mut difficulty,
+                        &mut lives,
+                        &mut stats,
+                        &entities,
+                        &updater,
+                    );
+                }
+                Some(MenuAction::MainMenu) => *game_state = GameState::GameStateInit {},
+                _ => (),
+            },
         }
     }
 }
 
-fn spawn_player<'a>(entities: &Entities, updater: &Read<'a, LazyUpdate>) -> Entity {
+fn start_run<'a>(
+    player_count: &PlayerCount,
+    score: &mut Score,
+    difficulty: &mut Difficulty,
+    lives: &mut Lives,
+    stats: &mut RunStats,
+    entities: &Entities,
+    updater: &Read<'a, LazyUpdate>,
+) -> GameState {
+    let player_entities = (0..player_count.0)
+        .map(|slot| spawn_player(slot, player_count.0, entities, updater))
+        .collect();
+    *score = Score::default();
+    *difficulty = Difficulty::new(difficulty.preset);
+    *lives = Lives::new(lives.mode, player_count.0);
+    *stats = RunStats::default();
+    GameState::GameStatePlay { player_entities }
+}
+
+// Everything from the run has a collider, players and the ships they left behind included. The
+// spawners pick up from the start of their schedules again on the next run.
+fn end_run(
+    entities: &Entities,
+    collider_storage: &ReadStorage<Collider>,
+    enemy_spawner: &mut EnemySpawnerState,
+    wave_scheduler: &mut WaveSchedulerState,
+    wave_status: &mut WaveStatus,
+    boss: &mut BossState,
+    boss_status: &mut BossStatus,
+) {
+    for (entity, _) in (entities, collider_storage).join() {
+        entities.delete(entity).unwrap();
+    }
+    *enemy_spawner = EnemySpawnerState::default();
+    *wave_scheduler = WaveSchedulerState::default();
+    *wave_status = WaveStatus::default();
+    *boss = BossState::default();
+    *boss_status = BossStatus::default();
+}
+
+fn spawn_position(slot: usize, player_count: usize) -> glam::Vec2 {
+    let offset = slot as f32 - (player_count - 1) as f32 / 2.0;
+    glam::vec2(offset * PLAYER_SPAWN_SPACING, 0.0)
+}
+
+fn respawn_player(
+    player: Entity,
+    position: glam::Vec2,
+    transform_storage: &mut WriteStorage<Transform>,
+    velocity_storage: &mut WriteStorage<Velocity>,
+    status_storage: &mut WriteStorage<StatusEffects>,
+) {
+    if let Some(transform) = transform_storage.get_mut(player) {
+        *transform = Transform {
+            position,
+            ..Default::default()
+        };
+    }
+    if let Some(velocity) = velocity_storage.get_mut(player) {
+        *velocity = Velocity::default();
+    }
+    if let Some(effects) = status_storage.get_mut(player) {
+        *effects = StatusEffects::default();
+        effects.apply(
+            StatusEffectKind::Invulnerable,
+            GAMERULE_RESPAWN_INVULNERABILITY,
+        );
+    }
+}
+
+fn clear_respawn_area(
+    center: glam::Vec2,
+    entities: &Entities,
+    transform_storage: &WriteStorage<Transform>,
+    collider_storage: &ReadStorage<Collider>,
+    bursts: &mut ParticleBursts,
+) {
+    for (entity, transform, collider) in (entities, transform_storage, collider_storage).join() {
+        if collider.tag == ColliderTag::Asteroid
+            && transform.position.distance(center) < GAMERULE_RESPAWN_CLEAR_RADIUS
+        {
+            bursts.push(ASTEROID_DEBRIS_EFFECT, transform.position);
+            entities.delete(entity).unwrap();
+        }
+    }
+}
+
+fn spawn_player<'a>(
+    slot: usize,
+    player_count: usize,
+    entities: &Entities,
+    updater: &Read<'a, LazyUpdate>,
+) -> Entity {
     let player = entities.create();
-    updater.insert(player, Transform::default());
+    updater.insert(
+        player,
+        Transform {
+            position: spawn_position(slot, player_count),
+            ..Default::default()
+        },
+    );
     updater.insert(player, Velocity::default());
-    updater.insert(player, Display { sprite_idx: 0 });
-    updater.insert(player, Player::default());
+    updater.insert(
+        player,
+        Display {
+            sprite_idx: 0,
+            tint: PLAYER_TINTS[slot],
+        },
+    );
+    updater.insert(player, Player::new(slot));
+    updater.insert(player, StatusEffects::default());
     updater.insert(player, Collider::new(ColliderTag::Player));
+    updater.insert(
+        player,
+        Emitter {
+            enabled: false,
+            ..Emitter::new(THRUST_EFFECT, PLAYER_EXHAUST_OFFSET)
+        },
+    );
 
     player
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_ends_after_starting_lives() {
+        let mut world = World::new();
+        let mut system = GameManagerSystem::default();
+        System::setup(&mut system, &mut world);
+
+        let player_entity = world
+            .create_entity()
+            .with(Transform::default())
+            .with(Velocity::default())
+            .with(Player::new(0))
+            .with(StatusEffects::default())
+            .build();
+        *world.write_resource::<GameState>() = GameState::GameStatePlay {
+            player_entities: vec![player_entity],
+        };
+
+        // The ship in play counts, so the HUD's number is exactly how many deaths end the run
+        for death in 1..=Lives::STARTING {
+            world
+                .write_storage::<Player>()
+                .get_mut(player_entity)
+                .unwrap()
+                .health = 0.0;
+            system.run_now(&world);
+            world.maintain();
+
+            let game_over = matches!(
+                *world.read_resource::<GameState>(),
+                GameState::GameStateEnd {}
+            );
+            assert_eq!(game_over, death == Lives::STARTING);
+            assert_eq!(
+                world.read_resource::<Lives>().remaining(0),
+                Lives::STARTING - death
+            );
+        }
+    }
+}
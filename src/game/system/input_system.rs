@@ -0,0 +1,117 @@
+use specs::{prelude::*, shrev::EventChannel};
+use winit::dpi::PhysicalSize;
+
+use crate::{
+    game::{
+        input::{Action, ActionStates, InputBindings, InputFrame, InputSource},
+        resource::{
+            ControlScheme, Cursor, GameState, GameWindowSize, KeyboardEvent, MouseEvent, Paused,
+        },
+    },
+    renderer::Renderer,
+};
+
+// Turns whatever the input sources report into per-player actions. Runs ahead of the update
+// dispatcher, even while paused, so it also owns the pause toggle.
+#[derive(Default)]
+pub struct InputSystem {
+    sources: Vec<Box<dyn InputSource>>,
+    reader: Option<ReaderId<KeyboardEvent>>,
+    mouse_reader: Option<ReaderId<MouseEvent>>,
+    // Kept in window pixels so the world position follows window resizes
+    cursor_window_position: Option<glam::Vec2>,
+}
+
+impl InputSystem {
+    pub fn new(sources: Vec<Box<dyn InputSource>>) -> Self {
+        Self {
+            sources,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> System<'a> for InputSystem {
+    type SystemData = (
+        Read<'a, EventChannel<KeyboardEvent>>,
+        Read<'a, EventChannel<MouseEvent>>,
+        Read<'a, InputBindings>,
+        Read<'a, GameState>,
+        Read<'a, ControlScheme>,
+        Read<'a, GameWindowSize>,
+        Write<'a, ActionStates>,
+        Write<'a, Cursor>,
+        Write<'a, Paused>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<KeyboardEvent>>()
+                .register_reader(),
+        );
+        self.mouse_reader = Some(
+            world
+                .fetch_mut::<EventChannel<MouseEvent>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(
+        &mut self,
+        (
+            events,
+            mouse_events,
+            bindings,
+            game_state,
+            control_scheme,
+            window_size,
+            mut actions,
+            mut cursor,
+            mut paused,
+        ): Self::SystemData,
+    ) {
+        actions.begin_tick();
+
+        let keyboard: Vec<KeyboardEvent> = events
+            .read(self.reader.as_mut().unwrap())
+            .cloned()
+            .collect();
+        let mouse: Vec<MouseEvent> = mouse_events
+            .read(self.mouse_reader.as_mut().unwrap())
+            .cloned()
+            .collect();
+        let frame = InputFrame {
+            keyboard: &keyboard,
+            mouse: &mouse,
+            bindings: &bindings,
+            mouse_enabled: *control_scheme == ControlScheme::Mouse,
+        };
+
+        let mut action_events = Vec::new();
+        for source in self.sources.iter_mut() {
+            source.poll(&frame, &mut action_events);
+        }
+        for event in action_events {
+            actions.apply(event);
+        }
+
+        for event in mouse.iter() {
+            if let MouseEvent::Moved(x, y) = *event {
+                self.cursor_window_position = Some(glam::vec2(x, y));
+            }
+        }
+        cursor.0 = self.cursor_window_position.map(|position| {
+            Renderer::window_to_world(position, PhysicalSize::new(window_size.0, window_size.1))
+        });
+
+        if matches!(*game_state, GameState::GameStatePlay { .. }) {
+            if actions.any_just_pressed(Action::Pause) {
+                paused.0 = !paused.0;
+            }
+        } else {
+            paused.0 = false;
+        }
+    }
+}
@@ -0,0 +1,581 @@
+use specs::{prelude::*, shrev::EventChannel};
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use super::game_manager_system::MAX_PLAYERS;
+use crate::{
+    game::{
+        input::{Action, ActionStates, InputBindings},
+        menu::{self, ActiveMenu, Menu, MenuAction, MenuCommand, MenuItem, MenuScreen, Setting},
+        resource::{
+            ControlScheme, DamageSource, Difficulty, DifficultyPreset, GameState, GameWindowSize,
+            HighScores, KeyboardEvent, Lives, LivesMode, MouseEvent, Paused, PlayerCount,
+            QuitRequested, RunStats, Score,
+        },
+    },
+    settings::{Settings, WindowMode, RESOLUTIONS},
+};
+
+// Volumes go up and down in steps of this much, screen shake in bigger ones
+const VOLUME_STEP: f32 = 0.1;
+const SCREEN_SHAKE_STEP: f32 = 0.25;
+
+// Opens the menu that goes with the game state and moves through it. Runs right after input,
+// outside the update dispatcher, so the pause menu works while everything else is frozen.
+// Up and down are thrust and brake, left and right step through setting values.
+#[derive(Default)]
+pub struct MenuSystem {
+    reader: Option<ReaderId<KeyboardEvent>>,
+    mouse_reader: Option<ReaderId<MouseEvent>>,
+    // In window pixels
+    cursor: Option<glam::Vec2>,
+    // Waiting for a key to bind to this on the controls screen
+    rebinding: Option<Action>,
+}
+
+impl<'a> System<'a> for MenuSystem {
+    type SystemData = (
+        Read<'a, EventChannel<KeyboardEvent>>,
+        Read<'a, EventChannel<MouseEvent>>,
+        Read<'a, ActionStates>,
+        Read<'a, GameState>,
+        Read<'a, GameWindowSize>,
+        Read<'a, Score>,
+        Read<'a, RunStats>,
+        Read<'a, HighScores>,
+        Write<'a, ActiveMenu>,
+        Write<'a, MenuCommand>,
+        Write<'a, Paused>,
+        Write<'a, QuitRequested>,
+        Write<'a, Difficulty>,
+        Write<'a, PlayerCount>,
+        Write<'a, Lives>,
+        Write<'a, ControlScheme>,
+        Write<'a, InputBindings>,
+        Write<'a, Settings>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<KeyboardEvent>>()
+                .register_reader(),
+        );
+        self.mouse_reader = Some(
+            world
+                .fetch_mut::<EventChannel<MouseEvent>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(
+        &mut self,
+        (
+            events,
+            mouse_events,
+            actions,
+            game_state,
+            window_size,
+            score,
+            stats,
+            high_scores,
+            mut active_menu,
+            mut command,
+            mut paused,
+            mut quit,
+            mut difficulty,
+            mut player_count,
+            mut lives,
+            mut control_scheme,
+            mut bindings,
+            mut settings,
+        ): Self::SystemData,
+    ) {
+        let pressed_key = events
+            .read(self.reader.as_mut().unwrap())
+            .filter_map(|event| match event {
+                KeyboardEvent::Pressed(key) => Some(*key),
+                KeyboardEvent::Released(_) => None,
+            })
+            .last();
+        let mouse: Vec<MouseEvent> = mouse_events
+            .read(self.mouse_reader.as_mut().unwrap())
+            .cloned()
+            .collect();
+        let mut hovered = false;
+        for event in mouse.iter() {
+            if let MouseEvent::Moved(x, y) = *event {
+                self.cursor = Some(glam::vec2(x, y));
+                hovered = true;
+            }
+        }
+
+        // The game manager hasn't taken the last pick yet
+        if command.0.is_some() {
+            active_menu.0 = None;
+            return;
+        }
+
+        let current = active_menu.0.as_ref().map(|menu| menu.screen);
+        let screen = match *game_state {
+            GameState::GameStateInit {} => current
+                .filter(|screen| screen.is_title())
+                .or(Some(MenuScreen::Title)),
+            GameState::GameStatePlay { .. } => paused.0.then_some(MenuScreen::Pause),
+            GameState::GameStateEnd {} => Some(MenuScreen::GameOver),
+        };
+        if screen != current {
+            active_menu.0 = screen.map(Menu::new);
+        }
+        if screen != Some(MenuScreen::ControlSettings) {
+            self.rebinding = None;
+        }
+        let menu = match active_menu.0.as_mut() {
+            Some(menu) => menu,
+            None => return,
+        };
+
+        let mut data = MenuData {
+            difficulty: &mut difficulty,
+            player_count: &mut player_count,
+            lives: &mut lives,
+            control_scheme: &mut control_scheme,
+            bindings: &mut bindings,
+            settings: &mut settings,
+            score: &score,
+            stats: &stats,
+            high_scores: &high_scores,
+        };
+
+        // The key being bound also went through the old bindings, so the rest of this tick's
+        // input is ignored
+        if let Some(action) = self.rebinding {
+            match pressed_key {
+                Some(VirtualKeyCode::Escape) => self.rebinding = None,
+                Some(key) => {
+                    data.rebind(action, key);
+                    self.rebinding = None;
+                }
+                None => (),
+            }
+            data.fill(menu, self.rebinding);
+            return;
+        }
+        data.fill(menu, self.rebinding);
+
+        let window_size = (window_size.0, window_size.1);
+        let item_under_cursor = |menu: &Menu, cursor: Option<glam::Vec2>| {
+            cursor.and_then(|cursor| menu::item_at(menu, window_size, cursor))
+        };
+        if hovered {
+            if let Some(idx) = item_under_cursor(menu, self.cursor) {
+                menu.selected = idx;
+            }
+        }
+
+        // Which way to step a setting, activating anything else ignores it
+        let mut activate = None;
+        for event in mouse.iter() {
+            if *event == MouseEvent::Pressed(MouseButton::Left) {
+                if let Some(idx) = item_under_cursor(menu, self.cursor) {
+                    menu.selected = idx;
+                    activate = Some(1);
+                }
+            }
+        }
+        if actions.any_just_pressed(Action::Thrust) {
+            menu.move_selection(-1);
+        }
+        if actions.any_just_pressed(Action::Brake) {
+            menu.move_selection(1);
+        }
+        if actions.any_just_pressed(Action::Confirm) {
+            activate = Some(1);
+        }
+        if let Some(MenuAction::Change(_)) = menu.selected_action() {
+            if actions.any_just_pressed(Action::RotateLeft) {
+                activate = Some(-1);
+            }
+            if actions.any_just_pressed(Action::RotateRight) {
+                activate = Some(1);
+            }
+        }
+
+        let mut picked =
+            activate.and_then(|step| menu.selected_action().map(|action| (action, step)));
+        // Pausing again already resumes, on the title screen pages it goes back
+        if menu.screen.parent().is_some() && actions.any_just_pressed(Action::Pause) {
+            picked = Some((MenuAction::Back, 1));
+        }
+
+        if let Some((action, step)) = picked {
+            match action {
+                MenuAction::Open(screen) => *menu = Menu::new(screen),
+                MenuAction::Back => {
+                    if let Some(parent) = menu.screen.parent() {
+                        *menu = Menu::new(parent);
+                    }
+                }
+                MenuAction::Quit => quit.0 = true,
+                MenuAction::Change(setting) => data.change(setting, step),
+                MenuAction::Rebind(action) => self.rebinding = Some(action),
+                MenuAction::Resume => {
+                    paused.0 = false;
+                    active_menu.0 = None;
+                    return;
+                }
+                // Run changes are up to the game manager, which has to run this tick
+                MenuAction::Start
+                | MenuAction::Restart
+                | MenuAction::QuitRun
+                | MenuAction::Retry
+                | MenuAction::MainMenu => {
+                    command.0 = Some(action);
+                    paused.0 = false;
+                    active_menu.0 = None;
+                    return;
+                }
+            }
+        }
+        data.fill(menu, self.rebinding);
+    }
+}
+
+// Everything the menus show or change
+struct MenuData<'a> {
+    difficulty: &'a mut Difficulty,
+    player_count: &'a mut PlayerCount,
+    lives: &'a mut Lives,
+    control_scheme: &'a mut ControlScheme,
+    bindings: &'a mut InputBindings,
+    settings: &'a mut Settings,
+    score: &'a Score,
+    stats: &'a RunStats,
+    high_scores: &'a HighScores,
+}
+
+impl MenuData<'_> {
+    fn change(&mut self, setting: Setting, step: i32) {
+        let settings = &mut *self.settings;
+        match setting {
+            Setting::Difficulty => {
+                let preset = cycle(&DifficultyPreset::ALL, self.difficulty.preset, step);
+                *self.difficulty = Difficulty::new(preset);
+            }
+            Setting::Players => {
+                let max_players = self.bindings.player_count().min(MAX_PLAYERS);
+                let counts: Vec<usize> = (1..=max_players).collect();
+                self.player_count.0 = cycle(&counts, self.player_count.0, step);
+            }
+            Setting::ControlScheme => {
+                let schemes = [ControlScheme::Keyboard, ControlScheme::Mouse];
+                *self.control_scheme = cycle(&schemes, *self.control_scheme, step);
+            }
+            Setting::LivesMode => {
+                let modes = [LivesMode::Shared, LivesMode::Separate];
+                let mode = cycle(&modes, self.lives.mode, step);
+                *self.lives = Lives::new(mode, self.player_count.0);
+            }
+            Setting::Resolution => {
+                settings.resolution = cycle(&RESOLUTIONS, settings.resolution, step);
+            }
+            Setting::WindowMode => {
+                settings.window_mode = cycle(&WindowMode::ALL, settings.window_mode, step);
+            }
+            Setting::Vsync => settings.vsync = !settings.vsync,
+            Setting::Bloom => settings.post_effects.bloom = !settings.post_effects.bloom,
+            Setting::Crt => settings.post_effects.crt = !settings.post_effects.crt,
+            Setting::ChromaticAberration => {
+                let effects = &mut settings.post_effects;
+                effects.chromatic_aberration = !effects.chromatic_aberration;
+            }
+            Setting::ScreenShake => {
+                step_fraction(&mut settings.screen_shake, SCREEN_SHAKE_STEP, step)
+            }
+            Setting::MasterVolume => step_fraction(&mut settings.master_volume, VOLUME_STEP, step),
+            Setting::SfxVolume => step_fraction(&mut settings.sfx_volume, VOLUME_STEP, step),
+            Setting::MusicVolume => step_fraction(&mut settings.music_volume, VOLUME_STEP, step),
+        }
+    }
+
+    // Player one only, the key moves over from whatever it was bound to before
+    fn rebind(&mut self, action: Action, key: VirtualKeyCode) {
+        if let Some(keys) = self.bindings.players.first_mut() {
+            for bound in keys.values_mut() {
+                bound.retain(|bound_key| *bound_key != key);
+            }
+            keys.insert(action, vec![key]);
+            self.settings.bindings = Some(self.bindings.clone());
+        }
+    }
+
+    fn fill(&self, menu: &mut Menu, rebinding: Option<Action>) {
+        let settings = &*self.settings;
+        let change = |label: String, setting| MenuItem::new(label, MenuAction::Change(setting));
+        let on_off =
+            |label: &str, on: bool| format!("{}: {}", label, if on { "ON" } else { "OFF" });
+        let percent = |label: &str, value: f32| format!("{}: {}%", label, (value * 100.0).round());
+        let back = MenuItem::new("BACK", MenuAction::Back);
+
+        let (title, lines, items) = match menu.screen {
+            MenuScreen::Title => (
+                "UNSTABLE ORBIT",
+                vec![format!(
+                    "{} - {} {}",
+                    self.difficulty.preset.name(),
+                    self.player_count.0,
+                    if self.player_count.0 == 1 {
+                        "PLAYER"
+                    } else {
+                        "PLAYERS"
+                    }
+                )],
+                vec![
+                    MenuItem::new("START", MenuAction::Start),
+                    MenuItem::new("SETTINGS", MenuAction::Open(MenuScreen::Settings)),
+                    MenuItem::new("HIGH SCORES", MenuAction::Open(MenuScreen::HighScores)),
+                    MenuItem::new("QUIT", MenuAction::Quit),
+                ],
+            ),
+            MenuScreen::Settings => (
+                "SETTINGS",
+                Vec::new(),
+                vec![
+                    MenuItem::new("GAME", MenuAction::Open(MenuScreen::GameSettings)),
+                    MenuItem::new("VIDEO", MenuAction::Open(MenuScreen::VideoSettings)),
+                    MenuItem::new("AUDIO", MenuAction::Open(MenuScreen::AudioSettings)),
+                    MenuItem::new("CONTROLS", MenuAction::Open(MenuScreen::ControlSettings)),
+                    back,
+                ],
+            ),
+            MenuScreen::GameSettings => (
+                "GAME",
+                vec!["LEFT AND RIGHT CHANGE A SETTING".to_string()],
+                vec![
+                    change(
+                        format!("DIFFICULTY: {}", self.difficulty.preset.name()),
+                        Setting::Difficulty,
+                    ),
+                    change(
+                        format!("PLAYERS: {}", self.player_count.0),
+                        Setting::Players,
+                    ),
+                    change(
+                        match self.control_scheme {
+                            ControlScheme::Keyboard => "CONTROLS: KEYBOARD",
+                            ControlScheme::Mouse => "CONTROLS: MOUSE",
+                        }
+                        .to_string(),
+                        Setting::ControlScheme,
+                    ),
+                    change(
+                        match self.lives.mode {
+                            LivesMode::Shared => "LIVES: SHARED",
+                            LivesMode::Separate => "LIVES: SEPARATE",
+                        }
+                        .to_string(),
+                        Setting::LivesMode,
+                    ),
+                    back,
+                ],
+            ),
+            MenuScreen::VideoSettings => {
+                let (width, height) = settings.resolution;
+                let effects = &settings.post_effects;
+                (
+                    "VIDEO",
+                    vec!["LEFT AND RIGHT CHANGE A SETTING".to_string()],
+                    vec![
+                        change(
+                            format!("RESOLUTION: {} X {}", width, height),
+                            Setting::Resolution,
+                        ),
+                        change(
+                            format!("WINDOW: {}", settings.window_mode.name()),
+                            Setting::WindowMode,
+                        ),
+                        change(on_off("VSYNC", settings.vsync), Setting::Vsync),
+                        change(on_off("BLOOM", effects.bloom), Setting::Bloom),
+                        change(on_off("CRT", effects.crt), Setting::Crt),
+                        change(
+                            on_off("ABERRATION", effects.chromatic_aberration),
+                            Setting::ChromaticAberration,
+                        ),
+                        change(
+                            percent("SCREEN SHAKE", settings.screen_shake),
+                            Setting::ScreenShake,
+                        ),
+                        back,
+                    ],
+                )
+            }
+            MenuScreen::AudioSettings => (
+                "AUDIO",
+                vec!["LEFT AND RIGHT CHANGE A SETTING".to_string()],
+                vec![
+                    change(
+                        percent("MASTER", settings.master_volume),
+                        Setting::MasterVolume,
+                    ),
+                    change(percent("EFFECTS", settings.sfx_volume), Setting::SfxVolume),
+                    change(
+                        percent("MUSIC", settings.music_volume),
+                        Setting::MusicVolume,
+                    ),
+                    back,
+                ],
+            ),
+            MenuScreen::ControlSettings => {
+                let keys = self.bindings.players.first();
+                let mut items: Vec<MenuItem> = Action::ALL
+                    .iter()
+                    .map(|&action| {
+                        let bound = if rebinding == Some(action) {
+                            "PRESS A KEY".to_string()
+                        } else {
+                            let names: Vec<String> = keys
+                                .and_then(|keys| keys.get(&action))
+                                .into_iter()
+                                .flatten()
+                                .map(|key| format!("{:?}", key))
+                                .collect();
+                            if names.is_empty() {
+                                "-".to_string()
+                            } else {
+                                names.join(" / ")
+                            }
+                        };
+                        MenuItem::new(
+                            format!("{}: {}", action_name(action), bound),
+                            MenuAction::Rebind(action),
+                        )
+                    })
+                    .collect();
+                items.push(back);
+                (
+                    "CONTROLS",
+                    vec!["PLAYER ONE - ESCAPE CANCELS".to_string()],
+                    items,
+                )
+            }
+            MenuScreen::HighScores => {
+                let mut lines: Vec<String> = self
+                    .high_scores
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .map(|(rank, entry)| {
+                        format!(
+                            "{}. {} - {} {}P",
+                            rank + 1,
+                            entry.score,
+                            entry.preset.name(),
+                            entry.players
+                        )
+                    })
+                    .collect();
+                if lines.is_empty() {
+                    lines.push("NO RUNS YET".to_string());
+                }
+                ("HIGH SCORES", lines, vec![back])
+            }
+            MenuScreen::Pause => (
+                "PAUSED",
+                vec![format!("SCORE {}", self.score.points as u32)],
+                vec![
+                    MenuItem::new("RESUME", MenuAction::Resume),
+                    MenuItem::new("RESTART", MenuAction::Restart),
+                    MenuItem::new("QUIT", MenuAction::QuitRun),
+                ],
+            ),
+            MenuScreen::GameOver => {
+                let mut lines = vec![format!("SCORE {}", self.score.points as u32)];
+                match (self.high_scores.latest, self.high_scores.best()) {
+                    (Some(0), _) => lines.push("NEW HIGH SCORE".to_string()),
+                    (Some(rank), _) => lines.push(format!("RANK {}", rank + 1)),
+                    (None, Some(best)) => lines.push(format!("BEST {}", best)),
+                    (None, None) => (),
+                }
+                for source in DamageSource::ALL {
+                    let damage = self.stats.damage(source);
+                    if damage >= 1.0 {
+                        lines.push(format!("{} DAMAGE {}", source.name(), damage as u32));
+                    }
+                }
+                let packs: u32 = self.stats.packs_collected.values().sum();
+                lines.push(format!("PACKS COLLECTED {}", packs));
+                (
+                    "GAME OVER",
+                    lines,
+                    vec![
+                        MenuItem::new("RETRY", MenuAction::Retry),
+                        MenuItem::new("MENU", MenuAction::MainMenu),
+                    ],
+                )
+            }
+        };
+        menu.title = title.to_string();
+        menu.lines = lines.into_iter().map(|line| line.to_uppercase()).collect();
+        menu.items = items
+            .into_iter()
+            .map(|item| MenuItem {
+                label: item.label.to_uppercase(),
+                ..item
+            })
+            .collect();
+        menu.selected = menu.selected.min(menu.items.len().saturating_sub(1));
+    }
+}
+
+fn action_name(action: Action) -> &'static str {
+    match action {
+        Action::Thrust => "THRUST",
+        Action::Brake => "BRAKE",
+        Action::RotateLeft => "TURN LEFT",
+        Action::RotateRight => "TURN RIGHT",
+        Action::Fire => "FIRE",
+        Action::Confirm => "CONFIRM",
+        Action::Pause => "PAUSE",
+    }
+}
+
+// The value `step` places along, wrapping around. A value that isn't in the list counts as the
+// first one, an empty list leaves it as it is.
+fn cycle<T: Copy + PartialEq>(values: &[T], current: T, step: i32) -> T {
+    if values.is_empty() {
+        return current;
+    }
+    let idx = values
+        .iter()
+        .position(|value| *value == current)
+        .unwrap_or(0) as i32;
+    values[(idx + step).rem_euclid(values.len() as i32) as usize]
+}
+
+// Steps a value between 0 and 1, wrapping around at either end
+fn step_fraction(value: &mut f32, size: f32, step: i32) {
+    let steps = (1.0 / size).round() as i32;
+    let current = (*value / size).round() as i32;
+    *value = (current + step).rem_euclid(steps + 1) as f32 * size;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cycle, step_fraction};
+
+    #[test]
+    fn test_stepping_wraps() {
+        assert_eq!(cycle(&[1, 2, 3], 3, 1), 1);
+        assert_eq!(cycle(&[1, 2, 3], 1, -1), 3);
+        assert_eq!(cycle(&[1, 2, 3], 7, 1), 2);
+        assert_eq!(cycle(&[], 2, 1), 2);
+
+        let mut volume = 0.9;
+        step_fraction(&mut volume, 0.1, 1);
+        assert!((volume - 1.0).abs() < 1e-6);
+        step_fraction(&mut volume, 0.1, 1);
+        assert_eq!(volume, 0.0);
+        step_fraction(&mut volume, 0.25, -1);
+        assert!((volume - 1.0).abs() < 1e-6);
+    }
+}
@@ -1,44 +1,80 @@
 use super::{
     component::{Transform, Velocity},
-    resource::{DeltaTime, GameState, GameStateForRenderer},
+    resource::{BossPhase, BossStatus, DeltaTime, GameState, GameStateForRenderer},
 };
 use specs::prelude::*;
 
-mod asteroid_spawner_system;
+pub(crate) mod asteroid_spawner_system;
+mod audio_system;
+mod autopilot_system;
+mod boss_system;
+mod enemy_ai_system;
+mod enemy_spawner_system;
 mod entity_lifetime_system;
 mod entity_spinner_system;
 mod game_manager_system;
-mod particle_spawner_system;
+mod input_system;
+mod menu_system;
+mod particle_system;
+mod pickup_system;
 mod player_bounds_enforcer_system;
 mod player_collision_system;
 mod player_movement_system;
+mod player_weapon_system;
+pub(crate) mod projectile_system;
 mod render_system;
-mod repair_pack_manager_system;
+mod status_effect_system;
+mod wave_scheduler_system;
 
-pub use asteroid_spawner_system::AsteroidSpawnerSystem;
+// Systems keep what they remember between ticks in `*State` resources instead of on themselves,
+// so it goes along when the world gets saved
+pub use asteroid_spawner_system::{AsteroidSpawnerState, AsteroidSpawnerSystem};
+pub use audio_system::AudioSystem;
+pub use autopilot_system::AutopilotSystem;
+pub use boss_system::{BossState, BossSystem};
+pub use enemy_ai_system::EnemyAiSystem;
+pub use enemy_spawner_system::{EnemySpawnerState, EnemySpawnerSystem};
 pub use entity_lifetime_system::EntityLifetimeSystem;
 pub use entity_spinner_system::EntitySpinnerSystem;
 pub use game_manager_system::GameManagerSystem;
-pub use particle_spawner_system::ParticleSpawnerSystem;
+pub use input_system::InputSystem;
+pub use menu_system::MenuSystem;
+pub use particle_system::ParticleSystem;
+pub use pickup_system::{PickupState, PickupSystem};
 pub use player_bounds_enforcer_system::PlayerBoundsEnforcerSystem;
 pub use player_collision_system::PlayerCollisionSystem;
 pub use player_movement_system::PlayerMovementSystem;
+pub use player_weapon_system::PlayerWeaponSystem;
+pub use projectile_system::ProjectileSystem;
 pub use render_system::RenderSystem;
-pub use repair_pack_manager_system::RepairPackManagerSystem;
+pub use status_effect_system::StatusEffectSystem;
+pub use wave_scheduler_system::{WaveSchedulerState, WaveSchedulerSystem};
+
+// Speeds are in pixels per tick at the 60 ticks per second the game was tuned at
+const SPEED_TICK_RATE: f32 = 60.0;
 
 #[derive(Default)]
 pub struct VelocityApplicatorSystem {}
 
 impl<'a> System<'a> for VelocityApplicatorSystem {
-    type SystemData = (ReadStorage<'a, Velocity>, WriteStorage<'a, Transform>);
+    type SystemData = (
+        ReadStorage<'a, Velocity>,
+        WriteStorage<'a, Transform>,
+        Read<'a, DeltaTime>,
+    );
 
-    fn run(&mut self, (vel, mut pos): Self::SystemData) {
+    fn run(&mut self, (vel, mut pos, dt): Self::SystemData) {
+        // DeltaTime already has the time scale applied
+        let ticks = dt.0.as_secs_f32() * SPEED_TICK_RATE;
         for (velocity, transform) in (&vel, &mut pos).join() {
-            transform.position += velocity.direction * velocity.speed;
+            transform.position += velocity.direction * velocity.speed * ticks;
         }
     }
 }
 
+const PLAY_BACKGROUND_FRAMES: (u32, u32) = (2, 4);
+const FLARE_BACKGROUND_FRAMES: (u32, u32) = (5, 7);
+
 pub struct BackgroundAnimatorSystem {
     anim_speed: f32,
     anim_clock: f32,
@@ -58,9 +94,10 @@ impl<'a> System<'a> for BackgroundAnimatorSystem {
         Read<'a, GameState>,
         Write<'a, GameStateForRenderer>,
         Read<'a, DeltaTime>,
+        Read<'a, BossStatus>,
     );
 
-    fn run(&mut self, (game_state, mut game_state_renderer, dt): Self::SystemData) {
+    fn run(&mut self, (game_state, mut game_state_renderer, dt, boss_status): Self::SystemData) {
         match *game_state {
             GameState::GameStateInit {} => {
                 game_state_renderer.background_idx = 0;
@@ -69,12 +106,18 @@ impl<'a> System<'a> for BackgroundAnimatorSystem {
                 self.anim_clock -= dt.0.as_secs_f32();
 
                 if self.anim_clock < 0.0 {
+                    // The star flares up for as long as the boss is around
+                    let (first_frame, last_frame) = match boss_status.phase {
+                        BossPhase::Dormant => PLAY_BACKGROUND_FRAMES,
+                        BossPhase::Flare | BossPhase::Fight => FLARE_BACKGROUND_FRAMES,
+                    };
+
                     game_state_renderer.background_idx += 1;
 
-                    if game_state_renderer.background_idx < 2
-                        || game_state_renderer.background_idx > 4
+                    if game_state_renderer.background_idx < first_frame
+                        || game_state_renderer.background_idx > last_frame
                     {
-                        game_state_renderer.background_idx = 2;
+                        game_state_renderer.background_idx = first_frame;
                     }
 
                     self.anim_clock = self.anim_speed
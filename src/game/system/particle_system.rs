@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use specs::prelude::*;
+
+use crate::game::{
+    component::{Emitter, Transform},
+    particles::{EmitterId, EmitterSettings, ParticleBursts, ParticleField, ParticlePresets},
+    resource::{DeltaTime, GameState, GameWindowSize},
+};
+
+// Stars drift down at the speeds the old starfield entities had
+const STAR_SPEED: (f32, f32) = (300.0, 600.0);
+const STARS_PER_SECOND: f32 = 80.0;
+
+// Runs the particle field: the starfield while a run is on, emitters attached to entities and
+// one-off bursts. Particles are not saved, so the field emitters are tracked on the system rather
+// than in a state resource.
+#[derive(Default)]
+pub struct ParticleSystem {
+    starfield: Option<EmitterId>,
+    // Field emitter behind each entity's Emitter, with the preset it was made from
+    attached: HashMap<Entity, (EmitterId, String)>,
+}
+
+impl<'a> System<'a> for ParticleSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Emitter>,
+        ReadStorage<'a, Transform>,
+        Read<'a, GameState>,
+        Read<'a, DeltaTime>,
+        Read<'a, GameWindowSize>,
+        Read<'a, ParticlePresets>,
+        Write<'a, ParticleBursts>,
+        Write<'a, ParticleField>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            emitter_storage,
+            transform_storage,
+            game_state,
+            dt,
+            game_window_size,
+            presets,
+            mut bursts,
+            mut particles,
+        ): Self::SystemData,
+    ) {
+        let starfield = *self
+            .starfield
+            .get_or_insert_with(|| particles.add_emitter(starfield_settings(), glam::Vec2::ZERO));
+        if let Some(emitter) = particles.emitter_mut(starfield) {
+            let (width, height) = (game_window_size.0 as f32, game_window_size.1 as f32);
+            // Just above the top edge, across the whole window, living long enough to cross it
+            emitter.position = glam::vec2(0.0, height / 2.0 + 4.0);
+            emitter.settings.area = glam::vec2(width / 2.0, 0.0);
+            let crossing_time = (height + 8.0) / STAR_SPEED.0;
+            emitter.settings.lifetime = (crossing_time, crossing_time);
+            emitter.enabled = matches!(*game_state, GameState::GameStatePlay { .. });
+        }
+
+        for (entity, emitter, transform) in (&entities, &emitter_storage, &transform_storage).join()
+        {
+            let settings = match presets.get(&emitter.preset) {
+                Some(settings) => settings,
+                None => continue,
+            };
+            if let Some((id, preset)) = self.attached.get(&entity) {
+                if *preset != emitter.preset {
+                    particles.remove_emitter(*id);
+                    self.attached.remove(&entity);
+                }
+            }
+            let (id, _) = self.attached.entry(entity).or_insert_with(|| {
+                (
+                    particles.add_emitter(settings.clone(), transform.position),
+                    emitter.preset.clone(),
+                )
+            });
+
+            if let Some(field_emitter) = particles.emitter_mut(*id) {
+                // Offset and direction turn with the entity
+                let offset = emitter.offset * transform.scale;
+                let offset = transform.rotation * glam::vec3(offset.x, offset.y, 0.0);
+                let direction = transform.rotation
+                    * glam::vec3(settings.direction.x, settings.direction.y, 0.0);
+                field_emitter.position = transform.position + glam::vec2(offset.x, offset.y);
+                field_emitter.settings.direction = glam::vec2(direction.x, direction.y);
+                field_emitter.enabled = emitter.enabled;
+            }
+        }
+        // Whatever was emitting for entities that are gone stops, its particles play out
+        self.attached.retain(|entity, (id, _)| {
+            let attached = entities.is_alive(*entity) && emitter_storage.contains(*entity);
+            if !attached {
+                particles.remove_emitter(*id);
+            }
+            attached
+        });
+
+        for (preset, position) in bursts.0.drain(..) {
+            if let Some(settings) = presets.get(preset) {
+                particles.burst(settings, position, settings.burst);
+            }
+        }
+
+        particles.update(dt.0.as_secs_f32());
+    }
+}
+
+fn starfield_settings() -> EmitterSettings {
+    EmitterSettings {
+        sprite_idx: 2,
+        rate: STARS_PER_SECOND,
+        speed: STAR_SPEED,
+        direction: glam::vec2(0.0, -1.0),
+        spread: 0.0,
+        color: ([1.0; 4], [1.0; 4]),
+        size: (2.0, 2.0),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,224 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use specs::{prelude::*, shrev::EventChannel};
+
+use crate::{
+    audio::{Sound, SoundEvent},
+    game::{
+        component::{
+            Collider, ColliderTag, Display, Lifetime, Pickup, PickupKind, Player, StatusEffectKind,
+            StatusEffects, Transform,
+        },
+        particles::{ParticleBursts, REPAIR_EFFECT},
+        resource::{
+            DeltaTime, GameRng, GameState, GameWindowSize, PickupSpawnEntry, PickupSpawnTable,
+            RunStats, Score,
+        },
+    },
+};
+
+const MAX_PLAYER_HEALTH: f32 = 100.0;
+const MAX_WEAPON_LEVEL: u32 = 3;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PickupState {
+    pub spawn_clock: f32,
+}
+
+pub struct PickupSystem {
+    spawn_timeout: f32,
+}
+
+impl Default for PickupSystem {
+    fn default() -> Self {
+        Self { spawn_timeout: 5.0 }
+    }
+}
+
+impl<'a> System<'a> for PickupSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Player>,
+        WriteStorage<'a, StatusEffects>,
+        ReadStorage<'a, Pickup>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Collider>,
+        Read<'a, LazyUpdate>,
+        Read<'a, GameState>,
+        Read<'a, DeltaTime>,
+        Read<'a, GameWindowSize>,
+        Read<'a, PickupSpawnTable>,
+        Write<'a, Score>,
+        Write<'a, GameRng>,
+        Write<'a, RunStats>,
+        Write<'a, PickupState>,
+        Write<'a, ParticleBursts>,
+        Write<'a, EventChannel<SoundEvent>>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut player_storage,
+            mut status_effects_storage,
+            pickup_storage,
+            transform_storage,
+            collider_storage,
+            updater,
+            game_state,
+            dt,
+            game_window_size,
+            spawn_table,
+            mut score,
+            mut game_rng,
+            mut stats,
+            mut state,
+            mut bursts,
+            mut sounds,
+        ): Self::SystemData,
+    ) {
+        match *game_state {
+            GameState::GameStatePlay { .. } => {
+                state.spawn_clock -= dt.0.as_secs_f32();
+
+                if state.spawn_clock < 0.0 {
+                    // Uncollected pickups disappear by the time the next one shows up
+                    let rng = &mut game_rng.0;
+                    if let Some(entry) = spawn_table.pick(rng) {
+                        let position = Self::random_position(&game_window_size, rng);
+                        Self::spawn_pickup(
+                            entry,
+                            position,
+                            self.spawn_timeout,
+                            &entities,
+                            &updater,
+                        );
+                    }
+                    state.spawn_clock = self.spawn_timeout
+                }
+
+                for (pickup_entity, pickup, pickup_transform, pickup_collider) in (
+                    &entities,
+                    &pickup_storage,
+                    &transform_storage,
+                    &collider_storage,
+                )
+                    .join()
+                {
+                    for (player, status_effects, player_transform, player_collider) in (
+                        &mut player_storage,
+                        &mut status_effects_storage,
+                        &transform_storage,
+                        &collider_storage,
+                    )
+                        .join()
+                    {
+                        if player_collider.intersects(
+                            player_transform,
+                            pickup_collider,
+                            pickup_transform,
+                        ) {
+                            apply_pickup(pickup, player, status_effects, &mut score);
+                            stats.add_pack(pickup.kind);
+                            if pickup.kind == PickupKind::Repair {
+                                bursts.push(REPAIR_EFFECT, pickup_transform.position);
+                                sounds.single_write(SoundEvent::Play(
+                                    Sound::RepairPickup,
+                                    Some(pickup_transform.position),
+                                ));
+                            }
+                            entities.delete(pickup_entity).unwrap();
+                            // First player to touch it takes it
+                            break;
+                        }
+                    }
+                }
+            }
+            GameState::GameStateEnd {} => {
+                for (pickup_entity, _) in (&entities, &pickup_storage).join() {
+                    entities.delete(pickup_entity).unwrap();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl PickupSystem {
+    pub fn random_position<R: Rng>(game_window_size: &GameWindowSize, rng: &mut R) -> glam::Vec2 {
+        glam::vec2(
+            rng.gen_range(-(game_window_size.0 as f32 / 3.0)..(game_window_size.0 as f32 / 3.0)),
+            rng.gen_range(-(game_window_size.1 as f32 / 3.0)..(game_window_size.1 as f32 / 3.0)),
+        )
+    }
+
+    pub fn spawn_pickup<'a>(
+        entry: &PickupSpawnEntry,
+        position: glam::Vec2,
+        lifetime: f32,
+        entities: &Entities,
+        updater: &Read<'a, LazyUpdate>,
+    ) -> Entity {
+        let pickup = entities.create();
+
+        updater.insert(
+            pickup,
+            Transform {
+                position,
+                scale: glam::vec2(40.0, 40.0),
+                ..Default::default()
+            },
+        );
+        updater.insert(pickup, Display::new(entry.sprite_idx));
+        updater.insert(pickup, Collider::new(ColliderTag::Pickup));
+        updater.insert(
+            pickup,
+            Pickup {
+                kind: entry.kind,
+                effect: entry.effect,
+            },
+        );
+        updater.insert(
+            pickup,
+            Lifetime {
+                remaining: lifetime,
+            },
+        );
+
+        pickup
+    }
+}
+
+fn apply_pickup(
+    pickup: &Pickup,
+    player: &mut Player,
+    status_effects: &mut StatusEffects,
+    score: &mut Score,
+) {
+    match pickup.kind {
+        PickupKind::Repair => {
+            player.health = (player.health + pickup.effect).min(MAX_PLAYER_HEALTH);
+        }
+        PickupKind::Shield => {
+            status_effects.apply(StatusEffectKind::Shield, pickup.effect);
+        }
+        PickupKind::SpeedBoost => {
+            status_effects.apply(StatusEffectKind::SpeedBoost, pickup.effect);
+        }
+        PickupKind::SlowMo => {
+            status_effects.apply(StatusEffectKind::SlowMo, pickup.effect);
+        }
+        PickupKind::WeaponUpgrade => {
+            player.weapon_level =
+                (player.weapon_level + pickup.effect as u32).min(MAX_WEAPON_LEVEL);
+        }
+        PickupKind::ScoreMultiplier => {
+            score.multiplier += pickup.effect;
+        }
+    }
+}
@@ -1,56 +1,125 @@
-use specs::prelude::*;
+use specs::{prelude::*, shrev::EventChannel};
 
-use crate::game::{
-    component::{Collider, ColliderTag, Player, Transform},
-    resource::DeltaTime,
+use crate::{
+    audio::{Sound, SoundEvent},
+    game::{
+        component::{
+            Collider, ColliderTag, Enemy, Player, Projectile, StatusEffectKind, StatusEffects,
+            Transform,
+        },
+        particles::{ParticleBursts, ASTEROID_HIT_EFFECT},
+        resource::{DamageSource, DeltaTime, RunStats},
+    },
 };
 
+const ASTEROID_CONTACT_DAMAGE: f32 = 50.0;
+const BOSS_CONTACT_DAMAGE: f32 = 40.0;
+
 #[derive(Default)]
 pub struct PlayerCollisionSystem;
 
 impl<'a> System<'a> for PlayerCollisionSystem {
     type SystemData = (
+        Entities<'a>,
         WriteStorage<'a, Player>,
+        ReadStorage<'a, StatusEffects>,
         ReadStorage<'a, Transform>,
         ReadStorage<'a, Collider>,
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, Projectile>,
         Read<'a, DeltaTime>,
+        Write<'a, RunStats>,
+        Write<'a, ParticleBursts>,
+        Write<'a, EventChannel<SoundEvent>>,
     );
 
     fn setup(&mut self, world: &mut World) {
         Self::SystemData::setup(world);
     }
 
-    fn run(&mut self, (mut player, tf, coll, dt): Self::SystemData) {
-        for (player_comp, player_transform, player_collider) in (&mut player, &tf, &coll).join() {
-            for (other_transform, other_collider) in (&tf, &coll).join() {
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut player,
+            status,
+            tf,
+            coll,
+            enemy,
+            projectile,
+            dt,
+            mut stats,
+            mut bursts,
+            mut sounds,
+        ): Self::SystemData,
+    ) {
+        for (player_comp, player_status, player_transform, player_collider) in
+            (&mut player, (&status).maybe(), &tf, &coll).join()
+        {
+            let shielded = player_status.is_some_and(|effects| {
+                effects.has(StatusEffectKind::Shield) || effects.has(StatusEffectKind::Invulnerable)
+            });
+
+            let mut damage = |source: DamageSource, amount: f32| {
+                player_comp.health -= amount;
+                stats.add_damage(source, amount);
+            };
+
+            for (other_entity, other_transform, other_collider) in (&entities, &tf, &coll).join() {
                 if player_collider == other_collider {
                     // Skip self
                     continue;
                 }
 
                 // Unholy, probably want to cache it with flagged storage
-                if player_collider
-                    .bounding_box
-                    .translate(player_transform.position)
-                    .scale(player_transform.scale)
-                    .intersect(
-                        &other_collider
-                            .bounding_box
-                            .translate(other_transform.position)
-                            .scale(other_transform.scale),
-                    )
-                {
+                if player_collider.intersects(player_transform, other_collider, other_transform) {
                     match other_collider.tag {
-                        ColliderTag::Player => (),
                         ColliderTag::Asteroid => {
-                            player_comp.health -= 50.0 * dt.0.as_secs_f32();
+                            // Chips fly off where they touch, shield or not
+                            let contact = player_transform
+                                .position
+                                .lerp(other_transform.position, 0.5);
+                            bursts.push(ASTEROID_HIT_EFFECT, contact);
+                            sounds.single_write(SoundEvent::Play(
+                                Sound::AsteroidImpact,
+                                Some(contact),
+                            ));
+                            if !shielded {
+                                damage(
+                                    DamageSource::Asteroid,
+                                    ASTEROID_CONTACT_DAMAGE * dt.0.as_secs_f32(),
+                                );
+                            }
+                        }
+                        ColliderTag::Enemy => {
+                            if let (false, Some(enemy_comp)) = (shielded, enemy.get(other_entity)) {
+                                damage(
+                                    DamageSource::Enemy,
+                                    enemy_comp.variant.contact_damage * dt.0.as_secs_f32(),
+                                );
+                            }
+                        }
+                        ColliderTag::Projectile => {
+                            // Shots are used up on contact, the shield just eats the damage
+                            if let (false, Some(projectile_comp)) =
+                                (shielded, projectile.get(other_entity))
+                            {
+                                damage(DamageSource::Projectile, projectile_comp.damage);
+                            }
+                            entities.delete(other_entity).unwrap();
                         }
-                        ColliderTag::Health => {
-                            player_comp.health += 30.0;
-                            if player_comp.health > 100.0 {
-                                player_comp.health = 100.0;
+                        ColliderTag::Boss => {
+                            if !shielded {
+                                damage(
+                                    DamageSource::Boss,
+                                    BOSS_CONTACT_DAMAGE * dt.0.as_secs_f32(),
+                                );
                             }
                         }
+                        // Pickups are resolved by the pickup system, own shots by the projectile system
+                        ColliderTag::Player
+                        | ColliderTag::Pickup
+                        | ColliderTag::PlayerProjectile => (),
                     }
                 }
             }
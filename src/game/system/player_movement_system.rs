@@ -1,112 +1,153 @@
 use glam::EulerRot;
-use specs::{prelude::*, shred::DefaultProvider, shrev::EventChannel};
-use winit::event::VirtualKeyCode;
+use specs::{prelude::*, shrev::EventChannel};
 
 use crate::{
+    audio::{Sound, SoundEvent},
     game::component::{Transform, Velocity},
     game::{
-        component::Player,
-        resource::{DeltaTime, KeyboardEvent},
+        component::{Emitter, Player, StatusEffectKind, StatusEffects},
+        input::{Action, ActionStates, MOUSE_SLOT},
+        resource::{ControlScheme, Cursor, DeltaTime},
     },
 };
 
 const MAX_PLAYER_SPEED: f32 = 10.0;
+const BOOSTED_MAX_PLAYER_SPEED: f32 = 15.0;
 
 const PLAYER_ACCELERATION: f32 = 2.5;
 const PLAYER_ROTATION_SPEED: f32 = 5.0;
 const PLAYER_MANEUVER_SPEED: f32 = 1.0;
 
 #[derive(Default)]
-pub struct PlayerMovementSystem {
-    reader: Option<ReaderId<KeyboardEvent>>,
-    rotation_factor: i8,
-    acceleration_factor: i8,
-}
+pub struct PlayerMovementSystem {}
 
 impl<'a> System<'a> for PlayerMovementSystem {
     type SystemData = (
+        Entities<'a>,
         ReadStorage<'a, Player>,
+        ReadStorage<'a, StatusEffects>,
         WriteStorage<'a, Transform>,
         WriteStorage<'a, Velocity>,
-        Read<'a, EventChannel<KeyboardEvent>>,
+        WriteStorage<'a, Emitter>,
+        Read<'a, ActionStates>,
+        Read<'a, ControlScheme>,
+        Read<'a, Cursor>,
         Read<'a, DeltaTime>,
+        Write<'a, EventChannel<SoundEvent>>,
     );
 
     fn setup(&mut self, world: &mut World) {
         Self::SystemData::setup(world);
-        self.reader = Some(
-            world
-                .fetch_mut::<EventChannel<KeyboardEvent>>()
-                .register_reader(),
-        );
     }
 
-    fn run(&mut self, (player, mut pos, mut vel, events, dt): Self::SystemData) {
-        handle_inputs(
-            events,
-            &mut self.reader.as_mut().unwrap(),
-            &mut self.rotation_factor,
-            &mut self.acceleration_factor,
-        );
-
+    fn run(
+        &mut self,
+        (
+            entities,
+            player,
+            status,
+            mut pos,
+            mut vel,
+            mut emitter,
+            actions,
+            control_scheme,
+            cursor,
+            dt,
+            mut sounds,
+        ): Self::SystemData,
+    ) {
         let delta = dt.0.as_secs_f32();
 
-        for (_, transform, velocity) in (&player, &mut pos, &mut vel).join() {
-            if self.acceleration_factor != 0 {
-                velocity.speed += PLAYER_ACCELERATION * delta * f32::from(self.acceleration_factor);
+        for (entity, player_comp, player_status, transform, velocity, exhaust) in (
+            &entities,
+            &player,
+            (&status).maybe(),
+            &mut pos,
+            &mut vel,
+            (&mut emitter).maybe(),
+        )
+            .join()
+        {
+            let (rotation_factor, acceleration_factor) = read_actions(&actions, player_comp.slot);
+            let mouse_steered =
+                *control_scheme == ControlScheme::Mouse && player_comp.slot == MOUSE_SLOT;
+            let boosted =
+                player_status.is_some_and(|effects| effects.has(StatusEffectKind::SpeedBoost));
+            let max_speed = if boosted {
+                BOOSTED_MAX_PLAYER_SPEED
+            } else {
+                MAX_PLAYER_SPEED
+            };
+
+            if acceleration_factor != 0 {
+                velocity.speed += PLAYER_ACCELERATION * delta * f32::from(acceleration_factor);
                 // clamp velocity
-                velocity.speed = velocity.speed.clamp(0.0, MAX_PLAYER_SPEED);
+                velocity.speed = velocity.speed.clamp(0.0, max_speed);
+            } else if velocity.speed > max_speed {
+                // Boost ran out, bleed off the extra speed
+                velocity.speed = max_speed;
+            }
 
-                if self.acceleration_factor > 0 {
-                    // Make velocity direction vector more like facing direction vector if we're accelerating
-                    velocity.direction = velocity
-                        .direction
-                        .lerp(transform.get_facing_vector(), PLAYER_MANEUVER_SPEED * delta);
-                }
+            if let Some(exhaust) = exhaust {
+                exhaust.enabled = acceleration_factor > 0;
+            }
+            if acceleration_factor > 0 {
+                sounds.single_write(SoundEvent::Hold(
+                    Sound::Thrust,
+                    entity.id(),
+                    transform.position,
+                ));
             }
-            if self.rotation_factor != 0 {
+
+            if acceleration_factor > 0 {
+                // Make velocity direction vector more like facing direction vector if we're accelerating
+                velocity.direction = velocity
+                    .direction
+                    .lerp(transform.get_facing_vector(), PLAYER_MANEUVER_SPEED * delta);
+            }
+            let rotation = if mouse_steered {
+                cursor.0.map_or(0.0, |target| {
+                    turn_towards(transform, target, PLAYER_ROTATION_SPEED * delta)
+                })
+            } else {
+                PLAYER_ROTATION_SPEED * delta * f32::from(rotation_factor)
+            };
+            if rotation != 0.0 {
                 let (_, _, mut angle) = transform.rotation.to_euler(EulerRot::XYZ);
-                angle += PLAYER_ROTATION_SPEED * delta * f32::from(self.rotation_factor);
+                angle += rotation;
                 transform.rotation = glam::Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, angle);
             }
         }
     }
 }
 
-fn handle_inputs(
-    events: Read<EventChannel<KeyboardEvent>, DefaultProvider>,
-    reader: &mut ReaderId<KeyboardEvent>,
-    rotation_factor: &mut i8,
-    acceleration_factor: &mut i8,
-) {
-    // Smells like it belongs somewhere else
-    for event in events.read(reader) {
-        match event {
-            &KeyboardEvent::Pressed(VirtualKeyCode::W) => {
-                *acceleration_factor = 1;
-            }
-            &KeyboardEvent::Released(VirtualKeyCode::W) => {
-                *acceleration_factor = 0;
-            }
-            &KeyboardEvent::Pressed(VirtualKeyCode::S) => {
-                *acceleration_factor = -2;
-            }
-            &KeyboardEvent::Released(VirtualKeyCode::S) => {
-                *acceleration_factor = 0;
-            }
-            &KeyboardEvent::Pressed(VirtualKeyCode::D) => {
-                *rotation_factor = -1;
-            }
-            &KeyboardEvent::Released(VirtualKeyCode::D) => {
-                *rotation_factor = 0;
-            }
-            &KeyboardEvent::Pressed(VirtualKeyCode::A) => {
-                *rotation_factor = 1;
-            }
-            &KeyboardEvent::Released(VirtualKeyCode::A) => {
-                *rotation_factor = 0;
-            }
-            _ => (),
-        }
+// Braking wins over thrust and opposite turns cancel out
+fn read_actions(actions: &ActionStates, slot: usize) -> (i8, i8) {
+    let mut rotation_factor = 0;
+    if actions.pressed(slot, Action::RotateLeft) {
+        rotation_factor += 1;
+    }
+    if actions.pressed(slot, Action::RotateRight) {
+        rotation_factor -= 1;
+    }
+
+    let acceleration_factor = if actions.pressed(slot, Action::Brake) {
+        -2
+    } else if actions.pressed(slot, Action::Thrust) {
+        1
+    } else {
+        0
+    };
+
+    (rotation_factor, acceleration_factor)
+}
+
+// Signed rotation that brings the facing closer to the target, at most `max_step`
+fn turn_towards(transform: &Transform, target: glam::Vec2, max_step: f32) -> f32 {
+    let to_target = target - transform.position;
+    if to_target.length_squared() < f32::EPSILON {
+        return 0.0;
     }
+    let angle = transform.get_facing_vector().angle_between(to_target);
+    angle.clamp(-max_step, max_step)
 }
@@ -0,0 +1,66 @@
+use specs::prelude::*;
+
+use crate::game::{
+    component::{ColliderTag, Player, Transform},
+    input::{Action, ActionStates},
+    resource::DeltaTime,
+    system::projectile_system::{ProjectileSpawn, ProjectileSystem},
+};
+
+const FIRE_COOLDOWN: f32 = 0.25;
+const PROJECTILE_SPEED: f32 = 12.0;
+const PROJECTILE_DAMAGE: f32 = 10.0;
+const PROJECTILE_SIZE: f32 = 8.0;
+// Angle between shots once upgrades turn the gun into a spread
+const SPREAD_ANGLE: f32 = 0.15;
+
+#[derive(Default)]
+pub struct PlayerWeaponSystem {}
+
+impl<'a> System<'a> for PlayerWeaponSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Player>,
+        ReadStorage<'a, Transform>,
+        Read<'a, LazyUpdate>,
+        Read<'a, ActionStates>,
+        Read<'a, DeltaTime>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+    }
+
+    fn run(&mut self, (entities, mut player, pos, updater, actions, dt): Self::SystemData) {
+        let delta = dt.0.as_secs_f32();
+
+        for (player_comp, transform) in (&mut player, &pos).join() {
+            player_comp.fire_cooldown -= delta;
+            if !actions.pressed(player_comp.slot, Action::Fire) || player_comp.fire_cooldown > 0.0 {
+                continue;
+            }
+            player_comp.fire_cooldown = FIRE_COOLDOWN;
+
+            let shots = player_comp.weapon_level + 1;
+            let facing = transform.get_facing_vector();
+
+            for shot in 0..shots {
+                let angle = (shot as f32 - (shots - 1) as f32 / 2.0) * SPREAD_ANGLE;
+                let direction = glam::Mat2::from_angle(angle) * facing;
+
+                ProjectileSystem::spawn_projectile(
+                    ProjectileSpawn {
+                        position: transform.position + facing * transform.scale.max_element(),
+                        direction,
+                        speed: PROJECTILE_SPEED,
+                        size: PROJECTILE_SIZE,
+                        damage: PROJECTILE_DAMAGE,
+                        tag: ColliderTag::PlayerProjectile,
+                    },
+                    &entities,
+                    &updater,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use specs::prelude::*;
+
+use crate::game::{
+    component::{
+        Boss, BossPart, Collider, ColliderTag, Display, Enemy, Lifetime, Projectile, Transform,
+        Velocity,
+    },
+    resource::GameState,
+};
+
+const PROJECTILE_LIFETIME: f32 = 5.0;
+
+pub struct ProjectileSpawn {
+    pub position: glam::Vec2,
+    pub direction: glam::Vec2,
+    pub speed: f32,
+    pub size: f32,
+    pub damage: f32,
+    pub tag: ColliderTag,
+}
+
+// Resolves player shots against everything they can hurt.
+// Hits on the player itself are handled by the player collision system.
+#[derive(Default)]
+pub struct ProjectileSystem {}
+
+impl<'a> System<'a> for ProjectileSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Projectile>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Collider>,
+        WriteStorage<'a, Enemy>,
+        WriteStorage<'a, Boss>,
+        WriteStorage<'a, BossPart>,
+        Read<'a, GameState>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            projectile_storage,
+            transform_storage,
+            collider_storage,
+            mut enemy_storage,
+            mut boss_storage,
+            mut boss_part_storage,
+            game_state,
+        ): Self::SystemData,
+    ) {
+        if let GameState::GameStateEnd {} = *game_state {
+            for (entity, _) in (&entities, &projectile_storage).join() {
+                entities.delete(entity).unwrap();
+            }
+            return;
+        }
+
+        for (projectile_entity, projectile, projectile_transform, projectile_collider) in (
+            &entities,
+            &projectile_storage,
+            &transform_storage,
+            &collider_storage,
+        )
+            .join()
+        {
+            if projectile_collider.tag != ColliderTag::PlayerProjectile {
+                continue;
+            }
+
+            for (target_entity, target_transform, target_collider) in
+                (&entities, &transform_storage, &collider_storage).join()
+            {
+                if !matches!(target_collider.tag, ColliderTag::Enemy | ColliderTag::Boss)
+                    || !projectile_collider.intersects(
+                        projectile_transform,
+                        target_collider,
+                        target_transform,
+                    )
+                {
+                    continue;
+                }
+
+                if let Some(enemy) = enemy_storage.get_mut(target_entity) {
+                    enemy.health -= projectile.damage;
+                } else if let Some(part) = boss_part_storage.get_mut(target_entity) {
+                    part.health -= projectile.damage;
+                } else if let Some(boss) = boss_storage.get_mut(target_entity) {
+                    // The core is shielded for as long as any of its parts are around
+                    let shielded = (&boss_part_storage)
+                        .join()
+                        .any(|part| part.core == target_entity && part.health > 0.0);
+                    if !shielded {
+                        boss.health -= projectile.damage;
+                    }
+                }
+
+                entities.delete(projectile_entity).unwrap();
+                break;
+            }
+        }
+    }
+}
+
+impl ProjectileSystem {
+    pub fn spawn_projectile<'a>(
+        spawn: ProjectileSpawn,
+        entities: &Entities,
+        updater: &Read<'a, LazyUpdate>,
+    ) -> Entity {
+        let projectile = entities.create();
+
+        updater.insert(
+            projectile,
+            Transform {
+                position: spawn.position,
+                scale: glam::vec2(spawn.size, spawn.size),
+                ..Default::default()
+            },
+        );
+        updater.insert(
+            projectile,
+            Velocity {
+                direction: spawn.direction,
+                speed: spawn.speed,
+            },
+        );
+        updater.insert(projectile, Display::new(2));
+        updater.insert(projectile, Collider::new(spawn.tag));
+        updater.insert(
+            projectile,
+            Projectile {
+                damage: spawn.damage,
+            },
+        );
+        updater.insert(
+            projectile,
+            Lifetime {
+                remaining: PROJECTILE_LIFETIME,
+            },
+        );
+
+        projectile
+    }
+}
@@ -1,23 +1,41 @@
+use std::time::Instant;
+
 use specs::{prelude::*, shrev::EventChannel};
 use winit::dpi::PhysicalSize;
 
 use crate::{
-    game::component::{Display, Transform},
-    game::resource::{GameStateForRenderer, GameWindowSize, WindowEvent},
-    renderer::Renderer,
+    game::component::{Display, StatusEffectKind, StatusEffects, Transform},
+    game::hud,
+    game::menu::{self, ActiveMenu},
+    game::particles::ParticleField,
+    game::resource::{GameStateForRenderer, GameWindowSize, ScreenshotRequest, WindowEvent},
+    renderer::{FrameMetrics, SpriteRenderer, STATUS_FLAG_SLOW_MO},
+    settings::Settings,
 };
 
+// Blinks per second while invulnerable
+const INVULNERABLE_BLINK_RATE: f32 = 10.0;
+// Sprite offset in pixels at full damage flash and full screen shake
+const MAX_SCREEN_SHAKE: f32 = 12.0;
+// Radians the shake direction turns each frame
+const SCREEN_SHAKE_SPIN: f32 = 2.4;
+
 #[derive(Default)]
 pub struct RenderSystem {
-    renderer: Option<Renderer>,
+    renderer: Option<Box<dyn SpriteRenderer>>,
     reader: Option<ReaderId<WindowEvent>>,
+    // Last one given to the renderer, changing it reconfigures the surface
+    vsync: Option<bool>,
+    frame: u32,
 }
 
 impl RenderSystem {
-    pub fn new(renderer: Renderer) -> Self {
+    pub fn new(renderer: impl SpriteRenderer + 'static) -> Self {
         Self {
-            renderer: Some(renderer),
+            renderer: Some(Box::new(renderer)),
             reader: None,
+            vsync: None,
+            frame: 0,
         }
     }
 }
@@ -26,9 +44,15 @@ impl<'a> System<'a> for RenderSystem {
     type SystemData = (
         ReadStorage<'a, Transform>,
         ReadStorage<'a, Display>,
+        ReadStorage<'a, StatusEffects>,
         Read<'a, EventChannel<WindowEvent>>,
         Read<'a, GameStateForRenderer>,
         Write<'a, GameWindowSize>,
+        Write<'a, ScreenshotRequest>,
+        Write<'a, FrameMetrics>,
+        Read<'a, ParticleField>,
+        Read<'a, Settings>,
+        Read<'a, ActiveMenu>,
     );
 
     fn setup(&mut self, world: &mut World) {
@@ -40,7 +64,23 @@ impl<'a> System<'a> for RenderSystem {
         );
     }
 
-    fn run(&mut self, (pos, disp, events, game_state, mut game_window_size): Self::SystemData) {
+    fn run(
+        &mut self,
+        (
+            pos,
+            disp,
+            status,
+            events,
+            game_state,
+            mut game_window_size,
+            mut screenshot,
+            mut frame_metrics,
+            particles,
+            settings,
+            active_menu,
+        ): Self::SystemData,
+    ) {
+        let frame_start = Instant::now();
         if let Some(renderer) = &mut self.renderer {
             // Process events
             for event in events.read(&mut self.reader.as_mut().unwrap()) {
@@ -54,12 +94,70 @@ impl<'a> System<'a> for RenderSystem {
                 }
             }
             // Render stuff
-            for (position, display) in (&pos, &disp).join() {
-                renderer.add_sprite_instance(display.sprite_idx, position.to_model_mat());
+            let mut instance_count = particles.len();
+            for (sprite_idx, model_matrix, tint) in particles.instances() {
+                renderer.add_particle_instance(sprite_idx, model_matrix, tint);
+            }
+            for (position, display, effects) in (&pos, &disp, (&status).maybe()).join() {
+                let hidden = effects
+                    .and_then(|effects| effects.remaining(StatusEffectKind::Invulnerable))
+                    .is_some_and(|remaining| {
+                        (remaining * INVULNERABLE_BLINK_RATE * 2.0) as u32 % 2 == 1
+                    });
+                if hidden {
+                    continue;
+                }
+
+                renderer.add_sprite_instance(
+                    display.sprite_idx,
+                    position.to_model_mat(),
+                    display.tint,
+                );
+                instance_count += 1;
+            }
+
+            let window_size = (game_window_size.0, game_window_size.1);
+            for quad in hud::layout(&game_state, window_size) {
+                renderer.add_hud_quad(quad);
+            }
+            // Menus go over the HUD
+            if let Some(active_menu) = &active_menu.0 {
+                for quad in menu::layout(active_menu, window_size) {
+                    renderer.add_hud_quad(quad);
+                }
             }
 
-            renderer.set_background_state(game_state.background_idx, game_state.player_health);
-            renderer.draw_instances_or_panic();
+            let mut status_flags = 0;
+            if game_state.slow_mo_active {
+                status_flags |= STATUS_FLAG_SLOW_MO;
+            }
+
+            renderer.set_background_state(game_state.background_idx, status_flags);
+            renderer.set_post_effects(settings.post_effects, game_state.damage_flash);
+
+            let shake = game_state.damage_flash * settings.screen_shake * MAX_SCREEN_SHAKE;
+            let angle = self.frame as f32 * SCREEN_SHAKE_SPIN;
+            renderer.set_camera_offset(glam::vec2(angle.cos(), angle.sin()) * shake);
+            self.frame = self.frame.wrapping_add(1);
+
+            if self.vsync != Some(settings.vsync) {
+                renderer.set_vsync(settings.vsync);
+                self.vsync = Some(settings.vsync);
+            }
+            if let Some(path) = screenshot.0.take() {
+                let result = renderer
+                    .capture()
+                    .map_err(|err| err.to_string())
+                    .and_then(|frame| frame.save(&path).map_err(|err| err.to_string()));
+                match result {
+                    Ok(()) => log::info!("Saved screenshot to {}", path.display()),
+                    Err(err) => {
+                        log::error!("Could not save screenshot to {}: {}", path.display(), err)
+                    }
+                }
+            }
+            renderer.draw();
+            frame_metrics.record(frame_start, frame_start.elapsed(), instance_count);
         } else {
             panic!("No renderer was set!")
         }
@@ -0,0 +1,50 @@
+use specs::prelude::*;
+
+use crate::game::{
+    component::{Player, StatusEffectKind, StatusEffects},
+    resource::{GameStateForRenderer, TimeScale, UnscaledDeltaTime},
+};
+
+const SLOW_MO_TIME_SCALE: f32 = 0.5;
+
+#[derive(Default)]
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    type SystemData = (
+        ReadStorage<'a, Player>,
+        WriteStorage<'a, StatusEffects>,
+        Write<'a, TimeScale>,
+        Write<'a, GameStateForRenderer>,
+        // Durations are in real seconds, slow-mo doesn't stretch itself or anything else
+        Read<'a, UnscaledDeltaTime>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+    }
+
+    fn run(
+        &mut self,
+        (player, mut status, mut time_scale, mut game_state_renderer, dt): Self::SystemData,
+    ) {
+        let delta = dt.0.as_secs_f32();
+        let mut slow_mo_active = false;
+
+        for (_, effects) in (&player, &mut status).join() {
+            effects.tick(delta);
+
+            // Slow-mo is a world effect, whoever picked it up
+            slow_mo_active |= effects.has(StatusEffectKind::SlowMo);
+        }
+
+        // Picked up by the app on the next tick, when it hands out DeltaTime
+        *time_scale = if slow_mo_active {
+            TimeScale(SLOW_MO_TIME_SCALE)
+        } else {
+            TimeScale::default()
+        };
+
+        game_state_renderer.slow_mo_active = slow_mo_active;
+    }
+}
@@ -0,0 +1,215 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::game::{
+    component::{PickupKind, Player, Transform},
+    resource::{DeltaTime, GameRng, GameState, GameWindowSize, PickupSpawnTable, WaveStatus},
+    system::{asteroid_spawner_system::AsteroidSpawn, AsteroidSpawnerSystem, PickupSystem},
+    wave::{Aim, SpawnPattern, WaveDefinition, WaveSchedule},
+};
+
+// Ring waves start off screen and need to make it all the way across
+const WAVE_MIN_LIFETIME: f32 = 20.0;
+const CALM_PICKUP_LIFETIME: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WavePhase {
+    Waiting,
+    Spawning,
+    Finished,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaveSchedulerState {
+    pub wave_idx: usize,
+    pub phase: WavePhase,
+    pub clock: f32,
+    pub spawned: u32,
+    pub anchor: f32,
+    pub started: bool,
+}
+
+impl Default for WaveSchedulerState {
+    fn default() -> Self {
+        Self {
+            wave_idx: 0,
+            phase: WavePhase::Waiting,
+            clock: 0.0,
+            spawned: 0,
+            anchor: 0.0,
+            started: false,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct WaveSchedulerSystem {}
+
+impl<'a> System<'a> for WaveSchedulerSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Transform>,
+        Read<'a, LazyUpdate>,
+        Read<'a, GameState>,
+        Read<'a, DeltaTime>,
+        Read<'a, GameWindowSize>,
+        Read<'a, WaveSchedule>,
+        Read<'a, PickupSpawnTable>,
+        Write<'a, WaveStatus>,
+        Write<'a, GameRng>,
+        Write<'a, WaveSchedulerState>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            player_storage,
+            transform_storage,
+            updater,
+            game_state,
+            dt,
+            game_window_size,
+            schedule,
+            spawn_table,
+            mut wave_status,
+            mut game_rng,
+            mut state,
+        ): Self::SystemData,
+    ) {
+        match *game_state {
+            GameState::GameStatePlay { .. } => {
+                if schedule.waves.is_empty() {
+                    return;
+                }
+
+                if !state.started {
+                    *state = WaveSchedulerState::default();
+                    state.started = true;
+                    state.clock = schedule.waves[0].delay;
+                }
+
+                let delta = dt.0.as_secs_f32();
+                let rng = &mut game_rng.0;
+                let player_position = (&player_storage, &transform_storage)
+                    .join()
+                    .next()
+                    .map(|(_, transform)| transform.position);
+
+                if state.phase == WavePhase::Waiting {
+                    state.clock -= delta;
+                    if state.clock <= 0.0 {
+                        let wave = &schedule.waves[state.wave_idx];
+                        state.phase = WavePhase::Spawning;
+                        state.spawned = 0;
+                        state.anchor = rng.gen_range(-0.8..0.8);
+                        wave_status.wave_idx = Some(state.wave_idx);
+                        wave_status.calm = wave.pattern == SpawnPattern::Calm;
+                    }
+                }
+
+                if state.phase == WavePhase::Spawning {
+                    let wave = &schedule.waves[state.wave_idx];
+                    state.clock -= delta;
+
+                    while state.clock <= 0.0 && state.spawned < wave.count {
+                        state.spawn(
+                            wave,
+                            player_position,
+                            &game_window_size,
+                            &spawn_table,
+                            &entities,
+                            &updater,
+                            &mut *rng,
+                        );
+                        state.spawned += 1;
+                        state.clock += wave.interval;
+                    }
+
+                    if state.spawned >= wave.count {
+                        if state.wave_idx + 1 < schedule.waves.len() {
+                            state.wave_idx += 1;
+                        } else if schedule.repeat {
+                            state.wave_idx = 0;
+                        } else {
+                            state.phase = WavePhase::Finished;
+                            return;
+                        }
+                        state.phase = WavePhase::Waiting;
+                        state.clock = schedule.waves[state.wave_idx].delay;
+                    }
+                }
+            }
+            _ => {
+                state.started = false;
+                *wave_status = WaveStatus::default();
+            }
+        }
+    }
+}
+
+impl WaveSchedulerState {
+    #[allow(clippy::too_many_arguments)]
+    fn spawn<'a, R: Rng>(
+        &self,
+        wave: &WaveDefinition,
+        player_position: Option<glam::Vec2>,
+        game_window_size: &GameWindowSize,
+        spawn_table: &PickupSpawnTable,
+        entities: &Entities,
+        updater: &Read<'a, LazyUpdate>,
+        rng: &mut R,
+    ) {
+        let half_size = glam::vec2(game_window_size.0 as f32, game_window_size.1 as f32) / 2.0;
+        let pattern_spawn =
+            wave.pattern
+                .spawn_at(self.spawned, wave.count, self.anchor, half_size, rng);
+
+        if wave.pattern == SpawnPattern::Calm {
+            if let Some(entry) = spawn_table.entry(PickupKind::Repair) {
+                PickupSystem::spawn_pickup(
+                    entry,
+                    pattern_spawn.position,
+                    CALM_PICKUP_LIFETIME,
+                    entities,
+                    updater,
+                );
+            }
+            return;
+        }
+
+        let direction = match (wave.aim, player_position) {
+            (Aim::Center, _) => -pattern_spawn.position.normalize_or_zero(),
+            (Aim::Player, Some(target)) => (target - pattern_spawn.position).normalize_or_zero(),
+            _ => pattern_spawn.direction,
+        };
+        let speed = random_in(wave.speed, rng);
+
+        AsteroidSpawnerSystem::spawn_asteroid(
+            &AsteroidSpawn {
+                position: pattern_spawn.position,
+                direction,
+                speed,
+                size: random_in(wave.size, rng),
+                lifetime: (10.0 * speed).max(WAVE_MIN_LIFETIME),
+            },
+            rng,
+            entities,
+            updater,
+        );
+    }
+}
+
+fn random_in<R: Rng>((min, max): (f32, f32), rng: &mut R) -> f32 {
+    if max > min {
+        rng.gen_range(min..max)
+    } else {
+        min
+    }
+}
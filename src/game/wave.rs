@@ -0,0 +1,177 @@
+use rand::Rng;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum SpawnPattern {
+    // Evenly spaced around the center, radius is relative to the half diagonal of the window
+    Ring { radius: f32 },
+    // A line of asteroids coming in from one of the top corners
+    DiagonalSweep { from_left: bool },
+    // Asteroids pouring in around a random spot on one edge, spread is a fraction of that edge
+    MeteorShower { edge: Edge, spread: f32 },
+    // No asteroids, `count` repair packs instead
+    Calm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum Aim {
+    // Whatever direction the pattern implies
+    #[default]
+    Pattern,
+    Center,
+    Player,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WaveDefinition {
+    pub pattern: SpawnPattern,
+    pub count: u32,
+    // Seconds to wait after the previous wave before this one starts
+    pub delay: f32,
+    // Seconds between individual spawns, zero spawns the whole wave at once
+    #[serde(default)]
+    pub interval: f32,
+    pub speed: (f32, f32),
+    #[serde(default = "default_size")]
+    pub size: (f32, f32),
+    #[serde(default)]
+    pub aim: Aim,
+}
+
+fn default_size() -> (f32, f32) {
+    (60.0, 100.0)
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WaveSchedule {
+    pub waves: Vec<WaveDefinition>,
+    // Start over from the first wave once the last one is done
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+impl Default for WaveSchedule {
+    fn default() -> Self {
+        Self::from_ron_str(include_str!("../../assets/waves.ron")).unwrap()
+    }
+}
+
+impl WaveSchedule {
+    pub fn from_ron_str(data: &str) -> Result<Self, ron::Error> {
+        ron::from_str(data)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternSpawn {
+    pub position: glam::Vec2,
+    pub direction: glam::Vec2,
+}
+
+impl SpawnPattern {
+    // Where the `idx`th out of `count` spawns of this pattern goes, and which way it heads.
+    // `anchor` is rolled once per wave so patterns like meteor showers stay on one spot.
+    pub fn spawn_at<R: Rng>(
+        &self,
+        idx: u32,
+        count: u32,
+        anchor: f32,
+        half_size: glam::Vec2,
+        rng: &mut R,
+    ) -> PatternSpawn {
+        let fraction = (idx as f32 + 0.5) / count.max(1) as f32;
+
+        match *self {
+            SpawnPattern::Ring { radius } => {
+                let angle = fraction * std::f32::consts::TAU;
+                let offset = glam::vec2(angle.cos(), angle.sin());
+                PatternSpawn {
+                    position: offset * half_size.length() * radius,
+                    direction: -offset,
+                }
+            }
+            SpawnPattern::DiagonalSweep { from_left } => {
+                let side = if from_left { -1.0 } else { 1.0 };
+                let corner = glam::vec2(side * half_size.x, half_size.y);
+                // Line up across the corner, perpendicular to the direction of travel
+                let across = glam::vec2(-side, 1.0).normalize();
+                let spacing = 2.0 * half_size.max_element() / count.max(1) as f32;
+                PatternSpawn {
+                    position: corner + across * (fraction - 0.5) * count as f32 * spacing,
+                    direction: glam::vec2(-side, -1.0).normalize(),
+                }
+            }
+            SpawnPattern::MeteorShower { edge, spread } => {
+                let jitter = rng.gen_range(-0.5..0.5) * spread;
+                let along = (anchor + jitter).clamp(-1.0, 1.0);
+                let drift = rng.gen_range(-0.15..0.15);
+                let (position, inward) = match edge {
+                    Edge::Top => (
+                        glam::vec2(along * half_size.x, half_size.y),
+                        glam::vec2(0.0, -1.0),
+                    ),
+                    Edge::Bottom => (
+                        glam::vec2(along * half_size.x, -half_size.y),
+                        glam::vec2(0.0, 1.0),
+                    ),
+                    Edge::Left => (
+                        glam::vec2(-half_size.x, along * half_size.y),
+                        glam::vec2(1.0, 0.0),
+                    ),
+                    Edge::Right => (
+                        glam::vec2(half_size.x, along * half_size.y),
+                        glam::vec2(-1.0, 0.0),
+                    ),
+                };
+                PatternSpawn {
+                    position,
+                    direction: (inward + inward.perp() * drift).normalize(),
+                }
+            }
+            SpawnPattern::Calm => PatternSpawn {
+                position: glam::vec2(
+                    rng.gen_range(-0.66..0.66) * half_size.x,
+                    rng.gen_range(-0.66..0.66) * half_size.y,
+                ),
+                direction: glam::Vec2::ZERO,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{SpawnPattern, WaveSchedule};
+
+    #[test]
+    fn test_default_schedule_parses() {
+        let schedule = WaveSchedule::default();
+
+        assert!(!schedule.waves.is_empty());
+    }
+
+    #[test]
+    fn test_ring_closes_in() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let half_size = glam::vec2(640.0, 360.0);
+        let pattern = SpawnPattern::Ring { radius: 1.0 };
+
+        for idx in 0..8 {
+            let spawn = pattern.spawn_at(idx, 8, 0.0, half_size, &mut rng);
+            let distance = spawn.position.length();
+
+            assert!((distance - half_size.length()).abs() < 0.01);
+            assert!((spawn.position + spawn.direction).length() < distance);
+        }
+    }
+}
@@ -1,4 +1,11 @@
 pub mod app;
+pub mod assets;
+pub mod audio;
+pub mod batch;
 pub mod game;
 pub mod physics;
 pub mod renderer;
+pub mod replay;
+pub mod settings;
+pub mod simulation;
+pub mod soak;
@@ -1,44 +1,254 @@
-use std::time::{Duration, Instant};
+use std::{
+    error::Error,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use winit::{dpi::LogicalSize, event::Event, event_loop::EventLoop, window::WindowBuilder};
+use clap::Parser;
+use specs::WorldExt;
+use winit::{
+    dpi::{LogicalSize, PhysicalSize},
+    event::Event,
+    event_loop::EventLoop,
+    window::WindowBuilder,
+};
 
-use ld49::app::App;
+use ld49::{
+    app::{App, AppOptions},
+    assets::AssetSource,
+    game::{
+        input::{InputBindings, WinitInputSource},
+        resource::{GameState, Score},
+    },
+    replay::Replay,
+    settings::{Settings, SettingsOverrides, WindowMode},
+    simulation::Simulation,
+};
+
+#[derive(Debug, Parser)]
+#[clap(about = "Unstable space shooter")]
+struct Args {
+    /// Window width in logical pixels, the size from the settings when left out
+    #[clap(long, requires = "height")]
+    width: Option<u32>,
+    /// Window height in logical pixels
+    #[clap(long, requires = "width")]
+    height: Option<u32>,
+    /// Overrides the window mode from the settings
+    #[clap(long, arg_enum)]
+    window_mode: Option<WindowMode>,
+    /// Settings file, defaults to settings.ron in the user's config directory
+    #[clap(long)]
+    settings: Option<PathBuf>,
+    /// Simulation updates per second
+    #[clap(long, default_value_t = 60)]
+    tick_rate: u32,
+    /// Present frames as soon as they are ready instead of waiting for vertical sync
+    #[clap(long)]
+    no_vsync: bool,
+    /// Print frame times and instance counts once a second
+    #[clap(long)]
+    frame_stats: bool,
+    /// Turn off the glow around bright things
+    #[clap(long)]
+    no_bloom: bool,
+    /// Draw the game like an old CRT screen, with scanlines and a curved picture
+    #[clap(long)]
+    crt: bool,
+    /// Turn off the colour fringing when a ship takes damage
+    #[clap(long)]
+    no_aberration: bool,
+    /// Seed for everything random in the game, picked at random when left out
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Directory with asset files that replace the built-in ones
+    #[clap(long)]
+    assets: Option<PathBuf>,
+    /// Input bindings file
+    #[clap(long, default_value = "input.ron")]
+    config: PathBuf,
+    /// Record all input to this file on exit
+    #[clap(long, conflicts_with_all = &["replay", "headless"])]
+    record: Option<PathBuf>,
+    /// Play back a recorded replay
+    #[clap(long)]
+    replay: Option<PathBuf>,
+    /// Run the simulation without a window
+    #[clap(long)]
+    headless: bool,
+    /// Number of ticks to run when headless, defaults to the length of the replay
+    #[clap(long, requires = "headless")]
+    ticks: Option<u64>,
+    /// Let the autopilot fly the first player when headless
+    #[clap(long, requires = "headless")]
+    autopilot: bool,
+}
 
 fn main() {
     env_logger::init();
 
+    let args = Args::parse();
+    let result = if args.headless {
+        run_headless(&args)
+    } else {
+        run_windowed(args)
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn tick_duration(tick_rate: u32) -> Duration {
+    Duration::from_nanos(1_000_000_000 / u64::from(tick_rate.max(1)))
+}
+
+fn asset_source(args: &Args) -> AssetSource {
+    match &args.assets {
+        Some(directory) => AssetSource::directory(directory),
+        None => AssetSource::embedded(),
+    }
+}
+
+fn run_headless(args: &Args) -> Result<(), Box<dyn Error>> {
+    let replay = args.replay.as_deref().map(Replay::load).transpose()?;
+    let ticks = match (args.ticks, &replay) {
+        (Some(ticks), _) => ticks,
+        (None, Some(replay)) => replay.length(),
+        (None, None) => return Err("--headless needs --ticks or --replay".into()),
+    };
+    let tick_rate = replay
+        .as_ref()
+        .map_or(args.tick_rate, |replay| replay.tick_rate);
+
+    let mut simulation = Simulation::new(
+        (args.width.unwrap_or(1280), args.height.unwrap_or(720)),
+        vec![Box::new(WinitInputSource {})],
+    );
+    simulation.load_assets(&asset_source(args))?;
+    simulation
+        .world_mut()
+        .insert(InputBindings::load_or_default(&args.config));
+    if let Some(seed) = args.seed {
+        simulation.set_seed(seed);
+    }
+    if let Some(replay) = replay {
+        simulation.play_replay(replay);
+    }
+    if args.autopilot {
+        simulation.add_autopilot(0);
+    }
+
+    let tick = tick_duration(tick_rate);
+    for _ in 0..ticks {
+        simulation.tick(tick);
+    }
+
+    let world = simulation.world();
+    let state = match *world.read_resource::<GameState>() {
+        GameState::GameStateInit {} => "title screen",
+        GameState::GameStatePlay { .. } => "playing",
+        GameState::GameStateEnd {} => "game over",
+    };
+    println!("seed    {}", simulation.seed());
+    println!(
+        "ticks   {} ({:.1}s)",
+        ticks,
+        tick.as_secs_f64() * ticks as f64
+    );
+    println!("state   {}", state);
+    println!("score   {:.0}", world.read_resource::<Score>().points);
+    Ok(())
+}
+
+fn run_windowed(args: Args) -> Result<(), Box<dyn Error>> {
+    let replay = args.replay.as_deref().map(Replay::load).transpose()?;
+    let tick_rate = replay
+        .as_ref()
+        .map_or(args.tick_rate, |replay| replay.tick_rate);
+
+    let settings_path = args.settings.clone().or_else(Settings::user_path);
+    if settings_path.is_none() {
+        log::warn!("No config directory found, settings will not be saved");
+    }
+    let settings = settings_path
+        .as_deref()
+        .map(Settings::load_or_default)
+        .unwrap_or_default();
+    // Flags win over the settings file for this session, they don't get saved
+    let mut overrides = SettingsOverrides {
+        window_mode: args.window_mode,
+        vsync: args.no_vsync.then_some(false),
+        bloom: args.no_bloom.then_some(false),
+        crt: args.crt.then_some(true),
+        chromatic_aberration: args.no_aberration.then_some(false),
+        ..Default::default()
+    };
+
     let event_loop = EventLoop::new();
-    let window = WindowBuilder::new()
-        .with_title("Game")
-        .with_inner_size(LogicalSize::new(1280, 720))
-        .build(&event_loop)
-        .unwrap();
+    let mut window_builder = WindowBuilder::new().with_title("Game");
+    window_builder = match &replay {
+        // The game area depends on the window size, so a replay needs the one it was made with
+        Some(replay) => window_builder
+            .with_inner_size(PhysicalSize::new(
+                replay.window_size.0,
+                replay.window_size.1,
+            ))
+            .with_resizable(false),
+        None => match (args.width, args.height) {
+            (Some(width), Some(height)) => {
+                window_builder.with_inner_size(LogicalSize::new(width, height))
+            }
+            _ => window_builder.with_inner_size(settings.window_size()),
+        },
+    };
+    let window = window_builder.build(&event_loop)?;
+    if replay.is_none() {
+        // The size from the flags is what the settings screen shows and goes back to
+        if let (Some(width), Some(height)) = (args.width, args.height) {
+            let size: PhysicalSize<u32> =
+                LogicalSize::new(width, height).to_physical(window.scale_factor());
+            overrides.resolution = Some((size.width, size.height));
+        }
+        window.set_fullscreen(overrides.apply(&settings).fullscreen(&window));
+    }
 
-    let mut app = App::new(&window);
+    let options = AppOptions {
+        settings,
+        overrides,
+        settings_path,
+        assets: asset_source(&args),
+        input_config: args.config,
+        seed: args.seed,
+        tick_rate,
+        record: args.record,
+        replay,
+        frame_stats: args.frame_stats,
+    };
+    let mut app = App::new(&window, options)?;
 
     let mut previous_tick = Instant::now();
-    let mut lag = 0_u32;
-    let ms_per_update = 1000 / 60; // 60 ticks per second
+    let mut lag = Duration::ZERO;
+    let tick = tick_duration(tick_rate);
 
     event_loop.run(move |event, _, control_flow| {
         match event {
             Event::MainEventsCleared => {
-                let elapsed_time = previous_tick.elapsed().as_millis() as u32;
+                lag += previous_tick.elapsed();
                 previous_tick = Instant::now();
-                lag += elapsed_time;
 
-                while lag >= ms_per_update {
-                    if let Some(new_control_flow) =
-                        app.on_update(Duration::from_millis(ms_per_update.into()))
-                    {
+                while lag >= tick {
+                    if let Some(new_control_flow) = app.on_update(tick) {
                         *control_flow = new_control_flow;
                     }
-                    lag -= ms_per_update;
+                    lag -= tick;
                 }
+                app.apply_settings(&window);
 
                 window.request_redraw();
             }
-            Event::RedrawRequested(_) => app.on_render(f64::from(lag) / f64::from(ms_per_update)),
+            Event::RedrawRequested(_) => app.on_render(lag.as_secs_f64() / tick.as_secs_f64()),
+            Event::LoopDestroyed => app.on_exit(),
             event => app.on_event(event),
         };
     });
@@ -37,9 +37,12 @@ pub struct BackgroundPipelineGlobals {
     pub sprite_idx: u32,
     pub sprite_size: [u32; 2],
     pub sprite_sheet_size: [u32; 2],
-    pub player_health: f32,
+    pub status_flags: u32,
+    pub _padding: [u32; 2],
 }
 
+pub const STATUS_FLAG_SLOW_MO: u32 = 1;
+
 pub struct BackgroundBindGroup(pub BindGroup);
 
 pub struct BackgroundBinds {
@@ -72,7 +75,7 @@ impl BackgroundBinds {
                 // Globals
                 wgpu::BindGroupLayoutEntry {
                     binding: 2,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
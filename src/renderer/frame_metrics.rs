@@ -0,0 +1,109 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, Instant},
+};
+
+// Frames kept for the rolling numbers, about two seconds at 60 fps
+const FRAME_WINDOW: usize = 120;
+
+// Written by the render system every frame
+#[derive(Debug, Default)]
+pub struct FrameMetrics {
+    // Time from one frame to the next
+    frame_times: VecDeque<Duration>,
+    // Time spent in the render system, draw call included
+    render_times: VecDeque<Duration>,
+    instances: usize,
+    last_frame: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSummary {
+    pub average_frame: Duration,
+    pub worst_frame: Duration,
+    pub average_render: Duration,
+    pub worst_render: Duration,
+    pub instances: usize,
+}
+
+impl FrameMetrics {
+    pub fn record(&mut self, frame_start: Instant, render_time: Duration, instances: usize) {
+        if let Some(last_frame) = self.last_frame {
+            push_sample(&mut self.frame_times, frame_start - last_frame);
+        }
+        push_sample(&mut self.render_times, render_time);
+        self.last_frame = Some(frame_start);
+        self.instances = instances;
+    }
+
+    pub fn summary(&self) -> Option<FrameSummary> {
+        if self.frame_times.is_empty() {
+            return None;
+        }
+        Some(FrameSummary {
+            average_frame: average(&self.frame_times),
+            worst_frame: self.frame_times.iter().copied().max().unwrap_or_default(),
+            average_render: average(&self.render_times),
+            worst_render: self.render_times.iter().copied().max().unwrap_or_default(),
+            instances: self.instances,
+        })
+    }
+}
+
+impl fmt::Display for FrameSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1} fps, frame {:.2} ms avg {:.2} ms worst, render {:.2} ms avg {:.2} ms worst, {} instances",
+            1.0 / self.average_frame.as_secs_f64().max(f64::EPSILON),
+            self.average_frame.as_secs_f64() * 1000.0,
+            self.worst_frame.as_secs_f64() * 1000.0,
+            self.average_render.as_secs_f64() * 1000.0,
+            self.worst_render.as_secs_f64() * 1000.0,
+            self.instances,
+        )
+    }
+}
+
+fn push_sample(samples: &mut VecDeque<Duration>, sample: Duration) {
+    if samples.len() == FRAME_WINDOW {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+fn average(samples: &VecDeque<Duration>) -> Duration {
+    if samples.is_empty() {
+        return Duration::ZERO;
+    }
+    samples.iter().sum::<Duration>() / samples.len() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{FrameMetrics, FRAME_WINDOW};
+
+    #[test]
+    fn test_summary_covers_recent_frames() {
+        let mut metrics = FrameMetrics::default();
+        let start = Instant::now();
+        assert!(metrics.summary().is_none());
+
+        // A slow frame that falls out of the window, then steady 10 ms frames
+        metrics.record(start, Duration::from_millis(50), 10);
+        let mut now = start + Duration::from_millis(100);
+        for frame in 0..=FRAME_WINDOW {
+            metrics.record(now, Duration::from_millis(2), frame);
+            now += Duration::from_millis(10);
+        }
+
+        let summary = metrics.summary().unwrap();
+        assert_eq!(summary.average_frame, Duration::from_millis(10));
+        assert_eq!(summary.worst_frame, Duration::from_millis(10));
+        assert_eq!(summary.worst_render, Duration::from_millis(2));
+        assert_eq!(summary.instances, FRAME_WINDOW);
+    }
+}
@@ -0,0 +1,224 @@
+use wgpu::{BindGroup, BindGroupLayout, Buffer, RenderPipeline, ShaderModule};
+
+use super::texture::Texture;
+
+// A rectangle on screen in window pixels, from the top left corner. Plain color, or a sprite from
+// the sprite atlas multiplied by the color.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct HudQuad {
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    pub color: [f32; 4],
+    pub sprite_idx: u32,
+}
+
+impl HudQuad {
+    // Same as HUD_SOLID in hud.wgsl
+    pub const SOLID: u32 = u32::MAX;
+
+    pub fn solid(position: glam::Vec2, size: glam::Vec2, color: [f32; 4]) -> Self {
+        Self {
+            position: position.into(),
+            size: size.into(),
+            color,
+            sprite_idx: Self::SOLID,
+        }
+    }
+
+    pub fn sprite(
+        sprite_idx: u32,
+        position: glam::Vec2,
+        size: glam::Vec2,
+        color: [f32; 4],
+    ) -> Self {
+        Self {
+            position: position.into(),
+            size: size.into(),
+            color,
+            sprite_idx,
+        }
+    }
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use std::mem;
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<HudQuad>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Uint32,
+                },
+            ],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct HudPipelineGlobals {
+    pub view_proj_matrix: [[f32; 4]; 4],
+    pub sprite_size: [u32; 2],
+    pub sprite_sheet_size: [u32; 2],
+}
+
+pub struct HudBindGroup(pub BindGroup);
+
+pub struct HudBinds {
+    pub layout: BindGroupLayout,
+}
+
+impl HudBinds {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler {
+                        comparison: false,
+                        filtering: true,
+                    },
+                    count: None,
+                },
+                // Globals
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("HUD Bind Group"),
+        });
+
+        Self { layout }
+    }
+
+    pub fn bind_data(
+        &self,
+        device: &wgpu::Device,
+        sprite_atlas: &Texture,
+        globals_buffer: &Buffer,
+    ) -> HudBindGroup {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(sprite_atlas.get_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sprite_atlas.get_sampler()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: globals_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("HUD bind group"),
+        });
+
+        HudBindGroup(bind_group)
+    }
+}
+
+pub struct HudPipeline {
+    pub pipeline: RenderPipeline,
+}
+
+impl HudPipeline {
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        shader: &ShaderModule,
+        binds: &HudBinds,
+    ) -> Self {
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("HUD Render Pipeline Layout"),
+            bind_group_layouts: &[&binds.layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("HUD Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "main",
+                buffers: &[HudQuad::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "main",
+                targets: &[wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                clamp_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+        });
+
+        Self { pipeline }
+    }
+}
+
+// Window pixels with the origin in the top left corner, so the HUD keeps its size in pixels
+// whatever the window is
+pub(super) fn calc_hud_matrix(window_size: winit::dpi::PhysicalSize<u32>) -> glam::Mat4 {
+    glam::Mat4::orthographic_rh(
+        0.0,
+        window_size.width as f32,
+        window_size.height as f32,
+        0.0,
+        -1.0,
+        1.0,
+    )
+}
@@ -1,11 +1,84 @@
-use winit::window::Window;
+use std::fmt;
+
+use image::RgbaImage;
+use winit::{dpi::PhysicalSize, window::Window};
 
 mod background_pipeline;
+mod frame_metrics;
+mod hud_pipeline;
+mod post_pipeline;
 mod renderer;
+mod software_post;
+mod software_renderer;
 mod sprite_pipeline;
 mod texture;
 
+pub use background_pipeline::STATUS_FLAG_SLOW_MO;
+pub use frame_metrics::{FrameMetrics, FrameSummary};
+pub use hud_pipeline::HudQuad;
+pub use post_pipeline::PostEffects;
 pub use renderer::Renderer;
+pub use software_renderer::SoftwareRenderer;
+
+// What the render system draws through, so a frame can be drawn without a GPU
+pub trait SpriteRenderer: Send {
+    fn on_resize(&mut self, new_size: PhysicalSize<u32>);
+
+    fn set_background_state(&mut self, sprite_idx: u32, status_flags: u32);
+
+    fn add_sprite_instance(&mut self, sprite_idx: u32, model_matrix: [[f32; 4]; 4], tint: [f32; 4]);
+
+    // Particles go under the sprites, in the order they were added
+    fn add_particle_instance(
+        &mut self,
+        sprite_idx: u32,
+        model_matrix: [[f32; 4]; 4],
+        tint: [f32; 4],
+    );
+
+    // Screen-space quads drawn over everything else, post effects included
+    fn add_hud_quad(&mut self, quad: HudQuad);
+
+    // Full-screen effects for the next frames, damage is how hard the player was just hit from 0
+    // to 1
+    fn set_post_effects(&mut self, effects: PostEffects, damage: f32);
+
+    // Moves the sprites by this many pixels, for screen shake. The background and HUD stay put.
+    fn set_camera_offset(&mut self, offset: glam::Vec2);
+
+    fn set_vsync(&mut self, vsync: bool);
+
+    // Draws everything added since the last draw
+    fn draw(&mut self);
+
+    // Renders everything added since the last draw into an image, without drawing it yet
+    fn capture(&mut self) -> Result<RgbaImage, RendererError>;
+}
+
+#[derive(Debug)]
+pub enum RendererError {
+    NoAdapter,
+    NoSurfaceFormat,
+    Device(wgpu::RequestDeviceError),
+    Atlas(image::ImageError),
+    Readback(wgpu::BufferAsyncError),
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RendererError::NoAdapter => write!(f, "no graphics adapter is available"),
+            RendererError::NoSurfaceFormat => {
+                write!(f, "the graphics adapter can't present to this window")
+            }
+            RendererError::Device(err) => write!(f, "couldn't open the graphics device: {}", err),
+            RendererError::Atlas(err) => write!(f, "couldn't load a sprite atlas: {}", err),
+            RendererError::Readback(err) => write!(f, "couldn't read back a frame: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RendererError {}
 
 pub struct RenderDevice {
     pub surface: wgpu::Surface,
@@ -16,7 +89,7 @@ pub struct RenderDevice {
 }
 
 impl RenderDevice {
-    pub async fn new(window: &Window) -> Self {
+    pub async fn new(window: &Window, vsync: bool) -> Result<Self, RendererError> {
         let size = window.inner_size();
         let instance = wgpu::Instance::new(wgpu::Backends::all());
         let surface = unsafe { instance.create_surface(window) };
@@ -26,7 +99,7 @@ impl RenderDevice {
                 compatible_surface: Some(&surface),
             })
             .await
-            .unwrap();
+            .ok_or(RendererError::NoAdapter)?;
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
@@ -37,29 +110,40 @@ impl RenderDevice {
                 None,
             )
             .await
-            .unwrap();
+            .map_err(RendererError::Device)?;
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface.get_preferred_format(&adapter).unwrap(),
+            format: surface
+                .get_preferred_format(&adapter)
+                .ok_or(RendererError::NoSurfaceFormat)?,
             width: size.width,
             height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
+            present_mode: present_mode(vsync),
         };
         surface.configure(&device, &config);
 
-        Self {
+        Ok(Self {
             surface,
             device,
             queue,
             config,
             size,
-        }
+        })
     }
 
     pub fn on_surface_lost(&mut self) {
         self.on_resize(self.size);
     }
 
+    // Vsync on or off without recreating anything
+    pub fn set_vsync(&mut self, vsync: bool) {
+        let present_mode = present_mode(vsync);
+        if present_mode != self.config.present_mode {
+            self.config.present_mode = present_mode;
+            self.surface.configure(&self.device, &self.config);
+        }
+    }
+
     pub fn on_resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
@@ -69,3 +153,11 @@ impl RenderDevice {
         }
     }
 }
+
+fn present_mode(vsync: bool) -> wgpu::PresentMode {
+    if vsync {
+        wgpu::PresentMode::Fifo
+    } else {
+        wgpu::PresentMode::Immediate
+    }
+}
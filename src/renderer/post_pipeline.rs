@@ -0,0 +1,335 @@
+use serde::{Deserialize, Serialize};
+use wgpu::{BindGroup, BindGroupLayout, RenderPipeline};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PostGlobals {
+    pub resolution: [f32; 2],
+    pub damage: f32,
+    pub _padding: f32,
+}
+
+// Which full-screen effects run over the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostEffects {
+    pub bloom: bool,
+    pub crt: bool,
+    // Only shows for a moment after the player takes damage
+    pub chromatic_aberration: bool,
+}
+
+impl Default for PostEffects {
+    fn default() -> Self {
+        Self {
+            bloom: true,
+            crt: false,
+            chromatic_aberration: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PostPass {
+    BloomBlur,
+    BloomComposite,
+    ChromaticAberration,
+    Crt,
+}
+
+impl PostPass {
+    // In the order they run
+    pub(super) const ALL: [PostPass; 4] = [
+        PostPass::BloomBlur,
+        PostPass::BloomComposite,
+        PostPass::ChromaticAberration,
+        PostPass::Crt,
+    ];
+
+    pub(super) fn enabled(self, effects: &PostEffects, damage: f32) -> bool {
+        match self {
+            PostPass::BloomBlur | PostPass::BloomComposite => effects.bloom,
+            PostPass::ChromaticAberration => effects.chromatic_aberration && damage > 0.0,
+            PostPass::Crt => effects.crt,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PostPass::BloomBlur => "Bloom blur",
+            PostPass::BloomComposite => "Bloom composite",
+            PostPass::ChromaticAberration => "Chromatic aberration",
+            PostPass::Crt => "CRT",
+        }
+    }
+
+    fn shader(self) -> &'static str {
+        match self {
+            PostPass::BloomBlur => include_str!("shader/bloom_blur.wgsl"),
+            PostPass::BloomComposite => include_str!("shader/bloom_composite.wgsl"),
+            PostPass::ChromaticAberration => include_str!("shader/chromatic_aberration.wgsl"),
+            PostPass::Crt => include_str!("shader/crt.wgsl"),
+        }
+    }
+}
+
+pub(super) const POST_COMMON_SHADER: &str = include_str!("shader/post_common.wgsl");
+pub(super) const POST_COPY_SHADER: &str = include_str!("shader/post_copy.wgsl");
+
+// Every pass shader is the common part with its own fragment stage after it
+pub(super) fn post_shader_source(pass_shader: &str) -> String {
+    format!("{}\n{}", POST_COMMON_SHADER, pass_shader)
+}
+
+pub struct PostBinds {
+    pub layout: BindGroupLayout,
+    pub sampler: wgpu::Sampler,
+}
+
+impl PostBinds {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                // Source
+                texture_entry(0),
+                // Scene
+                texture_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler {
+                        comparison: false,
+                        filtering: true,
+                    },
+                    count: None,
+                },
+                // Globals
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Post Bind Group"),
+        });
+
+        // Blurs and curved screens sample between pixels
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self { layout, sampler }
+    }
+
+    pub fn bind_data(
+        &self,
+        device: &wgpu::Device,
+        source: &wgpu::TextureView,
+        scene: &wgpu::TextureView,
+        globals_buffer: &wgpu::Buffer,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(scene),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: globals_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("Post bind group"),
+        })
+    }
+}
+
+pub struct PostPipeline {
+    pub pipeline: RenderPipeline,
+}
+
+impl PostPipeline {
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        binds: &PostBinds,
+        label: &str,
+        pass_shader: &str,
+    ) -> Self {
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(post_shader_source(pass_shader).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Render Pipeline Layout"),
+            bind_group_layouts: &[&binds.layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "main",
+                targets: &[wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                clamp_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+        });
+
+        Self { pipeline }
+    }
+
+    pub fn for_pass(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        binds: &PostBinds,
+        pass: PostPass,
+    ) -> Self {
+        Self::new(device, config, binds, pass.label(), pass.shader())
+    }
+}
+
+// The frame is drawn into `scene`, then each pass reads the previous one's output and writes
+// the other ping-pong texture. The last pass writes straight to the window. Sized to the window.
+pub struct PostTargets {
+    #[allow(dead_code)]
+    scene: wgpu::Texture,
+    pub scene_view: wgpu::TextureView,
+    pub scene_bind_group: BindGroup,
+    #[allow(dead_code)]
+    ping_pong: [wgpu::Texture; 2],
+    pub ping_pong_views: [wgpu::TextureView; 2],
+    pub ping_pong_bind_groups: [BindGroup; 2],
+}
+
+impl PostTargets {
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        binds: &PostBinds,
+        globals_buffer: &wgpu::Buffer,
+    ) -> Self {
+        let create_target = |label| {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: config.width,
+                    height: config.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: config.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            (texture, view)
+        };
+
+        let (scene, scene_view) = create_target("Post scene texture");
+        let (ping, ping_view) = create_target("Post ping texture");
+        let (pong, pong_view) = create_target("Post pong texture");
+
+        let scene_bind_group = binds.bind_data(device, &scene_view, &scene_view, globals_buffer);
+        let ping_pong_bind_groups = [
+            binds.bind_data(device, &ping_view, &scene_view, globals_buffer),
+            binds.bind_data(device, &pong_view, &scene_view, globals_buffer),
+        ];
+
+        Self {
+            scene,
+            scene_view,
+            scene_bind_group,
+            ping_pong: [ping, pong],
+            ping_pong_views: [ping_view, pong_view],
+            ping_pong_bind_groups,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{post_shader_source, PostPass, POST_COPY_SHADER};
+
+    fn validate(name: &str, source: &str) {
+        let module = naga::front::wgsl::parse_str(source)
+            .unwrap_or_else(|err| panic!("{} doesn't parse: {:?}", name, err));
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap_or_else(|err| panic!("{} doesn't validate: {:?}", name, err));
+    }
+
+    #[test]
+    fn test_shaders_validate() {
+        validate(
+            "instanced_sprite.wgsl",
+            include_str!("shader/instanced_sprite.wgsl"),
+        );
+        validate("background.wgsl", include_str!("shader/background.wgsl"));
+        validate("hud.wgsl", include_str!("shader/hud.wgsl"));
+        validate("post_copy.wgsl", &post_shader_source(POST_COPY_SHADER));
+        for pass in PostPass::ALL {
+            validate(pass.label(), &post_shader_source(pass.shader()));
+        }
+    }
+}
@@ -1,8 +1,15 @@
+use image::RgbaImage;
 use wgpu::util::DeviceExt;
 use winit::window::Window;
 
 use crate::renderer::{
     background_pipeline::{BackgroundBinds, BackgroundPipeline, BackgroundPipelineGlobals},
+    hud_pipeline::{
+        calc_hud_matrix, HudBindGroup, HudBinds, HudPipeline, HudPipelineGlobals, HudQuad,
+    },
+    post_pipeline::{
+        PostBinds, PostEffects, PostGlobals, PostPass, PostPipeline, PostTargets, POST_COPY_SHADER,
+    },
     sprite_pipeline::{SpriteBinds, SpritePipelineGlobals},
     texture::Texture,
 };
@@ -10,7 +17,7 @@ use crate::renderer::{
 use super::{
     background_pipeline::BackgroundBindGroup,
     sprite_pipeline::{Instance, SpriteBindGroup, SpritePipeline, Vertex},
-    RenderDevice,
+    RenderDevice, RendererError, SpriteRenderer,
 };
 
 const SPRITE_VERTICES: &[Vertex] = &[
@@ -67,6 +74,76 @@ pub struct Renderer {
     background_globals_buffer: wgpu::Buffer,
 
     instances: Vec<Instance>,
+    instance_buffer: InstanceBuffer<Instance>,
+    // Drawn in their own pass between the background and the sprites
+    particles: Vec<Instance>,
+    particle_buffer: InstanceBuffer<Instance>,
+
+    // Drawn last, over the post effects
+    hud_pipeline: HudPipeline,
+    hud_bind_group: HudBindGroup,
+    hud_globals: HudPipelineGlobals,
+    hud_globals_buffer: wgpu::Buffer,
+    hud_quads: Vec<HudQuad>,
+    hud_buffer: InstanceBuffer<HudQuad>,
+
+    post_binds: PostBinds,
+    post_pipelines: Vec<(PostPass, PostPipeline)>,
+    // Runs instead when every pass is off, the frame still has to get to the window
+    post_copy_pipeline: PostPipeline,
+    post_globals: PostGlobals,
+    post_globals_buffer: wgpu::Buffer,
+    post_targets: PostTargets,
+    post_effects: PostEffects,
+
+    // Screen shake, in pixels
+    camera_offset: glam::Vec2,
+}
+
+// Enough for a busy frame without particles piling up
+const INITIAL_INSTANCE_CAPACITY: usize = 1024;
+// The starfield alone keeps a few hundred around
+const INITIAL_PARTICLE_CAPACITY: usize = 4096;
+// Score digits take a quad per lit cell
+const INITIAL_HUD_CAPACITY: usize = 512;
+
+// Reused every frame, replaced with a bigger one when the instances don't fit
+struct InstanceBuffer<T> {
+    label: &'static str,
+    buffer: wgpu::Buffer,
+    capacity: usize,
+    instance: std::marker::PhantomData<T>,
+}
+
+impl<T: bytemuck::Pod> InstanceBuffer<T> {
+    fn new(device: &wgpu::Device, label: &'static str, capacity: usize) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: (capacity * std::mem::size_of::<T>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            label,
+            buffer,
+            capacity,
+            instance: std::marker::PhantomData,
+        }
+    }
+
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, instances: &[T]) {
+        if instances.len() > self.capacity {
+            // Doubling keeps reallocations rare while the count climbs
+            let mut capacity = self.capacity * 2;
+            while capacity < instances.len() {
+                capacity *= 2;
+            }
+            *self = Self::new(device, self.label, capacity);
+        }
+        if !instances.is_empty() {
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(instances));
+        }
+    }
 }
 
 impl Renderer {
@@ -76,8 +153,9 @@ impl Renderer {
         sprite_size: (u32, u32),
         background_atlas_data: &[u8],
         background_size: (u32, u32),
-    ) -> Self {
-        let renderer = RenderDevice::new(window).await;
+        vsync: bool,
+    ) -> Result<Self, RendererError> {
+        let renderer = RenderDevice::new(window, vsync).await?;
 
         let sprite_shader = renderer
             .device
@@ -107,7 +185,7 @@ impl Renderer {
             sprite_atlas_data,
             "spritesheet.png",
         )
-        .unwrap();
+        .map_err(RendererError::Atlas)?;
 
         let background_atlas = Texture::from_bytes(
             &renderer.device,
@@ -115,7 +193,7 @@ impl Renderer {
             background_atlas_data,
             "background_spritesheet.png",
         )
-        .unwrap();
+        .map_err(RendererError::Atlas)?;
 
         let mat = Self::calc_ortho_matrix(renderer.size);
         let (sprite_width, sprite_height) = sprite_size;
@@ -145,7 +223,8 @@ impl Renderer {
             sprite_idx: 0,
             sprite_size: [sprite_width, sprite_height],
             sprite_sheet_size: [atlas_width, atlas_height],
-            player_health: 1.0,
+            status_flags: 0,
+            _padding: [0; 2],
         };
 
         let background_globals_buffer =
@@ -172,8 +251,77 @@ impl Renderer {
             });
 
         let instances = Vec::<Instance>::new();
+        let instance_buffer = InstanceBuffer::new(
+            &renderer.device,
+            "Instance Buffer",
+            INITIAL_INSTANCE_CAPACITY,
+        );
+        let particle_buffer = InstanceBuffer::new(
+            &renderer.device,
+            "Particle Buffer",
+            INITIAL_PARTICLE_CAPACITY,
+        );
 
-        Self {
+        let hud_shader = renderer
+            .device
+            .create_shader_module(&wgpu::include_wgsl!("shader/hud.wgsl"));
+        let hud_binds = HudBinds::new(&renderer.device);
+        let hud_pipeline =
+            HudPipeline::new(&renderer.device, &renderer.config, &hud_shader, &hud_binds);
+        let hud_globals = HudPipelineGlobals {
+            view_proj_matrix: calc_hud_matrix(renderer.size).to_cols_array_2d(),
+            sprite_size: sprite_globals.sprite_size,
+            sprite_sheet_size: sprite_globals.sprite_sheet_size,
+        };
+        let hud_globals_buffer =
+            renderer
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("HUD Globals Buffer"),
+                    contents: bytemuck::cast_slice(&[hud_globals]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+        let hud_bind_group =
+            hud_binds.bind_data(&renderer.device, &sprite_atlas, &hud_globals_buffer);
+        let hud_buffer = InstanceBuffer::new(&renderer.device, "HUD Buffer", INITIAL_HUD_CAPACITY);
+
+        let post_binds = PostBinds::new(&renderer.device);
+        let post_pipelines = PostPass::ALL
+            .iter()
+            .map(|&pass| {
+                let pipeline =
+                    PostPipeline::for_pass(&renderer.device, &renderer.config, &post_binds, pass);
+                (pass, pipeline)
+            })
+            .collect();
+        let post_copy_pipeline = PostPipeline::new(
+            &renderer.device,
+            &renderer.config,
+            &post_binds,
+            "Post copy",
+            POST_COPY_SHADER,
+        );
+        let post_globals = PostGlobals {
+            resolution: [renderer.config.width as f32, renderer.config.height as f32],
+            damage: 0.0,
+            _padding: 0.0,
+        };
+        let post_globals_buffer =
+            renderer
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Post Globals Buffer"),
+                    contents: bytemuck::cast_slice(&[post_globals]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+        let post_targets = PostTargets::new(
+            &renderer.device,
+            &renderer.config,
+            &post_binds,
+            &post_globals_buffer,
+        );
+
+        Ok(Self {
             renderer,
             sprite_pipeline,
             sprite_bind_group,
@@ -185,62 +333,31 @@ impl Renderer {
             background_globals,
             background_globals_buffer,
             instances,
-        }
+            instance_buffer,
+            particles: Vec::new(),
+            particle_buffer,
+            hud_pipeline,
+            hud_bind_group,
+            hud_globals,
+            hud_globals_buffer,
+            hud_quads: Vec::new(),
+            hud_buffer,
+            post_binds,
+            post_pipelines,
+            post_copy_pipeline,
+            post_globals,
+            post_globals_buffer,
+            post_targets,
+            post_effects: PostEffects::default(),
+            camera_offset: glam::Vec2::ZERO,
+        })
     }
 
     pub fn on_surface_lost(&mut self) {
         self.renderer.on_surface_lost();
     }
 
-    pub fn on_resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        self.renderer.on_resize(new_size);
-
-        let mat = Self::calc_ortho_matrix(new_size);
-        self.sprite_globals.view_proj_matrix = mat.to_cols_array_2d();
-
-        self.renderer.queue.write_buffer(
-            &self.sprite_globals_buffer,
-            0,
-            bytemuck::cast_slice(&[self.sprite_globals]),
-        )
-    }
-
-    pub fn set_background_state(&mut self, sprite_idx: u32, player_health: f32) {
-        self.background_globals.player_health = player_health;
-        self.background_globals.sprite_idx = sprite_idx;
-        self.renderer.queue.write_buffer(
-            &self.background_globals_buffer,
-            0,
-            bytemuck::cast_slice(&[self.background_globals]),
-        )
-    }
-
-    pub fn add_sprite_instance(&mut self, sprite_idx: u32, model_matrix: [[f32; 4]; 4]) {
-        self.instances.push(Instance {
-            sprite_idx,
-            model_matrix,
-        })
-    }
-
-    pub fn draw_instances_or_panic(&mut self) {
-        match self.draw_instances() {
-            Ok(_) => (),
-            Err(wgpu::SurfaceError::Lost) => self.on_surface_lost(),
-            Err(e) => panic!("{:?}", e),
-        }
-    }
-
     fn draw_instances(&mut self) -> Result<(), wgpu::SurfaceError> {
-        // @OPTIMIZATION: Recreating a buffer every frame is not the best idea
-        let instance_buffer =
-            self.renderer
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Instance Buffer"),
-                    contents: bytemuck::cast_slice(&self.instances),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
-
         let output = self.renderer.surface.get_current_frame()?.output;
         let view = output
             .texture
@@ -251,12 +368,124 @@ impl Renderer {
                 .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                     label: Some("Renderer command encoder"),
                 });
+        self.upload_instances();
+        self.encode_passes(&mut encoder, &view);
+        self.renderer
+            .queue
+            .submit(std::iter::once(encoder.finish()));
+
+        self.instances.clear();
+        self.particles.clear();
+        self.hud_quads.clear();
+        Ok(())
+    }
+
+    // Renders the instances added so far into a texture instead of the window and reads it back.
+    // They are still drawn to the window on the next draw.
+    pub fn capture(&mut self) -> Result<RgbaImage, RendererError> {
+        self.upload_instances();
+        let device = &self.renderer.device;
+        let (width, height) = (self.renderer.config.width, self.renderer.config.height);
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        // Same format as the window so the pipelines can draw into it
+        let format = self.renderer.config.format;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Capture texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Rows in the readback buffer have to be padded out to the copy alignment
+        let unpadded_bytes_per_row = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture command encoder"),
+        });
+        self.encode_passes(&mut encoder, &view);
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image: std::num::NonZeroU32::new(height),
+                },
+            },
+            size,
+        );
+        self.renderer
+            .queue
+            .submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(mapping).map_err(RendererError::Readback)?;
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+        for row in slice
+            .get_mapped_range()
+            .chunks(padded_bytes_per_row as usize)
+        {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+        buffer.unmap();
+
+        if matches!(
+            format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            for pixel in pixels.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        Ok(RgbaImage::from_raw(width, height, pixels).expect("capture buffer is the image size"))
+    }
 
+    fn upload_instances(&mut self) {
+        let (device, queue) = (&self.renderer.device, &self.renderer.queue);
+        self.instance_buffer.upload(device, queue, &self.instances);
+        self.particle_buffer.upload(device, queue, &self.particles);
+        self.hud_buffer.upload(device, queue, &self.hud_quads);
+    }
+
+    fn encode_passes(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        self.encode_scene_passes(encoder, &self.post_targets.scene_view);
+        self.encode_post_passes(encoder, view);
+        if !self.hud_quads.is_empty() {
+            self.encode_hud_pass(encoder, view);
+        }
+    }
+
+    fn encode_scene_passes(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Background render pass"),
                 color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -277,11 +506,32 @@ impl Renderer {
             render_pass.draw(0..6, 0..1);
         }
 
+        if !self.particles.is_empty() {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Particle render pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(&self.sprite_pipeline.pipeline);
+            render_pass.set_bind_group(0, &self.sprite_bind_group.0, &[]);
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, self.particle_buffer.buffer.slice(..));
+            render_pass.draw(0..6, 0..self.particles.len() as _);
+        }
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Instanced sprite render pass"),
                 color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Load,
@@ -294,18 +544,103 @@ impl Renderer {
             render_pass.set_pipeline(&self.sprite_pipeline.pipeline);
             render_pass.set_bind_group(0, &self.sprite_bind_group.0, &[]);
             render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, self.instance_buffer.buffer.slice(..));
             render_pass.draw(0..6, 0..self.instances.len() as _);
         }
-        self.renderer
-            .queue
-            .submit(std::iter::once(encoder.finish()));
+    }
 
-        self.instances.clear();
-        Ok(())
+    fn encode_post_passes(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut pipelines: Vec<&PostPipeline> = self
+            .post_pipelines
+            .iter()
+            .filter(|(pass, _)| pass.enabled(&self.post_effects, self.post_globals.damage))
+            .map(|(_, pipeline)| pipeline)
+            .collect();
+        if pipelines.is_empty() {
+            pipelines.push(&self.post_copy_pipeline);
+        }
+
+        let targets = &self.post_targets;
+        let mut source = &targets.scene_bind_group;
+        for (idx, pipeline) in pipelines.iter().enumerate() {
+            let target = if idx + 1 == pipelines.len() {
+                view
+            } else {
+                &targets.ping_pong_views[idx % 2]
+            };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post render pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(&pipeline.pipeline);
+            render_pass.set_bind_group(0, source, &[]);
+            render_pass.draw(0..3, 0..1);
+            source = &targets.ping_pong_bind_groups[idx % 2];
+        }
+    }
+
+    fn encode_hud_pass(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("HUD render pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.hud_pipeline.pipeline);
+        render_pass.set_bind_group(0, &self.hud_bind_group.0, &[]);
+        render_pass.set_vertex_buffer(0, self.hud_buffer.buffer.slice(..));
+        render_pass.draw(0..6, 0..self.hud_quads.len() as _);
+    }
+
+    fn write_sprite_globals(&mut self) {
+        let mat = Self::calc_ortho_matrix(self.renderer.size)
+            * glam::Mat4::from_translation(self.camera_offset.extend(0.0));
+        self.sprite_globals.view_proj_matrix = mat.to_cols_array_2d();
+        self.renderer.queue.write_buffer(
+            &self.sprite_globals_buffer,
+            0,
+            bytemuck::cast_slice(&[self.sprite_globals]),
+        );
+    }
+
+    fn write_post_globals(&self) {
+        self.renderer.queue.write_buffer(
+            &self.post_globals_buffer,
+            0,
+            bytemuck::cast_slice(&[self.post_globals]),
+        )
+    }
+
+    // Inverse of what calc_ortho_matrix does to world coordinates, window y points down
+    pub fn window_to_world(
+        position: glam::Vec2,
+        window_size: winit::dpi::PhysicalSize<u32>,
+    ) -> glam::Vec2 {
+        let half_width = window_size.width / 2;
+        let half_height = window_size.height / 2;
+        glam::vec2(
+            position.x - half_width as f32,
+            half_height as f32 - position.y,
+        )
     }
 
-    fn calc_ortho_matrix(window_size: winit::dpi::PhysicalSize<u32>) -> glam::Mat4 {
+    pub(super) fn calc_ortho_matrix(window_size: winit::dpi::PhysicalSize<u32>) -> glam::Mat4 {
         // Screen coordinates are pixel coordinates, recentered to 0,0 being the center of the window
         let half_width = window_size.width / 2;
         let half_height = window_size.height / 2;
@@ -320,3 +655,121 @@ impl Renderer {
             )
     }
 }
+
+impl SpriteRenderer for Renderer {
+    fn on_resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.renderer.on_resize(new_size);
+
+        self.write_sprite_globals();
+
+        self.hud_globals.view_proj_matrix = calc_hud_matrix(new_size).to_cols_array_2d();
+        self.renderer.queue.write_buffer(
+            &self.hud_globals_buffer,
+            0,
+            bytemuck::cast_slice(&[self.hud_globals]),
+        );
+
+        // Follows the surface, which keeps its old size when the window is minimized
+        let config = &self.renderer.config;
+        self.post_globals.resolution = [config.width as f32, config.height as f32];
+        self.write_post_globals();
+        self.post_targets = PostTargets::new(
+            &self.renderer.device,
+            config,
+            &self.post_binds,
+            &self.post_globals_buffer,
+        );
+    }
+
+    fn set_post_effects(&mut self, effects: PostEffects, damage: f32) {
+        self.post_effects = effects;
+        self.post_globals.damage = damage;
+        self.write_post_globals();
+    }
+
+    fn set_camera_offset(&mut self, offset: glam::Vec2) {
+        if offset != self.camera_offset {
+            self.camera_offset = offset;
+            self.write_sprite_globals();
+        }
+    }
+
+    fn set_vsync(&mut self, vsync: bool) {
+        self.renderer.set_vsync(vsync);
+    }
+
+    fn set_background_state(&mut self, sprite_idx: u32, status_flags: u32) {
+        self.background_globals.sprite_idx = sprite_idx;
+        self.background_globals.status_flags = status_flags;
+        self.renderer.queue.write_buffer(
+            &self.background_globals_buffer,
+            0,
+            bytemuck::cast_slice(&[self.background_globals]),
+        )
+    }
+
+    fn add_sprite_instance(
+        &mut self,
+        sprite_idx: u32,
+        model_matrix: [[f32; 4]; 4],
+        tint: [f32; 4],
+    ) {
+        self.instances.push(Instance {
+            sprite_idx,
+            model_matrix,
+            tint,
+        })
+    }
+
+    fn add_particle_instance(
+        &mut self,
+        sprite_idx: u32,
+        model_matrix: [[f32; 4]; 4],
+        tint: [f32; 4],
+    ) {
+        self.particles.push(Instance {
+            sprite_idx,
+            model_matrix,
+            tint,
+        })
+    }
+
+    fn add_hud_quad(&mut self, quad: HudQuad) {
+        self.hud_quads.push(quad)
+    }
+
+    fn draw(&mut self) {
+        match self.draw_instances() {
+            Ok(_) => (),
+            Err(wgpu::SurfaceError::Lost) => self.on_surface_lost(),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    fn capture(&mut self) -> Result<RgbaImage, RendererError> {
+        Renderer::capture(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::dpi::PhysicalSize;
+
+    use super::Renderer;
+
+    #[test]
+    fn test_window_to_world_matches_ortho() {
+        let size = PhysicalSize::new(1280, 720);
+        let ortho = Renderer::calc_ortho_matrix(size);
+
+        for (window, ndc) in [
+            (glam::vec2(0.0, 0.0), glam::vec2(-1.0, 1.0)),
+            (glam::vec2(1280.0, 720.0), glam::vec2(1.0, -1.0)),
+            (glam::vec2(640.0, 360.0), glam::vec2(0.0, 0.0)),
+        ] {
+            let world = Renderer::window_to_world(window, size);
+            let clip = ortho * glam::vec4(world.x, world.y, -1.0, 1.0);
+            assert!((glam::vec2(clip.x, clip.y) - ndc).length() < 1e-5);
+        }
+    }
+}
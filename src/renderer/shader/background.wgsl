@@ -6,7 +6,7 @@ struct Globals {
     sprite_idx: u32;
     sprite_size: array<u32, 2>;
     sprite_sheet_size: array<u32, 2>;
-    player_health: f32;
+    status_flags: u32;
 };
 [[group(0), binding(2)]]
 var<uniform> globals: Globals;
@@ -20,7 +20,6 @@ struct VertexOutput {
     [[builtin(position)]] clip_position: vec4<f32>;
     [[location(0)]] tex_coords: vec2<f32>;
     [[location(1)]] sprite_tex_coords: vec2<f32>;
-    [[location(2)]] player_health: f32;
 };
 
 struct SpriteCell {
@@ -97,33 +96,27 @@ fn main(
         globals.sprite_sheet_size,
     );
     out.clip_position = vec4<f32>(in.position, 1.0);
-    out.player_health = globals.player_health;
     return out;
 }
 
 // Fragment shader
 
-let HEALTH_BAR_SIZE: f32 = 0.05;
+let STATUS_FLAG_SLOW_MO: u32 = 1u;
 
 [[group(0), binding(0)]]
 var t_diffuse: texture_2d<f32>;
 [[group(0), binding(1)]]
 var s_diffuse: sampler;
 
-fn health_bar_at_coord(tex_coords: vec2<f32>, player_health: f32) -> f32 {
-    if (tex_coords.y < HEALTH_BAR_SIZE && tex_coords.x < player_health) {
-        return 0.0;
-    } else {
-        return 1.0;
-    }
-}
-
 [[stage(fragment)]]
 fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
     var texture_color = textureSample(t_diffuse, s_diffuse, in.sprite_tex_coords);
-    var health_bar_color = vec4<f32>(1.0, 1.0, 1.0, 1.0);
 
-    var health_bar = health_bar_at_coord(in.tex_coords, in.player_health);
+    // Slow-mo washes the background out towards blue
+    if ((globals.status_flags & STATUS_FLAG_SLOW_MO) != 0u) {
+        var luminance = dot(texture_color.rgb, vec3<f32>(0.299, 0.587, 0.114));
+        texture_color = vec4<f32>(mix(texture_color.rgb, vec3<f32>(luminance * 0.6, luminance * 0.8, luminance), 0.7), texture_color.a);
+    }
 
-    return mix(health_bar_color, texture_color, health_bar);
+    return texture_color;
 }
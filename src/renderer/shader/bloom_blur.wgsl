@@ -0,0 +1,24 @@
+// Keeps the bright parts of the frame and blurs them sideways
+
+let BLOOM_THRESHOLD: f32 = 0.6;
+let BLOOM_TAPS: i32 = 6;
+let BLOOM_TAP_SPACING: f32 = 2.0;
+
+fn bright(color: vec3<f32>) -> vec3<f32> {
+    let luminance = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    return color * max(luminance - BLOOM_THRESHOLD, 0.0) / max(luminance, 0.0001);
+}
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let texel = vec2<f32>(BLOOM_TAP_SPACING / globals.resolution.x, 0.0);
+    var sum: vec3<f32> = vec3<f32>(0.0, 0.0, 0.0);
+    var weight_sum: f32 = 0.0;
+    for (var i: i32 = -BLOOM_TAPS; i <= BLOOM_TAPS; i = i + 1) {
+        let weight = exp(-f32(i * i) / 18.0);
+        let color = textureSample(t_source, s_post, in.tex_coords + texel * f32(i)).rgb;
+        sum = sum + bright(color) * weight;
+        weight_sum = weight_sum + weight;
+    }
+    return vec4<f32>(sum / weight_sum, 1.0);
+}
@@ -0,0 +1,19 @@
+// Blurs the bright parts upwards and adds them back onto the frame
+
+let BLOOM_STRENGTH: f32 = 0.8;
+let BLOOM_TAPS: i32 = 6;
+let BLOOM_TAP_SPACING: f32 = 2.0;
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let texel = vec2<f32>(0.0, BLOOM_TAP_SPACING / globals.resolution.y);
+    var sum: vec3<f32> = vec3<f32>(0.0, 0.0, 0.0);
+    var weight_sum: f32 = 0.0;
+    for (var i: i32 = -BLOOM_TAPS; i <= BLOOM_TAPS; i = i + 1) {
+        let weight = exp(-f32(i * i) / 18.0);
+        sum = sum + textureSample(t_source, s_post, in.tex_coords + texel * f32(i)).rgb * weight;
+        weight_sum = weight_sum + weight;
+    }
+    let scene = textureSample(t_scene, s_post, in.tex_coords).rgb;
+    return vec4<f32>(scene + sum / weight_sum * BLOOM_STRENGTH, 1.0);
+}
@@ -0,0 +1,13 @@
+// Pulls the red and blue channels apart towards the edges, harder the more damage was taken
+
+// Fraction of the screen the channels split by in the corners at full damage
+let MAX_ABERRATION: f32 = 0.012;
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let offset = (in.tex_coords - vec2<f32>(0.5, 0.5)) * 2.0 * MAX_ABERRATION * globals.damage;
+    let red = textureSample(t_source, s_post, in.tex_coords + offset).r;
+    let green = textureSample(t_source, s_post, in.tex_coords).g;
+    let blue = textureSample(t_source, s_post, in.tex_coords - offset).b;
+    return vec4<f32>(red, green, blue, 1.0);
+}
@@ -0,0 +1,21 @@
+// Curved screen, scanlines and darker corners
+
+let CRT_CURVATURE: f32 = 0.06;
+let SCANLINE_STRENGTH: f32 = 0.25;
+let VIGNETTE_STRENGTH: f32 = 0.35;
+let PI: f32 = 3.14159265;
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var uv: vec2<f32> = in.tex_coords * 2.0 - vec2<f32>(1.0, 1.0);
+    uv = uv + uv * (uv.yx * uv.yx) * CRT_CURVATURE;
+    let coords = uv * 0.5 + vec2<f32>(0.5, 0.5);
+    // Black past the curved edges
+    let inside = coords.x >= 0.0 && coords.x <= 1.0 && coords.y >= 0.0 && coords.y <= 1.0;
+
+    let color = textureSample(t_source, s_post, coords).rgb * select(0.0, 1.0, inside);
+    // Every other pixel row is darker
+    let scanline = 1.0 - SCANLINE_STRENGTH * (0.5 + 0.5 * sin(coords.y * globals.resolution.y * PI));
+    let vignette = 1.0 - VIGNETTE_STRENGTH * dot(uv, uv) * 0.5;
+    return vec4<f32>(color * scanline * vignette, 1.0);
+}
@@ -0,0 +1,73 @@
+// Vertex shader
+
+[[block]]
+struct HudGlobals {
+    // Window pixels to clip space, y pointing down
+    view_proj_mat: mat4x4<f32>;
+    sprite_size: vec2<u32>;
+    sprite_sheet_size: vec2<u32>;
+};
+[[group(0), binding(2)]]
+var<uniform> globals: HudGlobals;
+
+struct InstanceInput {
+    [[location(0)]] position: vec2<f32>;
+    [[location(1)]] size: vec2<f32>;
+    [[location(2)]] color: vec4<f32>;
+    [[location(3)]] sprite_idx: u32;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] tex_coords: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+    // 1 for sprites, 0 for plain rectangles
+    [[location(2)]] textured: f32;
+};
+
+let HUD_SOLID: u32 = 4294967295u;
+
+[[stage(vertex)]]
+fn main(
+    [[builtin(vertex_index)]] vertex_index: u32,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var corners: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+    );
+    let corner = corners[vertex_index];
+
+    var out: VertexOutput;
+    out.color = instance.color;
+    out.textured = 0.0;
+    out.tex_coords = vec2<f32>(0.0, 0.0);
+    if (instance.sprite_idx != HUD_SOLID) {
+        let columns = globals.sprite_sheet_size.x / globals.sprite_size.x;
+        let cell = vec2<f32>(f32(instance.sprite_idx % columns), f32(instance.sprite_idx / columns));
+        let sprite_size = vec2<f32>(f32(globals.sprite_size.x), f32(globals.sprite_size.y));
+        let sheet_size = vec2<f32>(f32(globals.sprite_sheet_size.x), f32(globals.sprite_sheet_size.y));
+        out.tex_coords = (cell + corner) * sprite_size / sheet_size;
+        out.textured = 1.0;
+    }
+    let position = instance.position + corner * instance.size;
+    out.clip_position = globals.view_proj_mat * vec4<f32>(position, 0.0, 1.0);
+    return out;
+}
+
+// Fragment shader
+
+[[group(0), binding(0)]]
+var t_diffuse: texture_2d<f32>;
+[[group(0), binding(1)]]
+var s_diffuse: sampler;
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let texture_color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    return mix(vec4<f32>(1.0, 1.0, 1.0, 1.0), texture_color, in.textured) * in.color;
+}
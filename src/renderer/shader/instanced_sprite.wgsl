@@ -15,6 +15,7 @@ struct InstanceInput {
     [[location(12)]] model_matrix_2: vec4<f32>;
     [[location(13)]] model_matrix_3: vec4<f32>;
     [[location(14)]] sprite_idx: u32;
+    [[location(15)]] tint: vec4<f32>;
 };
 
 struct VertexInput {
@@ -25,6 +26,7 @@ struct VertexInput {
 struct VertexOutput {
     [[builtin(position)]] clip_position: vec4<f32>;
     [[location(0)]] tex_coords: vec2<f32>;
+    [[location(1)]] tint: vec4<f32>;
 };
 
 struct SpriteCell {
@@ -107,6 +109,7 @@ fn main(
         globals.sprite_size,
         globals.sprite_sheet_size,
     );
+    out.tint = instance.tint;
     out.clip_position = globals.view_proj_mat * model_matrix * vec4<f32>(in.position, 1.0);
     return out;
 }
@@ -120,5 +123,5 @@ var s_diffuse: sampler;
 
 [[stage(fragment)]]
 fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    return textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.tint;
 }
@@ -0,0 +1,37 @@
+// Shared by every post pass, the pass shader is appended to this
+
+[[block]]
+struct PostGlobals {
+    resolution: vec2<f32>;
+    // 0 to 1, how hard the player was just hit
+    damage: f32;
+    _padding: f32;
+};
+
+// What the previous pass drew, or the frame itself for the first pass
+[[group(0), binding(0)]]
+var t_source: texture_2d<f32>;
+// The frame before any post pass
+[[group(0), binding(1)]]
+var t_scene: texture_2d<f32>;
+[[group(0), binding(2)]]
+var s_post: sampler;
+[[group(0), binding(3)]]
+var<uniform> globals: PostGlobals;
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] tex_coords: vec2<f32>;
+};
+
+// One triangle that covers the whole screen, no vertex buffer needed
+[[stage(vertex)]]
+fn main([[builtin(vertex_index)]] vertex_index: u32) -> VertexOutput {
+    let x = f32((vertex_index & 1u) * 2u);
+    let y = f32((vertex_index >> 1u) * 2u);
+
+    var out: VertexOutput;
+    out.tex_coords = vec2<f32>(x, y);
+    out.clip_position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+    return out;
+}
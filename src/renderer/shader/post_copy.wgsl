@@ -0,0 +1,6 @@
+// Used when every post pass is off
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return textureSample(t_source, s_post, in.tex_coords);
+}
@@ -0,0 +1,140 @@
+use super::post_pipeline::{PostEffects, PostPass};
+
+// Same as the constants in the post shaders
+const BLOOM_THRESHOLD: f32 = 0.6;
+const BLOOM_STRENGTH: f32 = 0.8;
+const BLOOM_TAPS: i32 = 6;
+const BLOOM_TAP_SPACING: f32 = 2.0;
+const MAX_ABERRATION: f32 = 0.012;
+const CRT_CURVATURE: f32 = 0.06;
+const SCANLINE_STRENGTH: f32 = 0.25;
+const VIGNETTE_STRENGTH: f32 = 0.35;
+
+// A frame of linear colors the passes read from
+struct Frame<'a> {
+    pixels: &'a [[f32; 4]],
+    width: u32,
+    height: u32,
+}
+
+impl Frame<'_> {
+    // Bilinear and clamped to the edge, like the post sampler
+    fn sample(&self, uv: glam::Vec2) -> glam::Vec3 {
+        let x = (uv.x * self.width as f32 - 0.5).clamp(0.0, (self.width - 1) as f32);
+        let y = (uv.y * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+        let texel = |x: u32, y: u32| {
+            let [r, g, b, _] = self.pixels[(y * self.width + x) as usize];
+            glam::vec3(r, g, b)
+        };
+        let top = texel(x0, y0).lerp(texel(x1, y0), fx);
+        let bottom = texel(x0, y1).lerp(texel(x1, y1), fx);
+        top.lerp(bottom, fy)
+    }
+}
+
+// Runs the enabled passes over a drawn frame in the same order as the GPU does
+pub(super) fn apply(
+    pixels: Vec<[f32; 4]>,
+    width: u32,
+    height: u32,
+    effects: &PostEffects,
+    damage: f32,
+) -> Vec<[f32; 4]> {
+    let scene = pixels;
+    let mut source = None;
+    for pass in PostPass::ALL {
+        if !pass.enabled(effects, damage) {
+            continue;
+        }
+        let scene_frame = Frame {
+            pixels: &scene,
+            width,
+            height,
+        };
+        let input = Frame {
+            pixels: source.as_deref().unwrap_or(&scene),
+            width,
+            height,
+        };
+        let resolution = glam::vec2(width as f32, height as f32);
+        let output = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let uv = (glam::vec2(x as f32, y as f32) + glam::Vec2::splat(0.5)) / resolution;
+                let color = match pass {
+                    PostPass::BloomBlur => bloom_blur(&input, uv, resolution),
+                    PostPass::BloomComposite => {
+                        bloom_composite(&input, &scene_frame, uv, resolution)
+                    }
+                    PostPass::ChromaticAberration => chromatic_aberration(&input, uv, damage),
+                    PostPass::Crt => crt(&input, uv, resolution),
+                };
+                [color.x, color.y, color.z, 1.0]
+            })
+            .collect();
+        source = Some(output);
+    }
+    source.unwrap_or(scene)
+}
+
+fn blur(
+    frame: &Frame,
+    uv: glam::Vec2,
+    texel: glam::Vec2,
+    filter: impl Fn(glam::Vec3) -> glam::Vec3,
+) -> glam::Vec3 {
+    let mut sum = glam::Vec3::ZERO;
+    let mut weight_sum = 0.0;
+    for i in -BLOOM_TAPS..=BLOOM_TAPS {
+        let weight = (-((i * i) as f32) / 18.0).exp();
+        sum += filter(frame.sample(uv + texel * i as f32)) * weight;
+        weight_sum += weight;
+    }
+    sum / weight_sum
+}
+
+fn bloom_blur(frame: &Frame, uv: glam::Vec2, resolution: glam::Vec2) -> glam::Vec3 {
+    let texel = glam::vec2(BLOOM_TAP_SPACING / resolution.x, 0.0);
+    blur(frame, uv, texel, |color| {
+        let luminance = color.dot(glam::vec3(0.2126, 0.7152, 0.0722));
+        color * (luminance - BLOOM_THRESHOLD).max(0.0) / luminance.max(0.0001)
+    })
+}
+
+fn bloom_composite(
+    frame: &Frame,
+    scene: &Frame,
+    uv: glam::Vec2,
+    resolution: glam::Vec2,
+) -> glam::Vec3 {
+    let texel = glam::vec2(0.0, BLOOM_TAP_SPACING / resolution.y);
+    scene.sample(uv) + blur(frame, uv, texel, |color| color) * BLOOM_STRENGTH
+}
+
+fn chromatic_aberration(frame: &Frame, uv: glam::Vec2, damage: f32) -> glam::Vec3 {
+    let offset = (uv - glam::Vec2::splat(0.5)) * 2.0 * MAX_ABERRATION * damage;
+    glam::vec3(
+        frame.sample(uv + offset).x,
+        frame.sample(uv).y,
+        frame.sample(uv - offset).z,
+    )
+}
+
+fn crt(frame: &Frame, uv: glam::Vec2, resolution: glam::Vec2) -> glam::Vec3 {
+    let mut curved = uv * 2.0 - glam::Vec2::ONE;
+    let swapped = glam::vec2(curved.y, curved.x);
+    curved += curved * swapped * swapped * CRT_CURVATURE;
+    let coords = curved * 0.5 + glam::Vec2::splat(0.5);
+    if coords.x < 0.0 || coords.x > 1.0 || coords.y < 0.0 || coords.y > 1.0 {
+        return glam::Vec3::ZERO;
+    }
+
+    let scanline = 1.0
+        - SCANLINE_STRENGTH * (0.5 + 0.5 * (coords.y * resolution.y * std::f32::consts::PI).sin());
+    let vignette = 1.0 - VIGNETTE_STRENGTH * curved.dot(curved) * 0.5;
+    frame.sample(coords) * scanline * vignette
+}
@@ -0,0 +1,465 @@
+use image::{Rgba, RgbaImage};
+use winit::dpi::PhysicalSize;
+
+use super::{
+    software_post, HudQuad, PostEffects, Renderer, RendererError, SpriteRenderer,
+    STATUS_FLAG_SLOW_MO,
+};
+
+struct Atlas {
+    image: RgbaImage,
+    cell_size: (u32, u32),
+}
+
+impl Atlas {
+    fn from_bytes(bytes: &[u8], cell_size: (u32, u32)) -> Result<Self, RendererError> {
+        let image = image::load_from_memory(bytes)
+            .map_err(RendererError::Atlas)?
+            .to_rgba8();
+        Ok(Self { image, cell_size })
+    }
+
+    // Nearest texel of a cell, u and v go from 0 to 1 across the cell like the shaders' tex coords
+    fn texel(&self, sprite_idx: u32, u: f32, v: f32) -> &Rgba<u8> {
+        let (cell_width, cell_height) = self.cell_size;
+        let columns = (self.image.width() / cell_width).max(1);
+        let cell_x = sprite_idx % columns;
+        let cell_y = sprite_idx / columns;
+
+        let x = cell_x * cell_width
+            + ((u.clamp(0.0, 1.0) * cell_width as f32) as u32).min(cell_width - 1);
+        let y = cell_y * cell_height
+            + ((v.clamp(0.0, 1.0) * cell_height as f32) as u32).min(cell_height - 1);
+        // Clamp to edge like the GPU sampler does
+        self.image.get_pixel(
+            x.min(self.image.width() - 1),
+            y.min(self.image.height() - 1),
+        )
+    }
+}
+
+struct Instance {
+    sprite_idx: u32,
+    model_matrix: glam::Mat4,
+    tint: [f32; 4],
+}
+
+// Draws the same frame as the wgpu renderer into an image, so it runs anywhere. Colors are blended
+// in linear space and stored as sRGB, matching the sRGB textures and surface on the GPU.
+pub struct SoftwareRenderer {
+    size: PhysicalSize<u32>,
+    sprite_atlas: Atlas,
+    background_atlas: Atlas,
+    background_idx: u32,
+    status_flags: u32,
+    instances: Vec<Instance>,
+    particles: Vec<Instance>,
+    hud_quads: Vec<HudQuad>,
+    post_effects: PostEffects,
+    damage: f32,
+    camera_offset: glam::Vec2,
+    // Linear colors of the frame being drawn
+    pixels: Vec<[f32; 4]>,
+    frame: RgbaImage,
+    srgb_to_linear: [f32; 256],
+}
+
+impl SoftwareRenderer {
+    pub fn new(
+        size: PhysicalSize<u32>,
+        sprite_atlas_data: &[u8],
+        sprite_size: (u32, u32),
+        background_atlas_data: &[u8],
+        background_size: (u32, u32),
+    ) -> Result<Self, RendererError> {
+        let mut srgb_to_linear = [0.0; 256];
+        for (value, linear) in srgb_to_linear.iter_mut().enumerate() {
+            *linear = decode_srgb(value as f32 / 255.0);
+        }
+
+        Ok(Self {
+            size,
+            sprite_atlas: Atlas::from_bytes(sprite_atlas_data, sprite_size)?,
+            background_atlas: Atlas::from_bytes(background_atlas_data, background_size)?,
+            background_idx: 0,
+            status_flags: 0,
+            instances: Vec::new(),
+            particles: Vec::new(),
+            hud_quads: Vec::new(),
+            post_effects: PostEffects::default(),
+            damage: 0.0,
+            camera_offset: glam::Vec2::ZERO,
+            pixels: Vec::new(),
+            frame: RgbaImage::new(size.width, size.height),
+            srgb_to_linear,
+        })
+    }
+
+    // The last drawn frame
+    pub fn frame(&self) -> &RgbaImage {
+        &self.frame
+    }
+
+    fn to_linear(&self, texel: &Rgba<u8>) -> [f32; 4] {
+        let [r, g, b, a] = texel.0;
+        [
+            self.srgb_to_linear[r as usize],
+            self.srgb_to_linear[g as usize],
+            self.srgb_to_linear[b as usize],
+            a as f32 / 255.0,
+        ]
+    }
+
+    fn render(&mut self) -> RgbaImage {
+        self.pixels.clear();
+        self.pixels.resize(
+            (self.size.width * self.size.height) as usize,
+            [0.0, 0.0, 0.0, 1.0],
+        );
+        self.draw_background();
+        let particles = std::mem::take(&mut self.particles);
+        let instances = std::mem::take(&mut self.instances);
+        for instance in particles.iter().chain(instances.iter()) {
+            self.draw_sprite(instance);
+        }
+        self.particles = particles;
+        self.instances = instances;
+        self.pixels = software_post::apply(
+            std::mem::take(&mut self.pixels),
+            self.size.width,
+            self.size.height,
+            &self.post_effects,
+            self.damage,
+        );
+        let hud_quads = std::mem::take(&mut self.hud_quads);
+        for quad in &hud_quads {
+            self.draw_hud_quad(quad);
+        }
+        self.hud_quads = hud_quads;
+
+        let width = self.size.width;
+        RgbaImage::from_fn(width, self.size.height, |x, y| {
+            let [r, g, b, a] = self.pixels[(y * width + x) as usize];
+            Rgba([
+                to_byte(encode_srgb(r)),
+                to_byte(encode_srgb(g)),
+                to_byte(encode_srgb(b)),
+                to_byte(a),
+            ])
+        })
+    }
+
+    fn draw_background(&mut self) {
+        let (width, height) = (self.size.width, self.size.height);
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                let color = self.background_color(u, v);
+                self.pixels[(y * width + x) as usize] = color;
+            }
+        }
+    }
+
+    fn background_color(&self, u: f32, v: f32) -> [f32; 4] {
+        let mut color = self.to_linear(self.background_atlas.texel(self.background_idx, u, v));
+        if self.status_flags & STATUS_FLAG_SLOW_MO != 0 {
+            let luminance = 0.299 * color[0] + 0.587 * color[1] + 0.114 * color[2];
+            let washed = [luminance * 0.6, luminance * 0.8, luminance];
+            for channel in 0..3 {
+                color[channel] += (washed[channel] - color[channel]) * 0.7;
+            }
+        }
+        color
+    }
+
+    fn draw_sprite(&mut self, instance: &Instance) {
+        let (width, height) = (self.size.width as f32, self.size.height as f32);
+        let view_proj = Renderer::calc_ortho_matrix(self.size)
+            * glam::Mat4::from_translation(self.camera_offset.extend(0.0))
+            * instance.model_matrix;
+        // The sprite quad spans -1..1 in model space, this takes it straight to pixels
+        let to_pixel = |x: f32, y: f32| {
+            let clip = view_proj * glam::vec4(x, y, 0.0, 1.0);
+            glam::vec2((clip.x + 1.0) * 0.5 * width, (1.0 - clip.y) * 0.5 * height)
+        };
+        let origin = to_pixel(0.0, 0.0);
+        let x_axis = to_pixel(1.0, 0.0) - origin;
+        let y_axis = to_pixel(0.0, 1.0) - origin;
+        let inverse = glam::Mat2::from_cols(x_axis, y_axis);
+        if inverse.determinant().abs() < f32::EPSILON {
+            return;
+        }
+        let inverse = inverse.inverse();
+
+        let corners = [
+            to_pixel(-1.0, -1.0),
+            to_pixel(1.0, -1.0),
+            to_pixel(1.0, 1.0),
+            to_pixel(-1.0, 1.0),
+        ];
+        let min = corners
+            .iter()
+            .fold(glam::Vec2::splat(f32::MAX), |min, c| min.min(*c));
+        let max = corners
+            .iter()
+            .fold(glam::Vec2::splat(f32::MIN), |max, c| max.max(*c));
+        let x_range = min.x.max(0.0).floor() as u32..(max.x.min(width).ceil() as u32);
+        let y_range = min.y.max(0.0).floor() as u32..(max.y.min(height).ceil() as u32);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let local = inverse * (glam::vec2(x as f32 + 0.5, y as f32 + 0.5) - origin);
+                if local.x.abs() > 1.0 || local.y.abs() > 1.0 {
+                    continue;
+                }
+                let texel = self.sprite_atlas.texel(
+                    instance.sprite_idx,
+                    (local.x + 1.0) * 0.5,
+                    (1.0 - local.y) * 0.5,
+                );
+                let mut source = self.to_linear(texel);
+                for (channel, tint) in source.iter_mut().zip(instance.tint.iter()) {
+                    *channel *= tint;
+                }
+
+                self.blend(x, y, source);
+            }
+        }
+    }
+
+    // Pixels whose centers fall in the quad, like the GPU rasterizes it
+    fn draw_hud_quad(&mut self, quad: &HudQuad) {
+        let (width, height) = (self.size.width as f32, self.size.height as f32);
+        let min = glam::Vec2::from(quad.position);
+        let size = glam::Vec2::from(quad.size);
+        let max = min + size;
+        let x_range =
+            (min.x - 0.5).max(0.0).ceil() as u32..((max.x - 0.5).min(width).ceil() as u32);
+        let y_range =
+            (min.y - 0.5).max(0.0).ceil() as u32..((max.y - 0.5).min(height).ceil() as u32);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let mut source = if quad.sprite_idx == HudQuad::SOLID {
+                    [1.0; 4]
+                } else {
+                    let local = (glam::vec2(x as f32 + 0.5, y as f32 + 0.5) - min) / size;
+                    self.to_linear(self.sprite_atlas.texel(quad.sprite_idx, local.x, local.y))
+                };
+                for (channel, color) in source.iter_mut().zip(quad.color.iter()) {
+                    *channel *= color;
+                }
+                self.blend(x, y, source);
+            }
+        }
+    }
+
+    // Same as wgpu's ALPHA_BLENDING
+    fn blend(&mut self, x: u32, y: u32, source: [f32; 4]) {
+        let destination = &mut self.pixels[(y * self.size.width + x) as usize];
+        let alpha = source[3];
+        for channel in 0..3 {
+            destination[channel] = source[channel] * alpha + destination[channel] * (1.0 - alpha);
+        }
+        destination[3] = alpha + destination[3] * (1.0 - alpha);
+    }
+}
+
+impl SpriteRenderer for SoftwareRenderer {
+    fn on_resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+        }
+    }
+
+    fn set_background_state(&mut self, sprite_idx: u32, status_flags: u32) {
+        self.background_idx = sprite_idx;
+        self.status_flags = status_flags;
+    }
+
+    fn add_sprite_instance(
+        &mut self,
+        sprite_idx: u32,
+        model_matrix: [[f32; 4]; 4],
+        tint: [f32; 4],
+    ) {
+        self.instances.push(Instance {
+            sprite_idx,
+            model_matrix: glam::Mat4::from_cols_array_2d(&model_matrix),
+            tint,
+        });
+    }
+
+    fn add_particle_instance(
+        &mut self,
+        sprite_idx: u32,
+        model_matrix: [[f32; 4]; 4],
+        tint: [f32; 4],
+    ) {
+        self.particles.push(Instance {
+            sprite_idx,
+            model_matrix: glam::Mat4::from_cols_array_2d(&model_matrix),
+            tint,
+        });
+    }
+
+    fn add_hud_quad(&mut self, quad: HudQuad) {
+        self.hud_quads.push(quad);
+    }
+
+    fn set_post_effects(&mut self, effects: PostEffects, damage: f32) {
+        self.post_effects = effects;
+        self.damage = damage;
+    }
+
+    fn set_camera_offset(&mut self, offset: glam::Vec2) {
+        self.camera_offset = offset;
+    }
+
+    // Nothing is presented
+    fn set_vsync(&mut self, _vsync: bool) {}
+
+    fn draw(&mut self) {
+        self.frame = self.render();
+        self.instances.clear();
+        self.particles.clear();
+        self.hud_quads.clear();
+    }
+
+    fn capture(&mut self) -> Result<RgbaImage, RendererError> {
+        Ok(self.render())
+    }
+}
+
+fn decode_srgb(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn encode_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use winit::dpi::PhysicalSize;
+
+    use super::SoftwareRenderer;
+    use crate::{
+        assets::AssetSource,
+        renderer::{HudQuad, PostEffects, SpriteRenderer},
+    };
+
+    // Set UPDATE_GOLDEN=1 to rewrite this after an intended change to how frames look
+    const GOLDEN_FRAME: &str = "src/renderer/testdata/software_frame.png";
+
+    fn model_matrix(position: glam::Vec2, scale: f32, angle: f32) -> [[f32; 4]; 4] {
+        (glam::Mat4::from_translation(glam::vec3(position.x, position.y, -1.0))
+            * glam::Mat4::from_scale(glam::vec3(scale, scale, 1.0))
+            * glam::Mat4::from_rotation_z(angle))
+        .to_cols_array_2d()
+    }
+
+    fn renderer() -> SoftwareRenderer {
+        let assets = AssetSource::embedded();
+        SoftwareRenderer::new(
+            PhysicalSize::new(320, 200),
+            &assets.load("spritesheet.png").unwrap(),
+            (64, 64),
+            &assets.load("backgrounds.png").unwrap(),
+            (320, 200),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_capture_is_the_next_frame() {
+        let mut renderer = renderer();
+        renderer.add_sprite_instance(
+            4,
+            model_matrix(glam::vec2(10.0, -20.0), 32.0, 0.3),
+            [1.0; 4],
+        );
+        let capture = renderer.capture().unwrap();
+        renderer.draw();
+        assert_eq!(&capture, renderer.frame());
+    }
+
+    #[test]
+    fn test_frame_matches_golden() {
+        let mut renderer = renderer();
+
+        renderer.set_background_state(1, 0);
+        renderer.add_sprite_instance(0, model_matrix(glam::vec2(0.0, 0.0), 32.0, 0.0), [1.0; 4]);
+        renderer.add_sprite_instance(
+            2,
+            model_matrix(glam::vec2(-90.0, -40.0), 24.0, 0.6),
+            [1.0, 0.5, 0.5, 1.0],
+        );
+        renderer.add_sprite_instance(
+            3,
+            model_matrix(glam::vec2(20.0, 10.0), 40.0, -1.2),
+            [1.0, 1.0, 1.0, 0.5],
+        );
+        renderer.add_sprite_instance(
+            1,
+            model_matrix(glam::vec2(150.0, 90.0), 16.0, 0.0),
+            [1.0; 4],
+        );
+        renderer.add_hud_quad(HudQuad::solid(
+            glam::vec2(8.0, 8.0),
+            glam::vec2(120.0, 6.0),
+            [1.0, 0.2, 0.2, 1.0],
+        ));
+        renderer.add_hud_quad(HudQuad::solid(
+            glam::vec2(8.5, 16.5),
+            glam::vec2(60.0, 6.0),
+            [0.2, 0.2, 1.0, 0.5],
+        ));
+        renderer.add_hud_quad(HudQuad::sprite(
+            0,
+            glam::vec2(280.0, 160.0),
+            glam::vec2(24.0, 24.0),
+            [1.0; 4],
+        ));
+        renderer.set_post_effects(
+            PostEffects {
+                bloom: true,
+                crt: true,
+                chromatic_aberration: true,
+            },
+            0.5,
+        );
+        renderer.draw();
+        let frame = renderer.frame();
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_FRAME);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            frame.save(&path).unwrap();
+        }
+        let golden = image::open(&path).unwrap().to_rgba8();
+
+        assert_eq!(frame.dimensions(), golden.dimensions());
+        // Leave a little room for float differences between platforms
+        for (actual, expected) in frame.pixels().zip(golden.pixels()) {
+            for (a, e) in actual.0.iter().zip(expected.0.iter()) {
+                assert!((*a as i16 - *e as i16).abs() <= 1);
+            }
+        }
+    }
+}
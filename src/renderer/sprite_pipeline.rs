@@ -35,6 +35,7 @@ impl Vertex {
 pub struct Instance {
     pub model_matrix: [[f32; 4]; 4],
     pub sprite_idx: u32,
+    pub tint: [f32; 4],
 }
 
 impl Instance {
@@ -69,6 +70,12 @@ impl Instance {
                     shader_location: 14,
                     format: wgpu::VertexFormat::Uint32,
                 },
+                wgpu::VertexAttribute {
+                    offset: (mem::size_of::<[f32; 16]>() + mem::size_of::<u32>())
+                        as wgpu::BufferAddress,
+                    shader_location: 15,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    input::InputBindings,
+    resource::{KeyboardEvent, MouseEvent},
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    Keyboard(KeyboardEvent),
+    Mouse(MouseEvent),
+    Resize(u32, u32),
+}
+
+// Everything needed to play a run back exactly: the starting conditions and the raw input
+// that came in on every tick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub tick_rate: u32,
+    pub window_size: (u32, u32),
+    pub bindings: InputBindings,
+    pub events: Vec<(u64, RecordedEvent)>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = std::fs::read_to_string(path)?;
+        Ok(ron::from_str(&data)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let data = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+
+    // How many ticks it takes for all of the recorded input to play out
+    pub fn length(&self) -> u64 {
+        self.events.last().map_or(0, |(tick, _)| tick + 1)
+    }
+}
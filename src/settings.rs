@@ -0,0 +1,273 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use winit::{
+    dpi::PhysicalSize,
+    window::{Fullscreen, Window},
+};
+
+use crate::{assets::load_ron_or_default, game::input::InputBindings, renderer::PostEffects};
+
+const APP_DIRECTORY: &str = "unstable-orbit";
+const SETTINGS_FILE: &str = "settings.ron";
+
+// What the settings screen steps through
+pub const RESOLUTIONS: [(u32, u32); 6] = [
+    (960, 540),
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ArgEnum)]
+pub enum WindowMode {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [
+        WindowMode::Windowed,
+        WindowMode::Borderless,
+        WindowMode::Fullscreen,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "windowed",
+            WindowMode::Borderless => "borderless",
+            WindowMode::Fullscreen => "fullscreen",
+        }
+    }
+}
+
+// Everything the player can change from the settings screen, kept in a file per user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Window size in physical pixels, also the video mode in exclusive fullscreen
+    pub resolution: (u32, u32),
+    pub window_mode: WindowMode,
+    pub vsync: bool,
+    // From 0 to 1, effects and music are scaled by the master volume
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    // From 0 to 1, 0 turns it off
+    pub screen_shake: f32,
+    pub post_effects: PostEffects,
+    // Only set once keys were rebound in the game, the input config file is used until then
+    pub bindings: Option<InputBindings>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            resolution: (1280, 720),
+            window_mode: WindowMode::Windowed,
+            vsync: true,
+            master_volume: 0.8,
+            sfx_volume: 1.0,
+            music_volume: 0.7,
+            screen_shake: 1.0,
+            post_effects: PostEffects::default(),
+            bindings: None,
+        }
+    }
+}
+
+// Settings from the command line. They last the session and stay out of the settings file.
+#[derive(Debug, Clone, Default)]
+pub struct SettingsOverrides {
+    pub resolution: Option<(u32, u32)>,
+    pub window_mode: Option<WindowMode>,
+    pub vsync: Option<bool>,
+    pub bloom: Option<bool>,
+    pub crt: Option<bool>,
+    pub chromatic_aberration: Option<bool>,
+}
+
+impl SettingsOverrides {
+    // What the session starts with, the file's settings with the flags on top
+    pub fn apply(&self, settings: &Settings) -> Settings {
+        let effects = settings.post_effects;
+        Settings {
+            resolution: self.resolution.unwrap_or(settings.resolution),
+            window_mode: self.window_mode.unwrap_or(settings.window_mode),
+            vsync: self.vsync.unwrap_or(settings.vsync),
+            post_effects: PostEffects {
+                bloom: self.bloom.unwrap_or(effects.bloom),
+                crt: self.crt.unwrap_or(effects.crt),
+                chromatic_aberration: self
+                    .chromatic_aberration
+                    .unwrap_or(effects.chromatic_aberration),
+            },
+            ..settings.clone()
+        }
+    }
+}
+
+impl Settings {
+    // The config directory for the current user, None without a home directory to put it in
+    pub fn user_path() -> Option<PathBuf> {
+        let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+        let config_dir = if cfg!(windows) {
+            var("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            var("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+        } else {
+            var("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".config")))
+        };
+        config_dir.map(|dir| dir.join(APP_DIRECTORY).join(SETTINGS_FILE))
+    }
+
+    // Defaults if the file is missing or broken
+    pub fn load_or_default(path: &Path) -> Self {
+        load_ron_or_default(path)
+    }
+
+    // Takes what changed between two versions of the session's settings, so a setting the
+    // player didn't touch keeps its value from the file even while a flag overrides it
+    pub fn merge_changes(&mut self, before: &Settings, after: &Settings) {
+        fn take<T: PartialEq + Clone>(saved: &mut T, before: &T, after: &T) {
+            if after != before {
+                *saved = after.clone();
+            }
+        }
+        take(&mut self.resolution, &before.resolution, &after.resolution);
+        take(
+            &mut self.window_mode,
+            &before.window_mode,
+            &after.window_mode,
+        );
+        take(&mut self.vsync, &before.vsync, &after.vsync);
+        take(
+            &mut self.master_volume,
+            &before.master_volume,
+            &after.master_volume,
+        );
+        take(&mut self.sfx_volume, &before.sfx_volume, &after.sfx_volume);
+        take(
+            &mut self.music_volume,
+            &before.music_volume,
+            &after.music_volume,
+        );
+        take(
+            &mut self.screen_shake,
+            &before.screen_shake,
+            &after.screen_shake,
+        );
+        take(&mut self.bindings, &before.bindings, &after.bindings);
+        // One effect can be overridden while another is switched on the settings screen
+        let (effects, before, after) = (
+            &mut self.post_effects,
+            &before.post_effects,
+            &after.post_effects,
+        );
+        take(&mut effects.bloom, &before.bloom, &after.bloom);
+        take(&mut effects.crt, &before.crt, &after.crt);
+        take(
+            &mut effects.chromatic_aberration,
+            &before.chromatic_aberration,
+            &after.chromatic_aberration,
+        );
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let data = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+
+    pub fn window_size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.resolution.0, self.resolution.1)
+    }
+
+    // Exclusive fullscreen takes the monitor's video mode closest to the resolution
+    pub fn fullscreen(&self, window: &Window) -> Option<Fullscreen> {
+        match self.window_mode {
+            WindowMode::Windowed => None,
+            WindowMode::Borderless => Some(Fullscreen::Borderless(None)),
+            WindowMode::Fullscreen => window
+                .current_monitor()
+                .and_then(|monitor| {
+                    let (width, height) = self.resolution;
+                    monitor.video_modes().min_by_key(|mode| {
+                        let size = mode.size();
+                        let distance = (size.width as i64 - width as i64).abs()
+                            + (size.height as i64 - height as i64).abs();
+                        (distance, std::cmp::Reverse(mode.refresh_rate()))
+                    })
+                })
+                .map(Fullscreen::Exclusive),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Settings, SettingsOverrides, WindowMode};
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("ld49-settings-{}", std::process::id()))
+            .join("settings.ron");
+        let settings = Settings {
+            resolution: (1920, 1080),
+            window_mode: WindowMode::Borderless,
+            vsync: false,
+            music_volume: 0.2,
+            ..Default::default()
+        };
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load_or_default(&path), settings);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        // Files from older versions miss the newer fields
+        let partial: Settings = ron::from_str("(vsync: false)").unwrap();
+        assert!(!partial.vsync);
+        assert_eq!(partial.resolution, Settings::default().resolution);
+    }
+
+    #[test]
+    fn test_overrides_are_not_saved() {
+        let path = std::env::temp_dir()
+            .join(format!("ld49-overrides-{}", std::process::id()))
+            .join("settings.ron");
+        let file = Settings::default();
+        let overrides = SettingsOverrides {
+            resolution: Some((800, 600)),
+            crt: Some(true),
+            ..Default::default()
+        };
+        let session = overrides.apply(&file);
+        assert!(session.post_effects.crt);
+
+        // The player turns the music down on the settings screen
+        let changed = Settings {
+            music_volume: 0.3,
+            ..session.clone()
+        };
+        let mut saved = file.clone();
+        saved.merge_changes(&session, &changed);
+        saved.save(&path).unwrap();
+
+        let loaded = Settings::load_or_default(&path);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(loaded.music_volume, 0.3);
+        assert!(!loaded.post_effects.crt);
+        assert_eq!(loaded.resolution, file.resolution);
+    }
+}
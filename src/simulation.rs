@@ -0,0 +1,442 @@
+use std::{error::Error, time::Duration};
+
+use specs::{shrev::EventChannel, Dispatcher, DispatcherBuilder, RunNow, World, WorldExt};
+
+use crate::{
+    assets::AssetSource,
+    audio::AudioOutput,
+    game::{
+        enemy::EnemyRoster,
+        input::{InputBindings, InputSource},
+        particles::ParticlePresets,
+        resource::{
+            DeltaTime, GameRng, GameWindowSize, KeyboardEvent, MouseEvent, Paused, TimeScale,
+            UnscaledDeltaTime, WindowEvent,
+        },
+        save::{load_world, save_world},
+        system::{
+            AsteroidSpawnerSystem, AudioSystem, AutopilotSystem, BossSystem, EnemyAiSystem,
+            EnemySpawnerSystem, EntityLifetimeSystem, EntitySpinnerSystem, GameManagerSystem,
+            InputSystem, MenuSystem, ParticleSystem, PickupSystem, PlayerBoundsEnforcerSystem,
+            PlayerCollisionSystem, PlayerMovementSystem, PlayerWeaponSystem, ProjectileSystem,
+            StatusEffectSystem, VelocityApplicatorSystem, WaveSchedulerSystem,
+        },
+        wave::WaveSchedule,
+    },
+    replay::{RecordedEvent, Replay},
+};
+
+// The game world and everything that updates it, without a window or renderer attached
+pub struct Simulation<'a> {
+    world: World,
+    autopilots: Vec<AutopilotSystem>,
+    input_system: InputSystem,
+    menu_system: MenuSystem,
+    update_dispatcher: Dispatcher<'a, 'a>,
+    // Runs after the update systems, even while paused
+    audio_system: AudioSystem,
+    seed: u64,
+    tick_count: u64,
+    recording: Option<Replay>,
+    playback: Option<(Replay, usize)>,
+}
+
+impl<'a> Simulation<'a> {
+    pub fn new(window_size: (u32, u32), input_sources: Vec<Box<dyn InputSource>>) -> Self {
+        let mut world = World::new();
+        let mut update_dispatcher = DispatcherBuilder::new()
+            .with(GameManagerSystem::default(), "game_manager_system", &[])
+            .with(
+                WaveSchedulerSystem::default(),
+                "wave_scheduler_system",
+                &["game_manager_system"],
+            )
+            .with(
+                AsteroidSpawnerSystem::default(),
+                "asteroid_spawner_system",
+                &["wave_scheduler_system"],
+            )
+            .with(
+                EnemySpawnerSystem::default(),
+                "enemy_spawner_system",
+                &["game_manager_system"],
+            )
+            .with(
+                PlayerWeaponSystem::default(),
+                "player_weapon_system",
+                &["game_manager_system"],
+            )
+            .with(
+                ProjectileSystem::default(),
+                "projectile_system",
+                &["player_weapon_system"],
+            )
+            .with(
+                EnemyAiSystem::default(),
+                "enemy_ai_system",
+                &["enemy_spawner_system", "projectile_system"],
+            )
+            .with(BossSystem::default(), "boss_system", &["projectile_system"])
+            .with(
+                PlayerMovementSystem::default(),
+                "player_movement_system",
+                &["game_manager_system"],
+            )
+            .with(
+                PlayerCollisionSystem,
+                "player_collision_system",
+                &["game_manager_system"],
+            )
+            .with(
+                VelocityApplicatorSystem::default(),
+                "velocity_applicator",
+                &[],
+            )
+            .with(PlayerBoundsEnforcerSystem, "bounds_keeper", &[])
+            .with(
+                EntityLifetimeSystem::default(),
+                "entity_lifetime_system",
+                &[],
+            )
+            .with(EntitySpinnerSystem::default(), "entity_spinner_system", &[])
+            .with(
+                PickupSystem::default(),
+                "pickup_system",
+                &["game_manager_system"],
+            )
+            .with(
+                ParticleSystem::default(),
+                "particle_system",
+                &[
+                    "game_manager_system",
+                    "player_movement_system",
+                    "player_collision_system",
+                    "pickup_system",
+                ],
+            )
+            .with(
+                StatusEffectSystem::default(),
+                "status_effect_system",
+                &[
+                    "pickup_system",
+                    "player_collision_system",
+                    "player_movement_system",
+                ],
+            )
+            .build();
+
+        let mut input_system = InputSystem::new(input_sources);
+        RunNow::setup(&mut input_system, &mut world);
+        let mut menu_system = MenuSystem::default();
+        RunNow::setup(&mut menu_system, &mut world);
+        update_dispatcher.setup(&mut world);
+        let mut audio_system = AudioSystem::default();
+        RunNow::setup(&mut audio_system, &mut world);
+        // Normally read by the render system, which is not part of the simulation
+        world.insert(EventChannel::<WindowEvent>::new());
+
+        {
+            // Setup for the first time, since render system is not called before all the updates
+            let mut game_window_size = world.write_resource::<GameWindowSize>();
+            *game_window_size = GameWindowSize(window_size.0, window_size.1);
+        }
+
+        let mut simulation = Self {
+            world,
+            autopilots: Vec::new(),
+            input_system,
+            menu_system,
+            update_dispatcher,
+            audio_system,
+            seed: 0,
+            tick_count: 0,
+            recording: None,
+            playback: None,
+        };
+        simulation.set_seed(rand::random());
+        simulation
+    }
+
+    // Sound goes nowhere until this is called
+    pub fn set_audio_output(&mut self, output: impl AudioOutput + 'static) {
+        self.audio_system = AudioSystem::new(output);
+        RunNow::setup(&mut self.audio_system, &mut self.world);
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.world.insert(GameRng::seeded(seed));
+    }
+
+    pub fn load_assets(&mut self, assets: &AssetSource) -> Result<(), Box<dyn Error>> {
+        let waves = WaveSchedule::from_ron_str(&assets.load_string("waves.ron")?)?;
+        let enemies = EnemyRoster::from_ron_str(&assets.load_string("enemies.ron")?)?;
+        let particles = ParticlePresets::from_ron_str(&assets.load_string("particles.ron")?)?;
+        self.world.insert(waves);
+        self.world.insert(enemies);
+        self.world.insert(particles);
+        Ok(())
+    }
+
+    pub fn save(&mut self) -> Result<String, ron::Error> {
+        save_world(&mut self.world)
+    }
+
+    pub fn load(&mut self, data: &str) -> Result<(), ron::Error> {
+        load_world(&mut self.world, data)
+    }
+
+    pub fn tick_count(&self) -> u64 {
+        self.tick_count
+    }
+
+    // Keeps every bit of outside input from here on, along with what is needed to start over
+    // from the same spot
+    pub fn start_recording(&mut self, tick_rate: u32) {
+        let window_size = *self.world.read_resource::<GameWindowSize>();
+        self.recording = Some(Replay {
+            seed: self.seed,
+            tick_rate,
+            window_size: (window_size.0, window_size.1),
+            bindings: (*self.world.read_resource::<InputBindings>()).clone(),
+            events: Vec::new(),
+        });
+    }
+
+    pub fn take_recording(&mut self) -> Option<Replay> {
+        self.recording.take()
+    }
+
+    // Resets the starting conditions to the ones in the replay and feeds its input back in on
+    // the same ticks. Should be called before the first tick.
+    pub fn play_replay(&mut self, replay: Replay) {
+        self.set_seed(replay.seed);
+        self.world.insert(replay.bindings.clone());
+        self.resize(replay.window_size.0, replay.window_size.1);
+        self.playback = Some((replay, 0));
+    }
+
+    pub fn is_replay_finished(&self) -> bool {
+        match &self.playback {
+            Some((replay, _)) => self.tick_count >= replay.length(),
+            None => true,
+        }
+    }
+
+    pub fn send_keyboard(&mut self, event: KeyboardEvent) {
+        self.record(RecordedEvent::Keyboard(event.clone()));
+        self.world
+            .fetch_mut::<EventChannel<KeyboardEvent>>()
+            .single_write(event);
+    }
+
+    pub fn send_mouse(&mut self, event: MouseEvent) {
+        self.record(RecordedEvent::Mouse(event.clone()));
+        self.world
+            .fetch_mut::<EventChannel<MouseEvent>>()
+            .single_write(event);
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.record(RecordedEvent::Resize(width, height));
+        // Set directly as well since there might not be a render system around to do it
+        *self.world.write_resource::<GameWindowSize>() = GameWindowSize(width, height);
+        self.world
+            .fetch_mut::<EventChannel<WindowEvent>>()
+            .single_write(WindowEvent::Resize(width, height));
+    }
+
+    fn record(&mut self, event: RecordedEvent) {
+        if let Some(recording) = &mut self.recording {
+            recording.events.push((self.tick_count, event));
+        }
+    }
+
+    fn play_back_events(&mut self) {
+        let (replay, next) = match &mut self.playback {
+            Some(playback) => playback,
+            None => return,
+        };
+
+        let mut events = Vec::new();
+        while let Some((tick, event)) = replay.events.get(*next) {
+            if *tick > self.tick_count {
+                break;
+            }
+            events.push(event.clone());
+            *next += 1;
+        }
+
+        for event in events {
+            match event {
+                RecordedEvent::Keyboard(event) => self.send_keyboard(event),
+                RecordedEvent::Mouse(event) => self.send_mouse(event),
+                RecordedEvent::Resize(width, height) => self.resize(width, height),
+            }
+        }
+    }
+
+    // Lets a bot fly the given player slot, it types on the same keyboard as everyone else
+    pub fn add_autopilot(&mut self, slot: usize) {
+        let mut autopilot = AutopilotSystem::new(slot);
+        RunNow::setup(&mut autopilot, &mut self.world);
+        self.autopilots.push(autopilot);
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    pub fn tick(&mut self, delta_time: Duration) {
+        {
+            let time_scale = self.world.read_resource::<TimeScale>().0;
+            let mut delta = self.world.write_resource::<DeltaTime>();
+            *delta = DeltaTime(delta_time.mul_f32(time_scale));
+            let mut unscaled_delta = self.world.write_resource::<UnscaledDeltaTime>();
+            *unscaled_delta = UnscaledDeltaTime(delta_time);
+        }
+
+        self.play_back_events();
+        for autopilot in self.autopilots.iter_mut() {
+            autopilot.run_now(&self.world);
+        }
+        // Input runs on its own so unpausing still works while everything else is frozen
+        self.input_system.run_now(&self.world);
+        self.menu_system.run_now(&self.world);
+        if !self.world.read_resource::<Paused>().0 {
+            // Sequential so entities get created in the same order every time and seeded runs
+            // play out the same
+            self.update_dispatcher.dispatch_seq(&self.world);
+        }
+        self.audio_system.run_now(&self.world);
+        self.world.maintain();
+        self.tick_count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use specs::{Join, WorldExt};
+    use winit::event::VirtualKeyCode;
+
+    use super::Simulation;
+    use crate::game::{
+        component::{Player, Transform},
+        input::{Action, ScriptedInput, WinitInputSource},
+        menu::{ActiveMenu, MenuScreen},
+        resource::{GameState, KeyboardEvent, Paused, Score},
+    };
+
+    const TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+    #[test]
+    fn test_scripted_run() {
+        let script = ScriptedInput::default()
+            .tap(1, 0, Action::Confirm)
+            .press(5, 0, Action::Thrust)
+            .release(60, 0, Action::Thrust);
+        let mut simulation = Simulation::new((1280, 720), vec![Box::new(script)]);
+
+        for _ in 0..61 {
+            simulation.tick(TICK);
+        }
+
+        let world = simulation.world();
+        assert!(matches!(
+            *world.read_resource::<GameState>(),
+            GameState::GameStatePlay { .. }
+        ));
+        let players = world.read_storage::<Player>();
+        let transforms = world.read_storage::<Transform>();
+        let (_, transform) = (&players, &transforms).join().next().unwrap();
+        // The ship starts out facing up
+        assert!(transform.position.y > 0.0);
+    }
+
+    #[test]
+    fn test_restart_from_pause_menu() {
+        let script = ScriptedInput::default()
+            .tap(1, 0, Action::Confirm)
+            .tap(120, 0, Action::Pause)
+            // Down to Restart
+            .tap(122, 0, Action::Brake)
+            .tap(124, 0, Action::Confirm);
+        let mut simulation = Simulation::new((1280, 720), vec![Box::new(script)]);
+
+        for _ in 0..121 {
+            simulation.tick(TICK);
+        }
+        {
+            let world = simulation.world();
+            assert!(world.read_resource::<Paused>().0);
+            let menu = world.read_resource::<ActiveMenu>();
+            assert_eq!(menu.0.as_ref().unwrap().screen, MenuScreen::Pause);
+        }
+
+        // Straight into the new run, without a game over in between
+        for _ in 121..128 {
+            simulation.tick(TICK);
+            assert!(!matches!(
+                *simulation.world().read_resource::<GameState>(),
+                GameState::GameStateEnd {}
+            ));
+        }
+        let world = simulation.world();
+        assert!(!world.read_resource::<Paused>().0);
+        assert!(world.read_resource::<ActiveMenu>().0.is_none());
+        assert!(matches!(
+            *world.read_resource::<GameState>(),
+            GameState::GameStatePlay { .. }
+        ));
+        // A couple of ticks into the new run
+        assert!(world.read_resource::<Score>().points < 1.0);
+    }
+
+    fn positions(simulation: &Simulation) -> Vec<(f32, f32)> {
+        let transforms = simulation.world().read_storage::<Transform>();
+        transforms
+            .join()
+            .map(|transform| (transform.position.x, transform.position.y))
+            .collect()
+    }
+
+    #[test]
+    fn test_replay_plays_back_the_same() {
+        let mut recorded = Simulation::new((1280, 720), vec![Box::new(WinitInputSource {})]);
+        recorded.set_seed(7);
+        recorded.start_recording(60);
+        for tick in 0..600 {
+            match tick {
+                1 => recorded.send_keyboard(KeyboardEvent::Pressed(VirtualKeyCode::Return)),
+                2 => recorded.send_keyboard(KeyboardEvent::Released(VirtualKeyCode::Return)),
+                30 => recorded.send_keyboard(KeyboardEvent::Pressed(VirtualKeyCode::W)),
+                90 => recorded.send_keyboard(KeyboardEvent::Released(VirtualKeyCode::W)),
+                _ => (),
+            }
+            recorded.tick(TICK);
+        }
+        let replay = recorded.take_recording().unwrap();
+        assert_eq!(replay.events.len(), 4);
+
+        let mut played = Simulation::new((640, 480), vec![Box::new(WinitInputSource {})]);
+        played.play_replay(replay);
+        for _ in 0..600 {
+            played.tick(TICK);
+        }
+
+        assert!(played.is_replay_finished());
+        let expected = positions(&recorded);
+        assert!(expected.len() > 1);
+        assert_eq!(positions(&played), expected);
+    }
+}
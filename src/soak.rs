@@ -0,0 +1,106 @@
+use std::{error::Error, fmt};
+
+use crate::batch::{run_batch, BatchConfig, RunRecord};
+
+#[derive(Debug, Default)]
+pub struct SoakReport {
+    // Seconds survived per run, in the order they were played
+    pub survival_times: Vec<f32>,
+    pub timed_out: usize,
+}
+
+impl SoakReport {
+    pub fn from_records(records: &[RunRecord]) -> Self {
+        Self {
+            survival_times: records.iter().map(|record| record.survival_time).collect(),
+            timed_out: records.iter().filter(|record| record.timed_out).count(),
+        }
+    }
+
+    pub fn mean(&self) -> f32 {
+        if self.survival_times.is_empty() {
+            return 0.0;
+        }
+        self.survival_times.iter().sum::<f32>() / self.survival_times.len() as f32
+    }
+
+    // Nearest rank percentile, `fraction` in 0..=1
+    pub fn percentile(&self, fraction: f32) -> f32 {
+        if self.survival_times.is_empty() {
+            return 0.0;
+        }
+        let mut sorted = self.survival_times.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let rank = (fraction.clamp(0.0, 1.0) * (sorted.len() - 1) as f32).round() as usize;
+        sorted[rank]
+    }
+}
+
+impl fmt::Display for SoakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} runs, {} timed out",
+            self.survival_times.len(),
+            self.timed_out
+        )?;
+        writeln!(f, "mean   {:8.1}s", self.mean())?;
+        for (label, fraction) in [
+            ("min", 0.0),
+            ("p10", 0.1),
+            ("median", 0.5),
+            ("p90", 0.9),
+            ("max", 1.0),
+        ] {
+            writeln!(f, "{:<6} {:8.1}s", label, self.percentile(fraction))?;
+        }
+        Ok(())
+    }
+}
+
+// Plays the batch and boils it down to survival times
+pub fn run_soak(config: &BatchConfig) -> Result<SoakReport, Box<dyn Error>> {
+    Ok(SoakReport::from_records(&run_batch(config)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_soak, SoakReport};
+    use crate::batch::BatchConfig;
+
+    #[test]
+    fn test_report_stats() {
+        let report = SoakReport {
+            survival_times: vec![4.0, 1.0, 3.0, 2.0, 5.0],
+            timed_out: 0,
+        };
+        assert_eq!(report.mean(), 3.0);
+        assert_eq!(report.percentile(0.0), 1.0);
+        assert_eq!(report.percentile(0.5), 3.0);
+        assert_eq!(report.percentile(1.0), 5.0);
+    }
+
+    #[test]
+    fn test_short_soak() {
+        let report = run_soak(&BatchConfig {
+            runs: 2,
+            max_ticks_per_run: 60 * 30,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(report.survival_times.len(), 2);
+        assert!(report.survival_times.iter().all(|time| *time > 0.0));
+    }
+
+    // Thousands of runs, takes a while. `cargo test --release soak -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn soak_autopilot() {
+        let report = run_soak(&BatchConfig {
+            runs: 1000,
+            ..Default::default()
+        })
+        .unwrap();
+        println!("{}", report);
+    }
+}